@@ -26,7 +26,7 @@ fn main() -> Result<(), GridError> {
 
     println!("添加了 {} 个单元格", grid.get_cells_count());
 
-    let cells = vec![vec![cell1, cell2], vec![cell3, cell4]];
+    let cells = [vec![cell1, cell2], vec![cell3, cell4]];
 
     // 打印网格布局
     println!("   网格布局:");
@@ -81,9 +81,8 @@ fn main() -> Result<(), GridError> {
 
     // 6. 展示邻居关系
     println!("\n6. 邻居关系:");
-    for y in 0..height {
-        for x in 0..width {
-            let current = cells[y][x];
+    for row in &cells {
+        for &current in row {
             let neighbors = grid.get_neighbors(current);
             println!("   {:?}: neighbors = {:?}", current, neighbors);
         }
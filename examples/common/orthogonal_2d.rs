@@ -0,0 +1,230 @@
+//! # 正交2D示例的网格构建器与瓷砖集
+//!
+//! `orthogonal_2d_wfc`示例与集成测试共用这里的定义，保证两者使用同一份瓷砖规则。
+//! 网格按东、南、西、北顺序创建边，边界使用虚拟节点，
+//! 因此`neighbors()`返回 [北, 西, 南, 东]，与瓷砖边数据的顺序一致。
+
+/**
+ * @file orthogonal_2d.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief 正交2D网格构建器与方形瓷砖集
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+use rlwfc::{
+    Cell, CellId, GridBuilder, GridError, GridSystem, Tile, TileId, TileSet, TileSetVirtual,
+};
+use std::collections::HashMap;
+
+// =============================================================================
+// 正交2D网格构建器
+// =============================================================================
+
+/// 正交2D网格构建器，对应C++的Orthogonal2DGrid
+pub struct Orthogonal2DGridBuilder {
+    width: usize,
+    height: usize,
+    coords: HashMap<CellId, Vec<i32>>,
+}
+
+impl Orthogonal2DGridBuilder {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            coords: HashMap::new(),
+        }
+    }
+}
+
+impl GridBuilder for Orthogonal2DGridBuilder {
+    fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
+        println!("构建 {}x{} 正交2D网格...", self.width, self.height);
+
+        // Step 1: 创建所有单元格
+        let mut cells = vec![vec![]; self.height];
+        for (y, row) in cells.iter_mut().enumerate() {
+            *row = Vec::with_capacity(self.width);
+            for x in 0..self.width {
+                let cell_id = grid.add_cell_with_name(
+                    Cell::with_id((y * self.width + x) as u32),
+                    format!("cell_{}_{}", x, y),
+                );
+                self.coords.insert(cell_id, vec![x as i32, y as i32]);
+                row.push(cell_id);
+            }
+        }
+
+        // Step 2: 按照WFC库的要求创建边：东、南、西、北顺序
+        // 这样neighbors()会返回：[北, 西, 南, 东] (petgraph逆序)
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let current = cells[y][x];
+
+                // 1. 东边 (向右)
+                if x < self.width - 1 {
+                    grid.create_edge(current, Some(cells[y][x + 1]))?;
+                } else {
+                    grid.create_edge(current, None)?;
+                }
+
+                // 2. 南边 (向下)
+                if y < self.height - 1 {
+                    grid.create_edge(current, Some(cells[y + 1][x]))?;
+                } else {
+                    grid.create_edge(current, None)?;
+                }
+
+                // 3. 西边 (向左)
+                if x > 0 {
+                    grid.create_edge(current, Some(cells[y][x - 1]))?;
+                } else {
+                    grid.create_edge(current, None)?;
+                }
+
+                // 4. 北边 (向上)
+                if y > 0 {
+                    grid.create_edge(current, Some(cells[y - 1][x]))?;
+                } else {
+                    grid.create_edge(current, None)?;
+                }
+            }
+        }
+
+        println!(
+            "网格构建完成：{} 个单元格，{} 条边",
+            grid.get_cells_count(),
+            grid.get_edges_count()
+        );
+        Ok(())
+    }
+
+    fn get_dimensions(&self) -> Vec<usize> {
+        vec![self.width, self.height]
+    }
+
+    fn get_grid_type_name(&self) -> &'static str {
+        "Orthogonal2DGrid"
+    }
+
+    fn cell_coordinates(&self) -> Option<HashMap<CellId, Vec<i32>>> {
+        Some(self.coords.clone())
+    }
+}
+
+// =============================================================================
+// 方形瓷砖集，对应C++的SquareTileSet
+// =============================================================================
+
+/// 方形瓷砖集，对应C++的SquareTileSet
+pub struct SquareTileSet {
+    tiles: TileSet<i32>,
+}
+
+impl SquareTileSet {
+    pub fn new() -> Self {
+        Self {
+            tiles: TileSet::new(),
+        }
+    }
+}
+
+impl TileSetVirtual<i32> for SquareTileSet {
+    fn build_tile_set(&mut self) -> Result<(), GridError> {
+        self.tiles.clear();
+
+        // 添加基础瓷砖，用数字表示连接类型
+        // 0 = 空白，1 = 路径
+
+        // 全空白瓷砖
+        self.tiles.add_tile(vec![0, 0, 0, 0], 1); // [北, 西, 南, 东]
+
+        // 直线路径瓷砖
+        self.tiles.add_tile(vec![1, 0, 1, 0], 1); // 垂直路径
+        self.tiles.add_tile(vec![0, 1, 0, 1], 1); // 水平路径
+
+        // 端点
+        // self.tiles.add_tile(vec![1, 0, 0, 0], 1);
+        // self.tiles.add_tile(vec![0, 1, 0, 0], 1);
+        // self.tiles.add_tile(vec![0, 0, 1, 0], 1);
+        // self.tiles.add_tile(vec![0, 0, 0, 1], 1);
+
+        // 转角路径瓷砖
+        // self.tiles.add_tile(vec![1, 1, 0, 0], 1);  // 左上角
+        // self.tiles.add_tile(vec![1, 0, 0, 1], 1);  // 右上角
+        // self.tiles.add_tile(vec![0, 1, 1, 0], 1);  // 左下角
+        // self.tiles.add_tile(vec![0, 0, 1, 1], 1);  // 右下角
+
+        // T型路径瓷砖
+        self.tiles.add_tile(vec![1, 1, 1, 0], 1); // 向右开口的T
+        self.tiles.add_tile(vec![1, 0, 1, 1], 1); // 向左开口的T
+        self.tiles.add_tile(vec![0, 1, 1, 1], 1); // 向上开口的T
+        self.tiles.add_tile(vec![1, 1, 0, 1], 1); // 向下开口的T
+
+        // 十字路口瓷砖
+        self.tiles.add_tile(vec![1, 1, 1, 1], 1); // 全连通
+
+        println!("瓷砖集构建完成：{} 种瓷砖", self.tiles.get_tile_count());
+        Ok(())
+    }
+
+    fn judge_possibility(&self, neighbor_possibilities: &[Vec<TileId>], candidate: TileId) -> bool {
+        let candidate_tile = match self.tiles.get_tile(candidate) {
+            Some(tile) => tile,
+            None => return false,
+        };
+
+        // 检查候选瓷砖与所有邻居的兼容性
+        // neighbor_possibilities的索引对应：[北, 西, 南, 东]
+        for (direction_index, neighbor_tiles) in neighbor_possibilities.iter().enumerate() {
+            if neighbor_tiles.is_empty() {
+                continue;
+            }
+
+            let mut edge_compatible = false;
+            let candidate_edge = candidate_tile.edges[direction_index];
+
+            for &neighbor_tile_id in neighbor_tiles {
+                if let Some(neighbor_tile) = self.tiles.get_tile(neighbor_tile_id) {
+                    // 计算邻居瓷砖对应方向的边索引
+                    // 边数据顺序：[北, 西, 南, 东]
+                    let neighbor_edge_index = match direction_index {
+                        0 => 2, // 北边 -> 邻居的南边
+                        1 => 3, // 西边 -> 邻居的东边
+                        2 => 0, // 南边 -> 邻居的北边
+                        3 => 1, // 东边 -> 邻居的西边
+                        _ => continue,
+                    };
+
+                    let neighbor_edge = neighbor_tile.edges[neighbor_edge_index];
+
+                    // 相邻边必须相等才兼容
+                    if candidate_edge == neighbor_edge {
+                        edge_compatible = true;
+                        break;
+                    }
+                }
+            }
+
+            if !edge_compatible {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn get_tile(&self, tile_id: TileId) -> Option<&Tile<i32>> {
+        self.tiles.get_tile(tile_id)
+    }
+
+    fn get_tile_count(&self) -> usize {
+        self.tiles.get_tile_count()
+    }
+
+    fn get_all_tile_ids(&self) -> Vec<TileId> {
+        self.tiles.get_all_tile_ids()
+    }
+}
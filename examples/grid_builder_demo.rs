@@ -86,14 +86,14 @@ impl GridBuilder for Orthogonal2DBuilder {
 
         // Step 1: 创建所有单元格
         let mut cells = vec![vec![]; self.height];
-        for y in 0..self.height {
-            cells[y] = Vec::with_capacity(self.width);
+        for (y, row) in cells.iter_mut().enumerate() {
+            *row = Vec::with_capacity(self.width);
             for x in 0..self.width {
                 let cell_id = grid.add_cell_with_name(
                     Cell::with_id((y * self.width + x) as u32),
                     format!("cell_{}_{}", x, y),
                 );
-                cells[y].push(cell_id);
+                row.push(cell_id);
             }
         }
 
@@ -124,6 +124,11 @@ impl GridBuilder for Orthogonal2DBuilder {
     fn get_grid_type_name(&self) -> &'static str {
         "Orthogonal2D"
     }
+
+    fn estimated_capacity(&self) -> (usize, usize) {
+        // 每个单元格最多有东、南两条出边
+        (self.width * self.height, 2 * self.width * self.height)
+    }
 }
 
 // =============================================================================
@@ -147,7 +152,10 @@ impl RingGridBuilder {
 impl GridBuilder for RingGridBuilder {
     fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
         if self.size < 3 {
-            return Err(GridError::InvalidDirection); // 环形至少需要3个节点
+            return Err(GridError::BuilderError(format!(
+                "ring grid needs at least 3 nodes, got {}",
+                self.size
+            )));
         }
 
         // 创建所有单元格
@@ -354,3 +362,23 @@ fn validate_and_report(grid: &GridSystem, grid_type: &str) {
         Err(e) => println!("   ❌ {}结构验证失败: {:?}", grid_type, e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_grid_rejects_too_few_nodes() {
+        let Err(error) = GridSystem::from_builder(RingGridBuilder::new(2)) else {
+            panic!("ring grid with 2 nodes should be rejected");
+        };
+        match &error {
+            GridError::BuilderError(message) => assert!(message.contains("at least 3")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(error.to_string().contains("at least 3"));
+
+        let grid = GridSystem::from_builder(RingGridBuilder::new(3)).unwrap();
+        assert_eq!(grid.get_cells_count(), 3);
+    }
+}
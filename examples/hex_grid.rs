@@ -0,0 +1,143 @@
+//! # 六边形网格示例
+//!
+//! 这个示例展示如何使用`DirectionHex`和轴向坐标`(q, r)`构建菱形六边形地图。
+//!
+//! ## 边创建顺序
+//!
+//! 每个单元格按固定顺序创建六条边：西北 → 西 → 西南 → 东南 → 东 → 东北。
+//! 地图边界上缺失的方向使用`create_edge(cell, None)`创建虚拟节点占位，
+//! 保证`neighbors()`始终返回 [东北, 东, 东南, 西南, 西, 西北]，
+//! 与`DirectionHex`的索引映射一致。
+
+/**
+ * @file hex_grid.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief 轴向坐标六边形网格构建器与DirectionHex方向查询示例
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+use rlwfc::{Cell, CellId, DirectionHex, DirectionTrait, GridBuilder, GridError, GridSystem};
+use std::collections::HashMap;
+
+// =============================================================================
+// 六边形网格构建器
+// =============================================================================
+
+/// 菱形六边形网格构建器，q向东、r向东南
+struct HexGridBuilder {
+    width: i32,
+    height: i32,
+    coords: HashMap<CellId, Vec<i32>>,
+}
+
+impl HexGridBuilder {
+    fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            coords: HashMap::new(),
+        }
+    }
+
+    fn contains(&self, q: i32, r: i32) -> bool {
+        (0..self.width).contains(&q) && (0..self.height).contains(&r)
+    }
+
+    fn index(&self, q: i32, r: i32) -> usize {
+        (r * self.width + q) as usize
+    }
+}
+
+impl GridBuilder for HexGridBuilder {
+    fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
+        let mut cells = Vec::with_capacity((self.width * self.height) as usize);
+
+        // Step 1: 创建所有单元格
+        for r in 0..self.height {
+            for q in 0..self.width {
+                let cell_id = grid.add_cell_with_name(
+                    Cell::with_id(self.index(q, r) as u32),
+                    format!("hex_{}_{}", q, r),
+                );
+                self.coords.insert(cell_id, vec![q, r]);
+                cells.push(cell_id);
+            }
+        }
+
+        // Step 2: 按DirectionHex::all_directions()的固定顺序创建边
+        for r in 0..self.height {
+            for q in 0..self.width {
+                let current = cells[self.index(q, r)];
+                for direction in DirectionHex::all_directions() {
+                    let (dq, dr) = direction.axial_offset();
+                    let (nq, nr) = (q + dq, r + dr);
+                    let target = self.contains(nq, nr).then(|| cells[self.index(nq, nr)]);
+                    grid.create_edge(current, target)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_dimensions(&self) -> Vec<usize> {
+        vec![self.width as usize, self.height as usize]
+    }
+
+    fn get_grid_type_name(&self) -> &'static str {
+        "HexRhombusGrid"
+    }
+
+    fn cell_coordinates(&self) -> Option<HashMap<CellId, Vec<i32>>> {
+        Some(self.coords.clone())
+    }
+}
+
+// =============================================================================
+// 主函数
+// =============================================================================
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("=== 六边形网格示例 ===\n");
+
+    let grid = GridSystem::from_builder(HexGridBuilder::new(4, 4))?;
+    println!(
+        "单元格（含虚拟节点）: {}, 边: {}",
+        grid.get_cells_count(),
+        grid.get_edges_count()
+    );
+
+    // 内部单元格的六个方向都是真实邻居
+    let center = grid.get_cell_at(&[1, 1]).ok_or(GridError::NodeNotFound)?;
+    println!("\n内部单元格 {:?} 的邻居:", center);
+    for direction in DirectionHex::all_directions() {
+        let neighbor = grid
+            .get_neighbor_by_direction(center, direction)
+            .ok_or(GridError::InvalidDirection)?;
+        println!(
+            "  {:>9}: {:?} 坐标 {:?}",
+            direction.name(),
+            neighbor,
+            grid.get_cell_coords(neighbor)
+        );
+    }
+
+    // 角落单元格部分方向落在边界上
+    let corner = grid.get_cell_at(&[0, 0]).ok_or(GridError::NodeNotFound)?;
+    println!("\n角落单元格 {:?} 的邻居:", corner);
+    for direction in DirectionHex::all_directions() {
+        let neighbor = grid
+            .get_neighbor_by_direction(corner, direction)
+            .ok_or(GridError::InvalidDirection)?;
+        if grid.is_virtual_node(neighbor) {
+            println!("  {:>9}: 边界", direction.name());
+        } else {
+            println!("  {:>9}: {:?}", direction.name(), neighbor);
+        }
+    }
+
+    println!("\n=== 示例完成 ===");
+    Ok(())
+}
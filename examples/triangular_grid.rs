@@ -0,0 +1,148 @@
+//! # 三角形网格示例
+//!
+//! 这个示例展示如何使用`DirectionTri`构建`width × height`的三角形网格。
+//!
+//! ## 朝向与边创建顺序
+//!
+//! 每行内尖顶朝上和朝下的三角形交替出现，由`TriOrientation::at(x, y)`决定。
+//! 方向按三角形自身的边命名（左、右、底边），因此无论朝向如何，每个单元格都按
+//! 底边 → 右 → 左的固定顺序创建三条边，`neighbors()`始终返回 [左, 右, 底边]。
+//! 朝向只影响底边邻居的位置：朝上三角形在正下方，朝下三角形在正上方。
+
+/**
+ * @file triangular_grid.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief 三角形网格构建器与DirectionTri方向查询示例
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+use rlwfc::{
+    Cell, CellId, DirectionTrait, DirectionTri, GridBuilder, GridError, GridSystem, TriOrientation,
+};
+use std::collections::HashMap;
+
+// =============================================================================
+// 三角形网格构建器
+// =============================================================================
+
+/// 三角形网格构建器，x向右、y向下，`(0, 0)`尖顶朝上
+struct TriangularGridBuilder {
+    width: i32,
+    height: i32,
+    coords: HashMap<CellId, Vec<i32>>,
+}
+
+impl TriangularGridBuilder {
+    fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            coords: HashMap::new(),
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        (0..self.width).contains(&x) && (0..self.height).contains(&y)
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+}
+
+impl GridBuilder for TriangularGridBuilder {
+    fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
+        let mut cells = Vec::with_capacity((self.width * self.height) as usize);
+
+        // Step 1: 创建所有单元格
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell_id = grid.add_cell_with_name(
+                    Cell::with_id(self.index(x, y) as u32),
+                    format!("tri_{}_{}", x, y),
+                );
+                self.coords.insert(cell_id, vec![x, y]);
+                cells.push(cell_id);
+            }
+        }
+
+        // Step 2: 按DirectionTri::all_directions()的固定顺序创建边，偏移由朝向决定
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let current = cells[self.index(x, y)];
+                let orientation = TriOrientation::at(x, y);
+                for direction in DirectionTri::all_directions() {
+                    let (dx, dy) = direction.offset(orientation);
+                    let (nx, ny) = (x + dx, y + dy);
+                    let target = self.contains(nx, ny).then(|| cells[self.index(nx, ny)]);
+                    grid.create_edge(current, target)?;
+                }
+            }
+        }
+
+        grid.debug_assert_direction_consistency::<DirectionTri>();
+        Ok(())
+    }
+
+    fn get_dimensions(&self) -> Vec<usize> {
+        vec![self.width as usize, self.height as usize]
+    }
+
+    fn get_grid_type_name(&self) -> &'static str {
+        "TriangularGrid"
+    }
+
+    fn cell_coordinates(&self) -> Option<HashMap<CellId, Vec<i32>>> {
+        Some(self.coords.clone())
+    }
+}
+
+// =============================================================================
+// 主函数
+// =============================================================================
+
+fn print_neighbors(grid: &GridSystem, x: i32, y: i32) -> Result<(), GridError> {
+    let cell = grid.get_cell_at(&[x, y]).ok_or(GridError::NodeNotFound)?;
+    println!(
+        "\n三角形 ({}, {}) 朝向 {:?} 的邻居:",
+        x,
+        y,
+        TriOrientation::at(x, y)
+    );
+    for direction in DirectionTri::all_directions() {
+        let neighbor = grid
+            .get_neighbor_by_direction(cell, direction)
+            .ok_or(GridError::InvalidDirection)?;
+        if grid.is_virtual_node(neighbor) {
+            println!("  {:>5}: 边界", direction.name());
+        } else {
+            println!(
+                "  {:>5}: {:?} 坐标 {:?}",
+                direction.name(),
+                neighbor,
+                grid.get_cell_coords(neighbor)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("=== 三角形网格示例 ===\n");
+
+    let grid = GridSystem::from_builder(TriangularGridBuilder::new(6, 3))?;
+    println!(
+        "单元格（含虚拟节点）: {}, 边: {}",
+        grid.get_cells_count(),
+        grid.get_edges_count()
+    );
+
+    // 朝上的三角形与正下方朝下的三角形共享底边
+    print_neighbors(&grid, 2, 0)?;
+    print_neighbors(&grid, 2, 1)?;
+
+    println!("\n=== 示例完成 ===");
+    Ok(())
+}
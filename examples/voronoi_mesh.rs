@@ -0,0 +1,368 @@
+//! # Voronoi不规则网格示例
+//!
+//! 这个示例展示如何在非网格拓扑上运行WFC：以一组2D种子点为Voronoi单元，
+//! 两个单元相邻当且仅当它们的种子点在Delaunay三角剖分中共享一条边。
+//!
+//! ## 边标签
+//!
+//! 不规则网格中各单元格的邻居数量不同，无法使用固定的方向索引。
+//! 每条边在`GraphEdge.edge_type`中记录邻居相对于当前单元的方位（"E"、"NE"、"N"等八个方位），
+//! 瓷砖集通过`judge_possibility_labeled`按方位而不是位置判断约束。
+//!
+//! 每对相邻单元之间都创建方向相反的两条边，约束才能沿两个方向传播。
+
+/**
+ * @file voronoi_mesh.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief Delaunay邻接构建的不规则网格与按边标签约束的WFC示例
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rlwfc::{
+    Cell, CellId, DefaultInitializer, GraphEdge, GridBuilder, GridError, GridSystem, Tile, TileId,
+    TileSet, TileSetVirtual, WfcConfig, WfcManager,
+};
+use std::collections::{BTreeSet, HashMap};
+
+// =============================================================================
+// Delaunay邻接
+// =============================================================================
+
+/// 用空外接圆判定枚举Delaunay三角形，返回相邻种子点对`(i, j)`，`i < j`
+///
+/// 复杂度为O(n⁴)，只适合示例规模的点集；大规模点集应使用专门的三角剖分库。
+fn delaunay_adjacency(points: &[(f64, f64)]) -> BTreeSet<(usize, usize)> {
+    let mut pairs = BTreeSet::new();
+    let n = points.len();
+    for i in 0..n {
+        for j in i + 1..n {
+            for k in j + 1..n {
+                let Some((center, radius_sq)) = circumcircle(points[i], points[j], points[k])
+                else {
+                    continue;
+                };
+                let empty = (0..n).filter(|&m| m != i && m != j && m != k).all(|m| {
+                    let (dx, dy) = (points[m].0 - center.0, points[m].1 - center.1);
+                    dx * dx + dy * dy >= radius_sq
+                });
+                if empty {
+                    pairs.extend([(i, j), (i, k), (j, k)]);
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// 三点的外接圆圆心和半径平方，三点共线时返回`None`
+fn circumcircle(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> Option<((f64, f64), f64)> {
+    let d = 2.0 * (a.0 * (b.1 - c.1) + b.0 * (c.1 - a.1) + c.0 * (a.1 - b.1));
+    if d.abs() < 1e-9 {
+        return None;
+    }
+    let (a2, b2, c2) = (
+        a.0 * a.0 + a.1 * a.1,
+        b.0 * b.0 + b.1 * b.1,
+        c.0 * c.0 + c.1 * c.1,
+    );
+    let center = (
+        (a2 * (b.1 - c.1) + b2 * (c.1 - a.1) + c2 * (a.1 - b.1)) / d,
+        (a2 * (c.0 - b.0) + b2 * (a.0 - c.0) + c2 * (b.0 - a.0)) / d,
+    );
+    let (dx, dy) = (a.0 - center.0, a.1 - center.1);
+    Some((center, dx * dx + dy * dy))
+}
+
+/// 从`from`看向`to`的八方位标签，y轴向上
+fn bearing(from: (f64, f64), to: (f64, f64)) -> &'static str {
+    const LABELS: [&str; 8] = ["E", "NE", "N", "NW", "W", "SW", "S", "SE"];
+    let angle = (to.1 - from.1).atan2(to.0 - from.0).to_degrees();
+    let sector = ((angle + 360.0 + 22.5) / 45.0) as usize % 8;
+    LABELS[sector]
+}
+
+// =============================================================================
+// 不规则网格构建器
+// =============================================================================
+
+/// 以种子点为单元、Delaunay邻接为边的网格构建器
+struct DelaunayGridBuilder {
+    points: Vec<(f64, f64)>,
+    coords: HashMap<CellId, Vec<i32>>,
+}
+
+impl DelaunayGridBuilder {
+    fn new(points: Vec<(f64, f64)>) -> Self {
+        Self {
+            points,
+            coords: HashMap::new(),
+        }
+    }
+
+    /// 在`[0, size)²`内生成确定的随机种子点
+    fn random(count: usize, size: f64, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let points = (0..count)
+            .map(|_| (rng.random::<f64>() * size, rng.random::<f64>() * size))
+            .collect();
+        Self::new(points)
+    }
+}
+
+impl GridBuilder for DelaunayGridBuilder {
+    fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
+        // Step 1: 每个种子点一个单元格
+        // 坐标取整后可能重合，而单元格坐标必须唯一，重合时只有先出现的种子点记录坐标
+        let mut used_coords = BTreeSet::new();
+        let mut cells = Vec::with_capacity(self.points.len());
+        for (index, &(x, y)) in self.points.iter().enumerate() {
+            let cell_id =
+                grid.add_cell_with_name(Cell::with_id(index as u32), format!("site_{}", index));
+            let coords = vec![x.round() as i32, y.round() as i32];
+            if used_coords.insert(coords.clone()) {
+                self.coords.insert(cell_id, coords);
+            }
+            cells.push(cell_id);
+        }
+
+        // Step 2: 每对相邻单元创建方向相反的两条边，标签为邻居的方位
+        for (a, b) in delaunay_adjacency(&self.points) {
+            let (pa, pb) = (self.points[a], self.points[b]);
+            grid.create_edge_with_data(
+                cells[a],
+                Some(cells[b]),
+                GraphEdge::with_type(bearing(pa, pb).to_string()),
+            )?;
+            grid.create_edge_with_data(
+                cells[b],
+                Some(cells[a]),
+                GraphEdge::with_type(bearing(pb, pa).to_string()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn get_dimensions(&self) -> Vec<usize> {
+        vec![self.points.len()]
+    }
+
+    fn get_grid_type_name(&self) -> &'static str {
+        "DelaunayGrid"
+    }
+
+    fn cell_coordinates(&self) -> Option<HashMap<CellId, Vec<i32>>> {
+        Some(self.coords.clone())
+    }
+}
+
+// =============================================================================
+// 按方位约束的瓷砖集
+// =============================================================================
+
+/// 天空在上、地面在下的两瓷砖集
+///
+/// 天空单元正上方（N、NE、NW）的邻居必须可以是天空，
+/// 地面单元正下方（S、SE、SW）的邻居必须可以是地面，因此地面不会悬在天空之上。
+struct GravityTileSet {
+    tiles: TileSet<&'static str>,
+}
+
+const SKY: TileId = 0;
+const GROUND: TileId = 1;
+
+impl GravityTileSet {
+    fn new() -> Self {
+        let mut tiles = TileSet::new();
+        tiles.add_tile(vec!["sky"], 2);
+        tiles.add_tile(vec!["ground"], 3);
+        Self { tiles }
+    }
+}
+
+impl TileSetVirtual<&'static str> for GravityTileSet {
+    fn build_tile_set(&mut self) -> Result<(), GridError> {
+        Ok(())
+    }
+
+    fn judge_possibility(&self, _: &[Vec<TileId>], _: TileId) -> bool {
+        // 不规则网格没有位置约定，约束全部由边标签版本判断
+        true
+    }
+
+    fn uses_edge_labels(&self) -> bool {
+        true
+    }
+
+    fn judge_possibility_labeled(
+        &self,
+        edge_labels: &[Option<&str>],
+        neighbor_possibilities: &[Vec<TileId>],
+        candidate: TileId,
+    ) -> bool {
+        let required = match candidate {
+            SKY => ["N", "NE", "NW"],
+            GROUND => ["S", "SE", "SW"],
+            _ => return false,
+        };
+        edge_labels
+            .iter()
+            .zip(neighbor_possibilities)
+            .filter(|(label, tiles)| {
+                label.is_some_and(|label| required.contains(&label)) && !tiles.is_empty()
+            })
+            .all(|(_, tiles)| tiles.contains(&candidate))
+    }
+
+    fn get_tile(&self, tile_id: TileId) -> Option<&Tile<&'static str>> {
+        self.tiles.get_tile(tile_id)
+    }
+
+    fn get_tile_count(&self) -> usize {
+        self.tiles.get_tile_count()
+    }
+
+    fn get_all_tile_ids(&self) -> Vec<TileId> {
+        self.tiles.get_all_tile_ids()
+    }
+}
+
+// =============================================================================
+// 主函数
+// =============================================================================
+
+/// 把种子点按坐标绘制到字符画中，y轴向上
+fn render_sites(manager: &WfcManager<&'static str>, size: usize) -> String {
+    let mut canvas = vec![vec!['.'; size]; size / 2];
+    let grid = manager.get_grid();
+    for cell in grid.get_all_cells() {
+        let Some(coords) = grid.get_cell_coords(cell) else {
+            continue;
+        };
+        let symbol = match manager.get_collapsed_cell_tile(cell) {
+            Ok(SKY) => 'o',
+            Ok(_) => '#',
+            Err(_) => '?',
+        };
+        let x = (coords[0].max(0) as usize).min(size - 1);
+        let row = (coords[1].max(0) as usize / 2).min(size / 2 - 1);
+        canvas[size / 2 - 1 - row][x] = symbol;
+    }
+    canvas
+        .into_iter()
+        .map(|line| line.into_iter().collect::<String>() + "\n")
+        .collect()
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("=== Voronoi不规则网格示例 ===\n");
+
+    let size = 40;
+    let grid = GridSystem::from_builder(DelaunayGridBuilder::random(30, size as f64, 7))?;
+    let degrees: Vec<usize> = grid
+        .get_all_cells()
+        .map(|cell| grid.get_neighbors(cell).len())
+        .collect();
+    println!(
+        "单元格: {}, 边: {}, 邻居数量: {} ~ {}",
+        grid.get_cells_count(),
+        grid.get_edges_count(),
+        degrees.iter().min().unwrap_or(&0),
+        degrees.iter().max().unwrap_or(&0)
+    );
+    println!("连通: {}", grid.is_connected());
+    println!("双向边完整: {}", grid.validate_undirected_pairs().is_ok());
+
+    let site = grid
+        .get_cell_by_name("site_0")
+        .ok_or(GridError::NodeNotFound)?;
+    println!("\nsite_0 的邻居方位:");
+    for (neighbor, label) in grid
+        .get_neighbors(site)
+        .into_iter()
+        .zip(grid.get_neighbor_edge_types(site))
+    {
+        println!("  {:>2}: {:?}", label.unwrap_or("-"), neighbor);
+    }
+
+    let config = WfcConfig {
+        random_seed: Some(3),
+        ..WfcConfig::default()
+    };
+    let mut manager = WfcManager::with_config(grid, Box::new(GravityTileSet::new()), config)?;
+    manager.initialize_with(&mut DefaultInitializer)?;
+    manager.run()?;
+
+    println!("\n生成结果（o: 天空, #: 地面）:");
+    print!("{}", render_sites(&manager, size));
+
+    println!("\n=== 示例完成 ===");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delaunay_grid_is_connected_and_undirected() {
+        for seed in 0..5 {
+            let grid =
+                GridSystem::from_builder(DelaunayGridBuilder::random(25, 50.0, seed)).unwrap();
+            assert!(grid.is_connected(), "seed {}", seed);
+            assert_eq!(grid.validate_undirected_pairs(), Ok(()), "seed {}", seed);
+
+            // 每条边都带有方位标签，反向边的方位与之相对
+            for cell in grid.get_all_cells() {
+                let neighbors = grid.get_neighbors(cell);
+                let labels = grid.get_neighbor_edge_types(cell);
+                assert!(!neighbors.is_empty());
+                for (neighbor, label) in neighbors.into_iter().zip(labels) {
+                    let back = grid
+                        .get_neighbors(neighbor)
+                        .iter()
+                        .position(|&n| n == cell)
+                        .unwrap();
+                    let back_label = grid.get_neighbor_edge_types(neighbor)[back];
+                    assert!(label.is_some() && back_label.is_some());
+                    assert_ne!(label, back_label);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gravity_constraints_hold() {
+        let grid = GridSystem::from_builder(DelaunayGridBuilder::random(30, 40.0, 7)).unwrap();
+        let config = WfcConfig {
+            random_seed: Some(3),
+            ..WfcConfig::default()
+        };
+        let mut manager =
+            WfcManager::with_config(grid, Box::new(GravityTileSet::new()), config).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        manager.run().unwrap();
+
+        let grid = manager.get_grid();
+        for cell in grid.get_all_cells() {
+            let tile = manager.get_collapsed_cell_tile(cell).unwrap();
+            for (neighbor, label) in grid
+                .get_neighbors(cell)
+                .into_iter()
+                .zip(grid.get_neighbor_edge_types(cell))
+            {
+                let neighbor_tile = manager.get_collapsed_cell_tile(neighbor).unwrap();
+                if tile == SKY && matches!(label, Some("N" | "NE" | "NW")) {
+                    assert_eq!(neighbor_tile, SKY);
+                }
+                if tile == GROUND && matches!(label, Some("S" | "SE" | "SW")) {
+                    assert_eq!(neighbor_tile, GROUND);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! # 3D体素网格示例
+//!
+//! 这个示例展示如何使用`Direction6`构建`width × height × depth`的三维网格。
+//!
+//! ## 边创建顺序
+//!
+//! 每个单元格按固定顺序创建六条边：上 → 下 → 东 → 南 → 西 → 北。
+//! 边界处使用`create_edge(cell, None)`创建虚拟节点占位，保证`neighbors()`
+//! 始终返回 [北, 西, 南, 东, 下, 上]，与`Direction6`的索引映射一致。
+
+/**
+ * @file voxel_3d_grid.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief 3D体素网格构建器与Direction6方向查询示例
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+use rlwfc::{Cell, CellId, Direction6, DirectionTrait, GridBuilder, GridError, GridSystem};
+use std::collections::HashMap;
+
+// =============================================================================
+// 3D网格构建器
+// =============================================================================
+
+/// 3D体素网格构建器，x向东、y向南、z向上
+struct Lattice3DGridBuilder {
+    width: usize,
+    height: usize,
+    depth: usize,
+    coords: HashMap<CellId, Vec<i32>>,
+}
+
+impl Lattice3DGridBuilder {
+    fn new(width: usize, height: usize, depth: usize) -> Self {
+        Self {
+            width,
+            height,
+            depth,
+            coords: HashMap::new(),
+        }
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.height + y) * self.width + x
+    }
+}
+
+impl GridBuilder for Lattice3DGridBuilder {
+    fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
+        let mut cells = Vec::with_capacity(self.width * self.height * self.depth);
+
+        // Step 1: 创建所有单元格
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let cell_id = grid.add_cell_with_name(
+                        Cell::with_id(self.index(x, y, z) as u32),
+                        format!("voxel_{}_{}_{}", x, y, z),
+                    );
+                    self.coords
+                        .insert(cell_id, vec![x as i32, y as i32, z as i32]);
+                    cells.push(cell_id);
+                }
+            }
+        }
+
+        // Step 2: 按上、下、东、南、西、北的固定顺序创建边
+        for z in 0..self.depth {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let current = cells[self.index(x, y, z)];
+                    let up = (z + 1 < self.depth).then(|| cells[self.index(x, y, z + 1)]);
+                    let down = (z > 0).then(|| cells[self.index(x, y, z - 1)]);
+                    let east = (x + 1 < self.width).then(|| cells[self.index(x + 1, y, z)]);
+                    let south = (y + 1 < self.height).then(|| cells[self.index(x, y + 1, z)]);
+                    let west = (x > 0).then(|| cells[self.index(x - 1, y, z)]);
+                    let north = (y > 0).then(|| cells[self.index(x, y - 1, z)]);
+
+                    grid.create_edge(current, up)?;
+                    grid.create_edge(current, down)?;
+                    grid.create_edge(current, east)?;
+                    grid.create_edge(current, south)?;
+                    grid.create_edge(current, west)?;
+                    grid.create_edge(current, north)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_dimensions(&self) -> Vec<usize> {
+        vec![self.width, self.height, self.depth]
+    }
+
+    fn get_grid_type_name(&self) -> &'static str {
+        "Lattice3DGrid"
+    }
+
+    fn cell_coordinates(&self) -> Option<HashMap<CellId, Vec<i32>>> {
+        Some(self.coords.clone())
+    }
+}
+
+// =============================================================================
+// 主函数
+// =============================================================================
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("=== 3D体素网格示例 ===\n");
+
+    let grid = GridSystem::from_builder(Lattice3DGridBuilder::new(3, 3, 3))?;
+    println!(
+        "单元格（含虚拟节点）: {}, 边: {}",
+        grid.get_cells_count(),
+        grid.get_edges_count()
+    );
+
+    // 中心体素的六个方向都是真实邻居
+    let center = grid
+        .get_cell_at(&[1, 1, 1])
+        .ok_or(GridError::NodeNotFound)?;
+    println!("\n中心体素 {:?} 的邻居:", center);
+    for direction in Direction6::all_directions() {
+        let neighbor = grid
+            .get_neighbor_by_direction(center, direction)
+            .ok_or(GridError::InvalidDirection)?;
+        println!(
+            "  {:>5}: {:?} 坐标 {:?}",
+            direction.name(),
+            neighbor,
+            grid.get_cell_coords(neighbor)
+        );
+    }
+
+    // 角落体素有三个方向落在边界上
+    let corner = grid
+        .get_cell_at(&[0, 0, 0])
+        .ok_or(GridError::NodeNotFound)?;
+    println!("\n角落体素 {:?} 的邻居:", corner);
+    for direction in Direction6::all_directions() {
+        let neighbor = grid
+            .get_neighbor_by_direction(corner, direction)
+            .ok_or(GridError::InvalidDirection)?;
+        if grid.is_virtual_node(neighbor) {
+            println!("  {:>5}: 边界", direction.name());
+        } else {
+            println!("  {:>5}: {:?}", direction.name(), neighbor);
+        }
+    }
+
+    println!("\n=== 示例完成 ===");
+    Ok(())
+}
@@ -114,6 +114,19 @@
 //! - 通过[`DirectionTrait`]映射方向到邻居索引
 //!
 //! 这使得可以通过方向名称直接查询邻居，而不需要额外的方向信息存储。
+//!
+//! ## 单元格数据
+//!
+//! [`GridSystem`]和[`GridBuilder`]都带有默认为`()`的类型参数`T`，对应每个
+//! [`Cell<T>`](Cell)上存储的应用数据，可通过[`GridSystem::get_cell`]读取。
+//! 携带数据的网格使用`GridSystem::<T>::default()`创建；由于边界虚拟节点也需要
+//! 占位数据，`T`必须实现`Default`。
+//!
+//! ## 序列化
+//!
+//! 启用`serde`特性后，[`GridSystem`]实现`Serialize`/`Deserialize`。序列化格式
+//! 保存显式的节点列表和按源节点分组的边端点，反序列化时按原顺序重新插入，
+//! 因此`CellId`和每个单元格的`get_neighbors()`顺序都与原网格一致。
 
 /**
  * @file grid_system.rs
@@ -126,9 +139,17 @@
  * @copyright Copyright (c) 2025
  */
 use crate::wfc_util::*;
+use petgraph::unionfind::UnionFind;
+use petgraph::visit::EdgeRef;
+use petgraph::Direction::Incoming;
 use petgraph::Graph;
-use std::collections::HashMap;
-use std::collections::HashSet;
+
+use crate::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 
 // =============================================================================
 // GridBuilder Trait - 对应C++的buildGridSystem虚函数
@@ -146,7 +167,7 @@ use std::collections::HashSet;
 ///
 /// ## 边创建顺序的重要性
 ///
-/// - **方向识别依赖**：`Direction4::to_neighbor_index()` 的映射依赖于固定的边创建顺序
+/// - **方向识别依赖**：`DirectionTrait::to_neighbor_index()` 的映射依赖于固定的边创建顺序
 /// - **petgraph特性**：利用 `neighbors()` 返回逆序的稳定性
 /// - **全局一致性**：所有单元格必须使用相同的边创建顺序
 ///
@@ -159,7 +180,7 @@ use std::collections::HashSet;
 /// 3. **西向边** (West) - 如果有西邻居
 /// 4. **北向边** (North) - 如果有北邻居
 ///
-/// 这样 `neighbors()` 将返回 `[北, 西, 南, 东]` (逆序)，符合 `Direction4` 的索引映射。
+/// 这样 `neighbors()` 将返回 `[北, 西, 南, 东]` (逆序)，符合 `Direction4Full` 的索引映射。
 ///
 /// ## 与原C++设计的对比
 ///
@@ -246,7 +267,7 @@ use std::collections::HashSet;
 ///     }
 /// }
 /// ```
-pub trait GridBuilder {
+pub trait GridBuilder<T = ()> {
     /// 构建网格系统，对应原C++的buildGridSystem()纯虚函数
     ///
     /// 这个方法是GridBuilder trait的核心，负责实际的网格构建逻辑。
@@ -266,7 +287,7 @@ pub trait GridBuilder {
     /// - 创建重复边时返回`GridError::EdgeAlreadyExists`
     /// - 尝试创建自循环时返回`GridError::SelfLoop`
     /// - 其他图操作错误
-    fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError>;
+    fn build_grid_system(&mut self, grid: &mut GridSystem<T>) -> Result<(), GridError>;
 
     /// 获取网格的维度信息（可选实现）
     ///
@@ -288,6 +309,31 @@ pub trait GridBuilder {
     fn get_grid_type_name(&self) -> &'static str {
         "CustomGrid"
     }
+
+    /// 获取构建过程中记录的单元格坐标（可选实现）
+    ///
+    /// 大多数构建器在创建单元格时就知道其坐标，通过这个方法可以把坐标
+    /// 交给网格系统，而不必事后从单元格名称中解析。
+    /// [`GridSystem::build_with`]和[`GridSystem::from_builder`]会在构建完成后
+    /// 调用它，并通过[`GridSystem::set_cell_coords`]写入坐标。
+    ///
+    /// 默认实现返回`None`，表示不提供坐标。构建器也可以在
+    /// `build_grid_system`中直接调用`set_cell_coords`。
+    fn cell_coordinates(&self) -> Option<HashMap<CellId, Vec<i32>>> {
+        None
+    }
+
+    /// 预计的`(单元格数, 边数)`（可选实现）
+    ///
+    /// [`GridSystem::from_builder`]据此用[`GridSystem::with_capacity`]的方式预分配图存储，
+    /// [`GridSystem::build_with`]则在现有网格上额外预留，避免大网格构建时反复扩容。
+    /// 估计值只影响性能：偏小时照常扩容，偏大时浪费部分内存。
+    /// 单元格数应包含边界占位的虚拟节点。
+    ///
+    /// 默认实现返回`(0, 0)`，不预分配。
+    fn estimated_capacity(&self) -> (usize, usize) {
+        (0, 0)
+    }
 }
 
 // =============================================================================
@@ -376,13 +422,14 @@ pub trait GridBuilder {
 /// [`get_all_cells()`]: GridSystem::get_all_cells
 /// [`get_cells_count()`]: GridSystem::get_cells_count
 /// [`build_with(builder)`]: GridSystem::build_with
-pub struct GridSystem {
+#[derive(Clone)]
+pub struct GridSystem<T = ()> {
     /// 底层图存储，使用有向图支持方向识别
     ///
     /// 这是整个网格系统的核心数据结构。使用petgraph的有向图来存储：
     /// - 节点：代表网格中的单元格
     /// - 边：代表单元格之间的有向连接
-    graph: WFCGraph,
+    graph: WFCGraph<T>,
 
     /// 可选的单元格名称映射，用于快速查找
     ///
@@ -392,60 +439,486 @@ pub struct GridSystem {
 
     /// 虚拟节点集合，用于存储边界虚拟节点
     virtual_nodes: HashSet<CellId>,
+
+    /// 单元格坐标元数据，由构建器提供
+    cell_coords: HashMap<CellId, Vec<i32>>,
+
+    /// 坐标到单元格的反向映射，用于按坐标查找
+    coord_lookup: HashMap<Vec<i32>, CellId>,
+
+    /// 反向邻接索引：目标单元格 -> 指向它的源单元格（按建边顺序）
+    ///
+    /// 在建边和删边时增量维护，使需要反向查找的方向查询只检查入边邻居，
+    /// 而不必遍历全部节点。
+    incoming_index: HashMap<CellId, Vec<CellId>>,
 }
 
 impl GridSystem {
     /// 创建新的网格系统
+    ///
+    /// 携带单元格数据的网格使用`GridSystem::<T>::default()`创建。
     pub fn new() -> Self {
-        Self {
-            graph: Graph::new(),
-            cell_lookup: HashMap::new(),
-            virtual_nodes: HashSet::new(),
-        }
+        Self::default()
     }
 
     /// 创建带容量的网格系统
     pub fn with_capacity(nodes: usize, edges: usize) -> Self {
+        Self::with_graph(Graph::with_capacity(nodes, edges))
+    }
+}
+
+impl<T: Default> GridSystem<T> {
+    /// 基于给定的底层图创建网格系统，其余映射均为空
+    fn with_graph(graph: WFCGraph<T>) -> Self {
         Self {
-            graph: Graph::with_capacity(nodes, edges),
+            graph,
             cell_lookup: HashMap::new(),
             virtual_nodes: HashSet::new(),
+            cell_coords: HashMap::new(),
+            coord_lookup: HashMap::new(),
+            incoming_index: HashMap::new(),
         }
     }
 
     /// 使用builder构建网格系统，对应原C++的多态buildGridSystem调用
-    pub fn build_with<T: GridBuilder>(&mut self, mut builder: T) -> Result<(), GridError> {
-        builder.build_grid_system(self)
+    pub fn build_with<B: GridBuilder<T>>(&mut self, mut builder: B) -> Result<(), GridError> {
+        let (nodes, edges) = builder.estimated_capacity();
+        self.graph.reserve_nodes(nodes);
+        self.graph.reserve_edges(edges);
+        builder.build_grid_system(self)?;
+        self.apply_builder_coordinates(&builder)
     }
 
     /// 创建新的网格系统并立即使用builder构建
-    pub fn from_builder<T: GridBuilder>(mut builder: T) -> Result<Self, GridError> {
-        let mut grid = Self::new();
+    pub fn from_builder<B: GridBuilder<T>>(mut builder: B) -> Result<Self, GridError> {
+        let (nodes, edges) = builder.estimated_capacity();
+        let mut grid = Self::with_graph(Graph::with_capacity(nodes, edges));
         builder.build_grid_system(&mut grid)?;
+        grid.apply_builder_coordinates(&builder)?;
         Ok(grid)
     }
 
+    /// 写入构建器提供的坐标元数据
+    fn apply_builder_coordinates<B: GridBuilder<T> + ?Sized>(
+        &mut self,
+        builder: &B,
+    ) -> Result<(), GridError> {
+        if let Some(coords) = builder.cell_coordinates() {
+            for (cell_id, cell_coords) in coords {
+                self.set_cell_coords(cell_id, cell_coords)?;
+            }
+        }
+        Ok(())
+    }
+
     // ==========================================================================
     // 基础图操作 - 对应原C++的核心方法
     // ==========================================================================
 
     /// 添加单元格，对应原C++中向cells_添加元素
-    pub fn add_cell(&mut self, cell_data: Cell) -> CellId {
+    pub fn add_cell(&mut self, cell_data: Cell<T>) -> CellId {
         self.graph.add_node(cell_data)
     }
 
     /// 添加带名称的单元格，支持按名称查找
-    pub fn add_cell_with_name(&mut self, cell_data: Cell, name: String) -> CellId {
+    pub fn add_cell_with_name(&mut self, cell_data: Cell<T>, name: String) -> CellId {
         let cell_id = self.add_cell(cell_data);
         self.cell_lookup.insert(name, cell_id);
         cell_id
     }
 
+    /// 批量添加单元格，按输入顺序返回创建的单元格ID
+    ///
+    /// 根据迭代器的长度提示预先为图预留容量，适合构建器一次性创建整层单元格。
+    pub fn add_cells(&mut self, cells: impl IntoIterator<Item = Cell<T>>) -> Vec<CellId> {
+        let cells = cells.into_iter();
+        self.graph.reserve_nodes(cells.size_hint().0);
+        cells.map(|cell_data| self.add_cell(cell_data)).collect()
+    }
+
+    /// 批量添加带名称的单元格，按输入顺序返回创建的单元格ID
+    pub fn add_named_cells(
+        &mut self,
+        cells: impl IntoIterator<Item = (Cell<T>, String)>,
+    ) -> Vec<CellId> {
+        let cells = cells.into_iter();
+        self.graph.reserve_nodes(cells.size_hint().0);
+        cells
+            .map(|(cell_data, name)| self.add_cell_with_name(cell_data, name))
+            .collect()
+    }
+
+    /// 删除单元格及其所有关联边
+    ///
+    /// 同时清理该单元格的名称、坐标和虚拟节点记录。
+    ///
+    /// # ⚠️ 重要：CellId重映射
+    ///
+    /// petgraph删除节点时会把最后一个节点移动到被删除的位置，因此最后一个
+    /// 单元格的`CellId`会改变。返回值中的重映射表列出所有`(旧ID, 新ID)`，
+    /// 调用方需要据此修正自己保存的`CellId`。网格内部的名称、坐标等映射已自动修正。
+    ///
+    /// 删除操作同样会打乱边的创建顺序，删除后方向查询可能不再可靠。
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok((Cell, remap))` - 被删除的单元格数据和CellId重映射表
+    ///
+    /// # 错误情况
+    ///
+    /// - `GridError::NodeNotFound` - 单元格不存在
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{Cell, GridSystem};
+    ///
+    /// let mut grid = GridSystem::new();
+    /// let a = grid.add_cell(Cell::with_id(0));
+    /// let b = grid.add_cell(Cell::with_id(1));
+    /// let c = grid.add_cell(Cell::with_id(2));
+    ///
+    /// let (removed, remap) = grid.remove_cell(a).unwrap();
+    /// assert_eq!(removed.id, Some(0));
+    /// assert_eq!(remap, vec![(c, a)]); // 原来的c现在使用a的ID
+    /// assert_eq!(grid.get_cells_count(), 2);
+    /// # let _ = b;
+    /// ```
+    pub fn remove_cell(&mut self, cell_id: CellId) -> Result<(Cell<T>, CellRemap), GridError> {
+        let last_id = CellId::new(self.graph.node_count().wrapping_sub(1));
+        let cell = self
+            .graph
+            .remove_node(cell_id)
+            .ok_or(GridError::NodeNotFound)?;
+
+        // 清理被删除单元格的元数据
+        self.cell_lookup.retain(|_, id| *id != cell_id);
+        self.virtual_nodes.remove(&cell_id);
+        if let Some(coords) = self.cell_coords.remove(&cell_id) {
+            self.coord_lookup.remove(&coords);
+        }
+
+        if last_id == cell_id {
+            self.rebuild_direction_index();
+            return Ok((cell, Vec::new()));
+        }
+
+        // 最后一个节点被移动到了cell_id的位置
+        self.remap_cell_metadata(last_id, cell_id);
+        self.rebuild_direction_index();
+        Ok((cell, vec![(last_id, cell_id)]))
+    }
+
+    /// 将元数据中的旧CellId替换为新CellId
+    fn remap_cell_metadata(&mut self, old_id: CellId, new_id: CellId) {
+        for id in self.cell_lookup.values_mut() {
+            if *id == old_id {
+                *id = new_id;
+            }
+        }
+        if self.virtual_nodes.remove(&old_id) {
+            self.virtual_nodes.insert(new_id);
+        }
+        if let Some(coords) = self.cell_coords.remove(&old_id) {
+            self.coord_lookup.insert(coords.clone(), new_id);
+            self.cell_coords.insert(new_id, coords);
+        }
+    }
+
+    /// 合并另一个网格系统，并在两者之间创建桥接边
+    ///
+    /// `other`的全部单元格、边、虚拟节点、名称和坐标都会导入当前网格。导入后
+    /// `other`中的单元格获得新的ID，返回值给出`旧ID -> 新ID`的完整映射。
+    ///
+    /// `connections`中的每一项`(cell, other_cell)`分别是当前网格中的单元格ID
+    /// 和`other`中的**旧**单元格ID，合并后会在两者之间创建一对相对的有向边
+    /// （先`cell -> other_cell`，再`other_cell -> cell`），不携带边数据。
+    ///
+    /// - 名称冲突时，导入的名称会追加`#2`、`#3`……后缀直到不再冲突
+    /// - 坐标冲突时保留当前网格中的坐标，导入单元格不记录该坐标；
+    ///   需要平移坐标时可以借助返回的映射调用[`set_cell_coords`](Self::set_cell_coords)
+    ///
+    /// # ⚠️ 重要：桥接边的顺序
+    ///
+    /// 导入的边按每个单元格原有的创建顺序重建，子网格内部的方向查询保持不变。
+    /// 但桥接边在所有已有边**之后**创建，会出现在`get_neighbors()`结果的最前面，
+    /// 把桥接单元格原有的邻居索引整体后移一位。因此桥接单元格上的方向查询和
+    /// 依赖位置的兼容性判断不再可靠；需要方向感知的桥接时，应在同一个
+    /// [`GridBuilder`]中按统一顺序建边，或为边标记类型并使用按标签判断的瓷砖集。
+    ///
+    /// # 错误情况
+    ///
+    /// 错误在修改当前网格之前检测，出错时当前网格保持不变：
+    ///
+    /// - `GridError::NodeNotFound` - 连接引用了不存在的单元格
+    /// - `GridError::EdgeAlreadyExists` - 同一连接重复出现
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{Cell, GridSystem};
+    ///
+    /// let mut left = GridSystem::new();
+    /// let a = left.add_cell_with_name(Cell::new(), "a".to_string());
+    /// let mut right = GridSystem::new();
+    /// let b = right.add_cell_with_name(Cell::new(), "a".to_string());
+    ///
+    /// let remap = left.merge(right, &[(a, b)]).unwrap();
+    /// assert_eq!(left.get_cells_count(), 2);
+    /// assert_eq!(left.get_neighbors(a), vec![remap[&b]]);
+    /// assert_eq!(left.get_cell_by_name("a#2"), Some(remap[&b]));
+    /// ```
+    pub fn merge(
+        &mut self,
+        other: GridSystem<T>,
+        connections: &[(CellId, CellId)],
+    ) -> Result<HashMap<CellId, CellId>, GridError> {
+        let mut seen = HashSet::new();
+        for &(cell, other_cell) in connections {
+            if !self.contains_cell(cell) || !other.contains_cell(other_cell) {
+                return Err(GridError::NodeNotFound);
+            }
+            if !seen.insert((cell, other_cell)) {
+                return Err(GridError::EdgeAlreadyExists);
+            }
+        }
+
+        // petgraph按插入逆序返回出边，逐节点反转即可还原创建顺序
+        let mut edges = Vec::with_capacity(other.graph.edge_count());
+        for cell_id in other.graph.node_indices() {
+            let start = edges.len();
+            edges.extend(
+                other
+                    .graph
+                    .edges(cell_id)
+                    .map(|edge| (cell_id, edge.target(), edge.weight().clone())),
+            );
+            edges[start..].reverse();
+        }
+
+        let GridSystem {
+            graph,
+            cell_lookup,
+            virtual_nodes,
+            cell_coords,
+            ..
+        } = other;
+        let (nodes, _) = graph.into_nodes_edges();
+        self.graph.reserve_nodes(nodes.len());
+        self.graph
+            .reserve_edges(edges.len() + 2 * connections.len());
+        let remap: HashMap<CellId, CellId> = nodes
+            .into_iter()
+            .enumerate()
+            .map(|(index, node)| (CellId::new(index), self.graph.add_node(node.weight)))
+            .collect();
+
+        for (from, to, edge) in edges {
+            self.graph.add_edge(remap[&from], remap[&to], edge);
+        }
+        self.virtual_nodes
+            .extend(virtual_nodes.iter().map(|id| remap[id]));
+
+        // 排序后再导入，使冲突后缀的分配与哈希顺序无关
+        let mut names: Vec<(String, CellId)> = cell_lookup.into_iter().collect();
+        names.sort_unstable();
+        for (name, cell_id) in names {
+            let mut unique = name.clone();
+            let mut suffix = 1;
+            while self.cell_lookup.contains_key(&unique) {
+                suffix += 1;
+                unique = format!("{}#{}", name, suffix);
+            }
+            self.cell_lookup.insert(unique, remap[&cell_id]);
+        }
+
+        let mut coords: Vec<(CellId, Vec<i32>)> = cell_coords.into_iter().collect();
+        coords.sort_unstable();
+        for (cell_id, cell_coords) in coords {
+            if !self.coord_lookup.contains_key(&cell_coords) {
+                self.set_cell_coords(remap[&cell_id], cell_coords)?;
+            }
+        }
+
+        for &(cell, other_cell) in connections {
+            let bridged = remap[&other_cell];
+            self.graph.add_edge(cell, bridged, GraphEdge::new());
+            self.graph.add_edge(bridged, cell, GraphEdge::new());
+        }
+
+        self.rebuild_direction_index();
+        Ok(remap)
+    }
+
+    /// 提取只包含指定单元格及其相互之间连边的独立网格
+    ///
+    /// 单元格按原`CellId`索引升序加入新网格，返回值中的映射把原ID映射到新ID；
+    /// 不存在的ID会被忽略。单元格数据、名称、坐标和虚拟节点标记随单元格保留。
+    ///
+    /// 每个单元格保留的出边维持原来的相对顺序，指向区域外单元格的边被丢弃。
+    /// 区域边界上的单元格因此会少掉部分邻居，`get_neighbors()`中其后的邻居索引前移；
+    /// 需要保持方向索引时，应把边界外的虚拟节点一并包含在`cells`中。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{Cell, GridSystem};
+    /// use std::collections::HashSet;
+    ///
+    /// let mut grid = GridSystem::new();
+    /// let a = grid.add_cell(Cell::new());
+    /// let b = grid.add_cell(Cell::new());
+    /// let c = grid.add_cell(Cell::new());
+    /// grid.create_edge_to(a, b).unwrap();
+    /// grid.create_edge_to(b, c).unwrap();
+    ///
+    /// let (sub, remap) = grid.subgraph(&HashSet::from([a, b]));
+    /// assert_eq!(sub.get_cells_count(), 2);
+    /// assert_eq!(sub.get_neighbors(remap[&a]), vec![remap[&b]]);
+    /// assert!(sub.get_neighbors(remap[&b]).is_empty());
+    /// ```
+    pub fn subgraph(&self, cells: &HashSet<CellId>) -> (GridSystem<T>, HashMap<CellId, CellId>)
+    where
+        T: Clone,
+    {
+        let mut included: Vec<CellId> = cells
+            .iter()
+            .copied()
+            .filter(|&cell_id| self.contains_cell(cell_id))
+            .collect();
+        included.sort_unstable();
+
+        let mut sub = GridSystem::with_graph(Graph::with_capacity(included.len(), 0));
+        let remap: HashMap<CellId, CellId> = included
+            .iter()
+            .map(|&cell_id| (cell_id, sub.graph.add_node(self.graph[cell_id].clone())))
+            .collect();
+
+        // petgraph按插入逆序返回出边，逐节点反转即可还原创建顺序
+        for &cell_id in &included {
+            let mut edges: Vec<_> = self
+                .graph
+                .edges(cell_id)
+                .filter_map(|edge| Some((*remap.get(&edge.target())?, edge.weight().clone())))
+                .collect();
+            edges.reverse();
+            for (target, edge) in edges {
+                sub.graph.add_edge(remap[&cell_id], target, edge);
+            }
+        }
+
+        sub.virtual_nodes.extend(
+            self.virtual_nodes
+                .iter()
+                .filter_map(|cell_id| remap.get(cell_id).copied()),
+        );
+        for (name, cell_id) in &self.cell_lookup {
+            if let Some(&new_id) = remap.get(cell_id) {
+                sub.cell_lookup.insert(name.clone(), new_id);
+            }
+        }
+        for (cell_id, coords) in &self.cell_coords {
+            if let Some(&new_id) = remap.get(cell_id) {
+                sub.cell_coords.insert(new_id, coords.clone());
+                sub.coord_lookup.insert(coords.clone(), new_id);
+            }
+        }
+
+        sub.rebuild_direction_index();
+        (sub, remap)
+    }
+
+    /// 获取单元格上存储的数据，单元格不存在时返回`None`
+    pub fn get_cell(&self, cell_id: CellId) -> Option<&Cell<T>> {
+        self.graph.node_weight(cell_id)
+    }
+
+    /// 获取单元格数据的可变引用，单元格不存在时返回`None`
+    ///
+    /// 修改`Cell.name`不会更新名称映射，按名称查找仍使用添加时的名称。
+    pub fn get_cell_mut(&mut self, cell_id: CellId) -> Option<&mut Cell<T>> {
+        self.graph.node_weight_mut(cell_id)
+    }
+
     /// 根据名称获取单元格ID
     pub fn get_cell_by_name(&self, name: &str) -> Option<CellId> {
         self.cell_lookup.get(name).copied()
     }
 
+    /// 重命名单元格，`None`表示移除单元格的名称
+    ///
+    /// 单元格原有的名称从名称映射中移除，之后只能用新名称查找。
+    /// 新名称已属于其他单元格时返回错误且不做任何修改，不会覆盖其他单元格的名称；
+    /// 需要交换名称时先把其中一个设为`None`。与[`get_cell_mut`](Self::get_cell_mut)
+    /// 相同，`Cell.name`字段不受影响。
+    ///
+    /// # 错误
+    ///
+    /// * `NodeNotFound` - 单元格不存在
+    /// * `NameAlreadyExists` - 新名称已被其他单元格使用
+    pub fn set_cell_name(
+        &mut self,
+        cell_id: CellId,
+        name: Option<String>,
+    ) -> Result<(), GridError> {
+        if !self.contains_cell(cell_id) {
+            return Err(GridError::NodeNotFound);
+        }
+        if let Some(name) = &name {
+            if self
+                .cell_lookup
+                .get(name)
+                .is_some_and(|&owner| owner != cell_id)
+            {
+                return Err(GridError::NameAlreadyExists(name.clone()));
+            }
+        }
+
+        self.cell_lookup.retain(|_, id| *id != cell_id);
+        if let Some(name) = name {
+            self.cell_lookup.insert(name, cell_id);
+        }
+        Ok(())
+    }
+
+    /// 设置单元格坐标
+    ///
+    /// 坐标的维度和含义由构建器决定，例如2D网格使用`[x, y]`。
+    /// 重复设置会覆盖旧坐标。
+    ///
+    /// # 错误情况
+    ///
+    /// - `GridError::NodeNotFound` - 单元格不存在
+    /// - `GridError::CoordsAlreadyExists` - 坐标已属于其他单元格，两个单元格的坐标都保持不变
+    pub fn set_cell_coords(&mut self, cell_id: CellId, coords: Vec<i32>) -> Result<(), GridError> {
+        if !self.contains_cell(cell_id) {
+            return Err(GridError::NodeNotFound);
+        }
+        if self
+            .coord_lookup
+            .get(&coords)
+            .is_some_and(|&owner| owner != cell_id)
+        {
+            return Err(GridError::CoordsAlreadyExists(coords));
+        }
+
+        if let Some(old_coords) = self.cell_coords.insert(cell_id, coords.clone()) {
+            self.coord_lookup.remove(&old_coords);
+        }
+        self.coord_lookup.insert(coords, cell_id);
+        Ok(())
+    }
+
+    /// 获取单元格坐标
+    pub fn get_cell_coords(&self, cell_id: CellId) -> Option<&[i32]> {
+        self.cell_coords.get(&cell_id).map(Vec::as_slice)
+    }
+
+    /// 根据坐标获取单元格ID
+    pub fn get_cell_at(&self, coords: &[i32]) -> Option<CellId> {
+        self.coord_lookup.get(coords).copied()
+    }
+
     /// 判断节点是否是虚拟节点
     pub fn is_virtual_node(&self, node_id: CellId) -> bool {
         self.virtual_nodes.contains(&node_id)
@@ -509,6 +982,35 @@ impl GridSystem {
     /// 2. **应用层责任**：正确的边创建顺序只能由具体的网格构建逻辑确定
     /// 3. **错误预防**：避免提供可能破坏顺序一致性的便捷方法
     ///
+    /// 需要按连接而不是按单元格描述网格时，可以使用[`OrderedEdgeBuffer`]：
+    /// 它先缓存双向连接，再按给定的方向顺序统一建边。
+    ///
+    /// ## 边界占位
+    ///
+    /// 网格边界处没有真实邻居时传入`None`。此时会创建一个虚拟节点作为占位目标，
+    /// 使每个单元格的`get_neighbors()`长度保持一致，[北, 西, 南, 东] 的索引映射
+    /// 不会在边界处错位：
+    ///
+    /// ```rust
+    /// use rlwfc::{Cell, GridSystem};
+    ///
+    /// let mut grid = GridSystem::new();
+    /// let corner = grid.add_cell(Cell::new());
+    /// let east = grid.add_cell(Cell::new());
+    ///
+    /// grid.create_edge(corner, Some(east)).unwrap(); // 东
+    /// grid.create_edge(corner, None).unwrap(); // 南：边界
+    /// grid.create_edge(corner, None).unwrap(); // 西：边界
+    /// grid.create_edge(corner, None).unwrap(); // 北：边界
+    ///
+    /// let neighbors = grid.get_neighbors(corner);
+    /// assert_eq!(neighbors.len(), 4);
+    /// assert_eq!(neighbors[3], east); // 东方向仍位于索引3
+    /// assert!(grid.is_virtual_node(neighbors[0]));
+    /// ```
+    ///
+    /// 目标一定存在时可以使用[`create_edge_to`](Self::create_edge_to)。
+    ///
     /// # 参数
     ///
     /// * `from` - 源单元格ID
@@ -525,6 +1027,20 @@ impl GridSystem {
     /// - `GridError::EdgeAlreadyExists` - 边已存在
     /// - `GridError::NodeNotFound` - 源节点不存在
     pub fn create_edge(&mut self, from: CellId, to: Option<CellId>) -> Result<EdgeId, GridError> {
+        self.create_edge_with_data(from, to, GraphEdge::new())
+    }
+
+    /// 创建携带边数据的单向边
+    ///
+    /// 与[`create_edge`](Self::create_edge)相同，但边上存储`data`而不是`GraphEdge::new()`。
+    /// 可用于标记边的类型（例如"wall"与"door"），供自定义的兼容性判断使用。
+    /// 边创建顺序约束和错误情况与`create_edge`完全相同。
+    pub fn create_edge_with_data(
+        &mut self,
+        from: CellId,
+        to: Option<CellId>,
+        data: GraphEdge,
+    ) -> Result<EdgeId, GridError> {
         // 检查from节点是否存在
         if !self.graph.node_indices().any(|n| n == from) {
             return Err(GridError::NodeNotFound);
@@ -540,9 +1056,11 @@ impl GridSystem {
             }
             None => {
                 // 创建虚拟节点
-                let virtual_node = self
-                    .graph
-                    .add_node(Cell::with_name("__VIRTUAL__".to_string()));
+                let virtual_node = self.graph.add_node(Cell {
+                    id: None,
+                    name: Some("__VIRTUAL__".to_string()),
+                    data: T::default(),
+                });
                 self.virtual_nodes.insert(virtual_node);
                 virtual_node
             }
@@ -559,10 +1077,78 @@ impl GridSystem {
         }
 
         // 创建单向边：from指向target_node
-        let edge_id = self.graph.add_edge(from, target_node, GraphEdge::new());
+        let edge_id = self.graph.add_edge(from, target_node, data);
+        self.incoming_index
+            .entry(target_node)
+            .or_default()
+            .push(from);
         Ok(edge_id)
     }
 
+    /// 创建指向真实单元格的单向边
+    ///
+    /// 等价于`create_edge(from, Some(to))`，错误情况相同。
+    pub fn create_edge_to(&mut self, from: CellId, to: CellId) -> Result<EdgeId, GridError> {
+        self.create_edge(from, Some(to))
+    }
+
+    /// 删除单向边，`create_edge`的逆操作
+    ///
+    /// 用于在多次运行之间开闭通道、切除区域等动态编辑场景。
+    ///
+    /// # ⚠️ 重要：方向查询可能失效
+    ///
+    /// 方向识别依赖全局一致的边创建顺序。删除边后`from`的`get_neighbors()`会变短，
+    /// 其后的邻居索引随之前移，方向查询将不再可靠。除非调用方按原有顺序重新建边，
+    /// 否则不应再依赖`get_neighbor_by_direction`的结果。
+    ///
+    /// 与`remove_cell`类似，petgraph会把最后一条边移动到被删除的位置，
+    /// 之前保存的`EdgeId`可能失效。
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(GraphEdge)` - 被删除边上存储的数据
+    ///
+    /// # 错误情况
+    ///
+    /// - `GridError::EdgeNotFound` - 不存在从`from`指向`to`的边
+    pub fn remove_edge(&mut self, from: CellId, to: CellId) -> Result<GraphEdge, GridError> {
+        let edge_id = self
+            .graph
+            .find_edge(from, to)
+            .ok_or(GridError::EdgeNotFound)?;
+        let edge = self
+            .graph
+            .remove_edge(edge_id)
+            .ok_or(GridError::EdgeNotFound)?;
+
+        if let Some(sources) = self.incoming_index.get_mut(&to) {
+            sources.retain(|&source| source != from);
+        }
+        Ok(edge)
+    }
+
+    /// 获取边上存储的数据，边不存在时返回`None`
+    pub fn get_edge_data(&self, edge: EdgeId) -> Option<&GraphEdge> {
+        self.graph.edge_weight(edge)
+    }
+
+    /// 替换边上存储的数据
+    ///
+    /// 只修改边数据，不影响边的端点和邻居顺序。
+    ///
+    /// # 错误情况
+    ///
+    /// - `GridError::EdgeNotFound` - 边不存在
+    pub fn set_edge_data(&mut self, edge: EdgeId, data: GraphEdge) -> Result<(), GridError> {
+        let slot = self
+            .graph
+            .edge_weight_mut(edge)
+            .ok_or(GridError::EdgeNotFound)?;
+        *slot = data;
+        Ok(())
+    }
+
     /// 获取邻居，对应原C++的getNeighbor方法
     ///
     /// 利用petgraph有向图的特性实现方向感知
@@ -573,6 +1159,36 @@ impl GridSystem {
         self.graph.neighbors(cell_id).collect()
     }
 
+    /// 获取单元格的所有出边及其目标单元格，顺序与[`get_neighbors`](Self::get_neighbors)一致
+    ///
+    /// 边ID可以传给[`get_edge_data`](Self::get_edge_data)读取边数据，
+    /// 便于把邻居与标记在边上的[`GraphEdge`]对应起来。单元格不存在时返回空列表。
+    pub fn edges_from(&self, cell_id: CellId) -> Vec<(EdgeId, CellId)> {
+        self.graph
+            .edges(cell_id)
+            .map(|edge| (edge.id(), edge.target()))
+            .collect()
+    }
+
+    /// 获取通向各邻居的边的类型标签，顺序与[`get_neighbors`](Self::get_neighbors)一致
+    ///
+    /// 标签取自[`GraphEdge::edge_type`]，未设置类型的边为`None`。
+    /// 可用于不规则图中按边标签而不是位置判断约束。
+    pub fn get_neighbor_edge_types(&self, cell_id: CellId) -> Vec<Option<&str>> {
+        self.graph
+            .edges(cell_id)
+            .map(|edge| edge.weight().edge_type.as_deref())
+            .collect()
+    }
+
+    /// 获取入边邻居，即所有指向该单元格的源单元格
+    ///
+    /// 与[`get_neighbors`](Self::get_neighbors)相对应，返回顺序同样为入边插入的逆序。
+    /// 可用于检查无向连接（边对）的完整性，或编写自定义的传播逻辑。
+    pub fn get_incoming_neighbors(&self, cell_id: CellId) -> Vec<CellId> {
+        self.graph.neighbors_directed(cell_id, Incoming).collect()
+    }
+
     /// 查找边，对应原C++的findEdge方法
     pub fn find_edge(&self, from: CellId, to: CellId) -> Option<EdgeId> {
         self.graph.find_edge(from, to)
@@ -593,6 +1209,15 @@ impl GridSystem {
         self.graph.edge_count()
     }
 
+    /// 遍历所有边，产出边ID、源单元格、目标单元格和边数据
+    ///
+    /// 按边ID升序（即创建顺序）遍历，包括指向虚拟边界节点的占位边。
+    pub fn iter_edges(&self) -> impl Iterator<Item = (EdgeId, CellId, CellId, &GraphEdge)> + '_ {
+        self.graph
+            .edge_references()
+            .map(|edge| (edge.id(), edge.source(), edge.target(), edge.weight()))
+    }
+
     // ==========================================================================
     // 方向感知API - 新增的方向识别功能
     // ==========================================================================
@@ -614,6 +1239,8 @@ impl GridSystem {
     }
 
     /// 查找反向邻居（指向当前节点的邻居）
+    ///
+    /// 只检查反向邻接索引中指向当前节点的源单元格，复杂度与入度成正比。
     fn find_incoming_neighbor_by_direction<D>(
         &self,
         cell_id: CellId,
@@ -622,22 +1249,27 @@ impl GridSystem {
     where
         D: DirectionTrait,
     {
-        // 对于需要反向查找的方向，遍历所有节点
-        for node_id in self.graph.node_indices() {
-            let neighbors = self.get_neighbors(node_id);
-
-            // 检查该节点是否通过特定方向指向当前节点
-            if let Some(opposite_direction) = direction.opposite() {
-                if let Some(index) = opposite_direction.to_neighbor_index() {
-                    if let Some(&neighbor) = neighbors.get(index) {
-                        if neighbor == cell_id {
-                            return Some(node_id);
-                        }
-                    }
-                }
+        let index = direction.opposite()?.to_neighbor_index()?;
+        let sources = self.incoming_index.get(&cell_id)?;
+
+        // 检查源节点是否通过相反方向指向当前节点
+        sources
+            .iter()
+            .copied()
+            .find(|&source| self.get_neighbors(source).get(index) == Some(&cell_id))
+    }
+
+    /// 重新计算反向邻接索引
+    ///
+    /// 索引在`create_edge`、`remove_edge`和`remove_cell`中自动维护，
+    /// 一般无需手动调用；批量编辑后如需确保一致可强制重建。
+    pub fn rebuild_direction_index(&mut self) {
+        self.incoming_index.clear();
+        for edge_id in self.graph.edge_indices() {
+            if let Some((source, target)) = self.graph.edge_endpoints(edge_id) {
+                self.incoming_index.entry(target).or_default().push(source);
             }
         }
-        None
     }
 
     /// 获取指定方向的所有邻居
@@ -652,6 +1284,39 @@ impl GridSystem {
         }
     }
 
+    /// 获取单元格在各方向上的邻居，返回`(方向, 邻居)`列表
+    ///
+    /// 按`D::all_directions()`的顺序逐个调用[`get_neighbor_by_direction`](Self::get_neighbor_by_direction)，
+    /// 包括需要反向查找的方向；无法解析的方向被跳过。
+    /// 边界处的虚拟节点也会出现在结果中，可用[`is_virtual_node`](Self::is_virtual_node)过滤。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{Cell, Direction4, GridSystem};
+    ///
+    /// let mut grid = GridSystem::new();
+    /// let west = grid.add_cell(Cell::with_id(0));
+    /// let east = grid.add_cell(Cell::with_id(1));
+    /// grid.create_edge(west, Some(east)).unwrap(); // 东
+    /// grid.create_edge(west, None).unwrap(); // 南：边界
+    ///
+    /// let neighbors = grid.neighbors_with_directions::<Direction4>(east);
+    /// assert_eq!(neighbors, vec![(Direction4::West, west)]);
+    /// ```
+    pub fn neighbors_with_directions<D>(&self, cell: CellId) -> Vec<(D, CellId)>
+    where
+        D: DirectionTrait,
+    {
+        D::all_directions()
+            .into_iter()
+            .filter_map(|direction| {
+                self.get_neighbor_by_direction(cell, direction)
+                    .map(|neighbor| (direction, neighbor))
+            })
+            .collect()
+    }
+
     // ==========================================================================
     // 图状态查询和验证
     // ==========================================================================
@@ -675,6 +1340,18 @@ impl GridSystem {
     pub fn clear(&mut self) {
         self.graph.clear();
         self.cell_lookup.clear();
+        self.cell_coords.clear();
+        self.coord_lookup.clear();
+        self.incoming_index.clear();
+    }
+
+    /// 删除所有边，保留单元格及其名称和坐标
+    ///
+    /// 用于在同一组单元格上重新生成连接（例如迷宫生成器重新挖掘通道）。
+    /// 虚拟节点同样保留，只是不再与任何单元格相连。
+    pub fn clear_edges(&mut self) {
+        self.graph.clear_edges();
+        self.incoming_index.clear();
     }
 
     /// 获取单元格的度数（连接数）
@@ -683,39 +1360,235 @@ impl GridSystem {
     }
 
     // ==========================================================================
-    // 验证和调试工具
+    // 图遍历
     // ==========================================================================
 
-    /// 验证网格结构的完整性
-    pub fn validate_structure(&self) -> Result<(), GridError> {
-        // 验证所有边的端点都存在
-        for edge_id in self.graph.edge_indices() {
-            if let Some((source, target)) = self.graph.edge_endpoints(edge_id) {
-                if !self.contains_cell(source) {
-                    return Err(GridError::NodeNotFound);
+    /// 使用广度优先搜索查找从`from`到`to`的最短路径
+    ///
+    /// 搜索沿出边方向进行，虚拟边界节点不参与搜索。对于每个连接都有边对的网格，
+    /// 结果与方向无关（`to`到`from`的路径长度相同）。
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(path)` - 包含起点和终点的单元格序列，`from == to`时只含起点
+    /// * `None` - 任一单元格不存在或`to`不可达
+    pub fn shortest_path(&self, from: CellId, to: CellId) -> Option<Vec<CellId>> {
+        if !self.contains_cell(from) || !self.contains_cell(to) {
+            return None;
+        }
+
+        let mut parents: HashMap<CellId, CellId> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        parents.insert(from, from);
+
+        while let Some(cell) = queue.pop_front() {
+            if cell == to {
+                let mut path = vec![to];
+                let mut current = to;
+                while current != from {
+                    current = parents[&current];
+                    path.push(current);
                 }
-                if !self.contains_cell(target) {
-                    return Err(GridError::NodeNotFound);
+                path.reverse();
+                return Some(path);
+            }
+
+            for neighbor in self.get_neighbors(cell) {
+                if self.is_virtual_node(neighbor) || parents.contains_key(&neighbor) {
+                    continue;
                 }
+                parents.insert(neighbor, cell);
+                queue.push_back(neighbor);
             }
         }
-        Ok(())
-    }
 
-    /// 获取网格统计信息
-    pub fn get_statistics(&self) -> String {
-        format!(
-            "GridSystem Statistics:\n  Nodes: {}\n  Edges: {}\n  Capacity: {:?}\n  Named cells: {}",
-            self.get_cells_count(),
-            self.get_edges_count(),
-            self.capacity(),
-            self.cell_lookup.len()
-        )
+        None
     }
 
-    /// 调试打印指定单元格的邻居信息
-    pub fn debug_print_neighbors(&self, cell_id: CellId) {
-        println!("Cell {:?} neighbors:", cell_id);
+    /// 计算从`from`出发到所有可达单元格的广度优先距离（边数）
+    ///
+    /// 搜索沿出边方向进行，虚拟边界节点不参与搜索。结果包含`from`本身（距离为0），
+    /// `from`不存在时返回空映射。
+    pub fn bfs_distances(&self, from: CellId) -> HashMap<CellId, usize> {
+        let mut distances = HashMap::new();
+        if !self.contains_cell(from) {
+            return distances;
+        }
+
+        let mut queue = VecDeque::from([from]);
+        distances.insert(from, 0);
+
+        while let Some(cell) = queue.pop_front() {
+            let distance = distances[&cell];
+            for neighbor in self.get_neighbors(cell) {
+                if self.is_virtual_node(neighbor) || distances.contains_key(&neighbor) {
+                    continue;
+                }
+                distances.insert(neighbor, distance + 1);
+                queue.push_back(neighbor);
+            }
+        }
+
+        distances
+    }
+
+    /// 计算连通分量，边按无向处理
+    ///
+    /// 自定义[`GridBuilder`]漏建边时，网格可能被分割成互不相连的孤岛，
+    /// WFC的约束无法跨越孤岛传播。虚拟边界节点不计入任何分量。
+    ///
+    /// 每个分量内的单元格按ID升序排列，分量之间按最小的单元格ID排序。
+    pub fn connected_components(&self) -> Vec<Vec<CellId>> {
+        let mut union_find = UnionFind::<usize>::new(self.graph.node_count());
+        for edge_id in self.graph.edge_indices() {
+            if let Some((source, target)) = self.graph.edge_endpoints(edge_id) {
+                if !self.is_virtual_node(source) && !self.is_virtual_node(target) {
+                    union_find.union(source.index(), target.index());
+                }
+            }
+        }
+
+        let mut components: Vec<Vec<CellId>> = Vec::new();
+        let mut component_of_root: HashMap<usize, usize> = HashMap::new();
+        for cell_id in self.get_all_cells() {
+            if self.is_virtual_node(cell_id) {
+                continue;
+            }
+            let root = union_find.find(cell_id.index());
+            let index = *component_of_root.entry(root).or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[index].push(cell_id);
+        }
+        components
+    }
+
+    /// 检查所有真实单元格是否属于同一个连通分量，空网格视为连通
+    pub fn is_connected(&self) -> bool {
+        self.connected_components().len() <= 1
+    }
+
+    // ==========================================================================
+    // 验证和调试工具
+    // ==========================================================================
+
+    /// 验证网格结构的完整性
+    pub fn validate_structure(&self) -> Result<(), GridError> {
+        // 验证所有边的端点都存在
+        for edge_id in self.graph.edge_indices() {
+            if let Some((source, target)) = self.graph.edge_endpoints(edge_id) {
+                if !self.contains_cell(source) {
+                    return Err(GridError::NodeNotFound);
+                }
+                if !self.contains_cell(target) {
+                    return Err(GridError::NodeNotFound);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 验证每条真实边都有反向边，即所有连接都是无向的边对
+    ///
+    /// 对每条有向边`A→B`检查是否存在`B→A`。指向虚拟边界节点的占位边没有反向边，
+    /// 不参与检查。
+    ///
+    /// # 返回值
+    ///
+    /// * `Ok(())` - 所有真实边都成对出现
+    /// * `Err(unpaired)` - 缺少反向边的`(源, 目标)`列表，按源和目标ID排序
+    pub fn validate_undirected_pairs(&self) -> Result<(), Vec<(CellId, CellId)>> {
+        let mut unpaired: Vec<(CellId, CellId)> = self
+            .graph
+            .edge_indices()
+            .filter_map(|edge_id| self.graph.edge_endpoints(edge_id))
+            .filter(|&(source, target)| {
+                !self.is_virtual_node(target) && !self.contains_edge(target, source)
+            })
+            .collect();
+
+        if unpaired.is_empty() {
+            return Ok(());
+        }
+        unpaired.sort_unstable();
+        Err(unpaired)
+    }
+
+    /// 在调试构建中断言边对完整且方向查询往返一致，供[`GridBuilder`]在构建结束时调用
+    ///
+    /// 除[`validate_undirected_pairs`](Self::validate_undirected_pairs)外，还检查每个
+    /// 真实邻居沿相反方向能回到原单元格，从而发现边创建顺序不一致的问题。
+    /// 发布构建中不做任何检查。
+    ///
+    /// # Panics
+    ///
+    /// 调试构建中发现缺少反向边或方向不一致时panic。
+    pub fn debug_assert_direction_consistency<D: DirectionTrait>(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        if let Err(unpaired) = self.validate_undirected_pairs() {
+            panic!("Edges without a reverse edge: {:?}", unpaired);
+        }
+
+        for cell_id in self.get_all_cells() {
+            if self.is_virtual_node(cell_id) {
+                continue;
+            }
+            for direction in D::all_directions() {
+                let Some(opposite) = direction.opposite() else {
+                    continue;
+                };
+                let Some(neighbor) = self.get_neighbor_by_direction(cell_id, direction) else {
+                    continue;
+                };
+                if self.is_virtual_node(neighbor) {
+                    continue;
+                }
+                assert_eq!(
+                    self.get_neighbor_by_direction(neighbor, opposite),
+                    Some(cell_id),
+                    "Cell {:?} reaches {:?} going {}, but the opposite direction does not lead back",
+                    cell_id,
+                    neighbor,
+                    direction.name()
+                );
+            }
+        }
+    }
+
+    /// 获取网格统计信息
+    pub fn get_statistics(&self) -> String {
+        format!(
+            "GridSystem Statistics:\n  Nodes: {}\n  Edges: {}\n  Capacity: {:?}\n  Named cells: {}",
+            self.get_cells_count(),
+            self.get_edges_count(),
+            self.capacity(),
+            self.cell_lookup.len()
+        )
+    }
+
+    /// 统计单元格的度数分布，键为度数（`get_neighbors().len()`），值为单元格数量
+    ///
+    /// 与[`get_statistics`](Self::get_statistics)互补，用于快速检查构建器的结构：
+    /// 没有边界占位的2D网格应以4度的内部单元格为主，边缘为3度、角落为2度；
+    /// 使用虚拟节点占位的网格中所有真实单元格的度数相同。虚拟节点不计入统计。
+    pub fn degree_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for cell_id in self.get_all_cells() {
+            if !self.is_virtual_node(cell_id) {
+                *histogram.entry(self.get_cell_degree(cell_id)).or_insert(0) += 1;
+            }
+        }
+        histogram
+    }
+
+    /// 调试打印指定单元格的邻居信息
+    #[cfg(feature = "std")]
+    pub fn debug_print_neighbors(&self, cell_id: CellId) {
+        println!("Cell {:?} neighbors:", cell_id);
         let neighbors = self.get_neighbors(cell_id);
         for (i, neighbor) in neighbors.iter().enumerate() {
             println!("  [{}]: {:?}", i, neighbor);
@@ -733,6 +1606,7 @@ impl GridSystem {
     }
 
     /// 调试打印整个网格的信息
+    #[cfg(feature = "std")]
     pub fn debug_print_grid(&self) {
         println!("=== Grid System Debug Info ===");
         println!("{}", self.get_statistics());
@@ -747,234 +1621,1829 @@ impl GridSystem {
             println!("  '{}': {:?}", name, cell_id);
         }
     }
-}
 
-impl Default for GridSystem {
-    fn default() -> Self {
-        Self::new()
+    /// 导出Graphviz DOT格式，用于可视化检查连通性和边顺序
+    ///
+    /// - 节点标签由`Cell.id`和名称组成，虚拟节点以虚线显示
+    /// - 每条边的尾部标注它在源节点`get_neighbors()`中的索引
+    /// - 设置了`edge_type`的边按类型着色，同一类型使用相同颜色
+    ///
+    /// 输出可直接交给`dot -Tsvg`等工具渲染。
+    pub fn to_dot(&self) -> String {
+        const PALETTE: [&str; 6] = ["red", "blue", "darkgreen", "orange", "purple", "brown"];
+
+        // 名称可能来自Cell本身或名称映射，映射中有多个名称时取字典序最小者
+        let mut names: HashMap<CellId, &str> = HashMap::new();
+        for (name, &cell_id) in &self.cell_lookup {
+            let entry = names.entry(cell_id).or_insert(name);
+            if name.as_str() < *entry {
+                *entry = name;
+            }
+        }
+
+        let mut dot = String::from("digraph grid {\n");
+        for cell_id in self.get_all_cells() {
+            let index = cell_id.index();
+            if self.is_virtual_node(cell_id) {
+                dot.push_str(&format!(
+                    "    n{} [label=\"virtual {}\", style=dashed];\n",
+                    index, index
+                ));
+                continue;
+            }
+
+            let cell = &self.graph[cell_id];
+            let name = names.get(&cell_id).copied().or(cell.name.as_deref());
+            let label = match (cell.id, name) {
+                (Some(id), Some(name)) => format!("{}: {}", id, name),
+                (Some(id), None) => id.to_string(),
+                (None, Some(name)) => name.to_string(),
+                (None, None) => format!("#{}", index),
+            };
+            dot.push_str(&format!(
+                "    n{} [label=\"{}\"];\n",
+                index,
+                escape_dot(&label)
+            ));
+        }
+
+        let mut type_colors: HashMap<&str, &str> = HashMap::new();
+        for source in self.get_all_cells() {
+            for (neighbor_index, target) in self.get_neighbors(source).into_iter().enumerate() {
+                let mut attributes = vec![format!("taillabel=\"{}\"", neighbor_index)];
+                let edge_type = self
+                    .graph
+                    .find_edge(source, target)
+                    .and_then(|edge_id| self.graph[edge_id].edge_type.as_deref());
+                if let Some(edge_type) = edge_type {
+                    let next_color = PALETTE[type_colors.len() % PALETTE.len()];
+                    let color = *type_colors.entry(edge_type).or_insert(next_color);
+                    attributes.push(format!("color={}", color));
+                    attributes.push(format!("label=\"{}\"", escape_dot(edge_type)));
+                }
+                dot.push_str(&format!(
+                    "    n{} -> n{} [{}];\n",
+                    source.index(),
+                    target.index(),
+                    attributes.join(", ")
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// 转义DOT字符串中的反斜杠和双引号
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl<T: Default> Default for GridSystem<T> {
+    fn default() -> Self {
+        Self::with_graph(Graph::new())
+    }
+}
+
+// =============================================================================
+// 保持建边顺序的双向连接辅助器
+// =============================================================================
+
+/// 按规范方向顺序缓存双向连接，再统一建边的辅助器
+///
+/// [`GridSystem::create_edge`]直接建边时，双向连接的两条边很难同时满足
+/// 各自单元格的建边顺序。本辅助器为每个单元格维护一个按方向排列的待建边缓冲区：
+/// [`connect`](Self::connect)只登记`A→B`和`B→A`两条边应处的位置，
+/// [`flush`](Self::flush)再按单元格ID升序、每个单元格按`order`中的方向顺序建边。
+/// 没有登记邻居的方向创建虚拟节点，与手动按[东, 南, 西, 北]逐个建边得到完全相同的图。
+///
+/// # 示例
+///
+/// ```rust
+/// use rlwfc::{Cell, Direction4, GridSystem, OrderedEdgeBuffer};
+///
+/// let mut grid = GridSystem::new();
+/// let west = grid.add_cell(Cell::with_id(0));
+/// let east = grid.add_cell(Cell::with_id(1));
+///
+/// let order = [Direction4::East, Direction4::South, Direction4::West, Direction4::North];
+/// let mut edges = OrderedEdgeBuffer::new(&order);
+/// edges.connect(west, east, Direction4::East).unwrap();
+/// edges.flush(&mut grid).unwrap();
+///
+/// // neighbors()为建边逆序：[北, 西, 南, 东]
+/// assert_eq!(grid.get_neighbors(west)[3], east);
+/// assert_eq!(grid.get_neighbors(east)[1], west);
+/// assert!(grid.is_virtual_node(grid.get_neighbors(east)[3]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderedEdgeBuffer<D: DirectionTrait> {
+    /// 每个单元格的建边方向顺序
+    order: Vec<D>,
+    /// 每个单元格按`order`排列的待建边目标，`None`表示边界
+    pending: BTreeMap<CellId, Vec<Option<CellId>>>,
+}
+
+impl<D: DirectionTrait> OrderedEdgeBuffer<D> {
+    /// 使用规范建边顺序创建辅助器
+    ///
+    /// `order`是每个单元格的建边顺序（不是`get_neighbors()`的返回顺序），
+    /// 例如正交网格的`[东, 南, 西, 北]`。
+    pub fn new(order: &[D]) -> Self {
+        Self {
+            order: order.to_vec(),
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// 登记单元格，使其在`flush`时即使没有任何连接也会创建全部边界边
+    pub fn add_cell(&mut self, cell: CellId) {
+        let slots = self.order.len();
+        self.pending
+            .entry(cell)
+            .or_insert_with(|| vec![None; slots]);
+    }
+
+    /// 登记`a`沿`dir_a_to_b`方向到`b`的双向连接
+    ///
+    /// `b→a`的方向取`dir_a_to_b.opposite()`。
+    ///
+    /// # 错误情况
+    ///
+    /// - `GridError::SelfLoop` - `a`与`b`相同
+    /// - `GridError::InvalidDirection` - 方向或其相反方向不在`order`中
+    /// - `GridError::EdgeAlreadyExists` - 任一单元格的该方向已登记过连接
+    pub fn connect(&mut self, a: CellId, b: CellId, dir_a_to_b: D) -> Result<(), GridError> {
+        if a == b {
+            return Err(GridError::SelfLoop);
+        }
+        let slot_a = self.slot(dir_a_to_b)?;
+        let slot_b = self.slot(dir_a_to_b.opposite().ok_or(GridError::InvalidDirection)?)?;
+
+        // 先检查再登记，失败时不留下新的单元格
+        let occupied = |cell: CellId, slot: usize| {
+            self.pending
+                .get(&cell)
+                .is_some_and(|targets| targets[slot].is_some())
+        };
+        if occupied(a, slot_a) || occupied(b, slot_b) {
+            return Err(GridError::EdgeAlreadyExists);
+        }
+
+        self.add_cell(a);
+        self.add_cell(b);
+        self.pending.get_mut(&a).unwrap()[slot_a] = Some(b);
+        self.pending.get_mut(&b).unwrap()[slot_b] = Some(a);
+        Ok(())
+    }
+
+    /// 按单元格ID升序、方向顺序创建全部已登记的边
+    ///
+    /// # 错误情况
+    ///
+    /// 与[`GridSystem::create_edge`]相同，例如单元格不存在或边已存在。
+    pub fn flush<T: Default>(self, grid: &mut GridSystem<T>) -> Result<(), GridError> {
+        for (cell, targets) in self.pending {
+            for target in targets {
+                grid.create_edge(cell, target)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 方向在建边顺序中的位置
+    fn slot(&self, direction: D) -> Result<usize, GridError> {
+        self.order
+            .iter()
+            .position(|&candidate| candidate == direction)
+            .ok_or(GridError::InvalidDirection)
+    }
+}
+
+// =============================================================================
+// 组合构建器
+// =============================================================================
+
+/// 依次运行多个构建器的组合构建器
+///
+/// 所有构建器作用于同一个[`GridSystem`]，后面的构建器在前面的结果上继续添加
+/// 单元格和边，例如先构建正交网格、再补充对角边。每个构建器完成后立即写入它提供的
+/// 坐标，后续构建器可以通过[`GridSystem::get_cell_at`]找到之前创建的单元格。
+///
+/// # 建边顺序
+///
+/// `get_neighbors()`按建边逆序返回单元格的全部出边，不区分边由哪个构建器创建，
+/// 因此后面的构建器添加的边会排在前面，瓷砖边数据的索引也随之平移。
+/// 组合只有在各构建器约定好顺序时才能与瓷砖集配合：例如补充对角边的构建器应为
+/// 每个单元格以相同的方向顺序添加相同数量的边（边界同样用虚拟节点占位），
+/// 使所有单元格的邻居列表具有一致的排列。
+///
+/// # 示例
+///
+/// ```rust
+/// use rlwfc::{Cell, ChainBuilder, GridBuilder, GridError, GridSystem};
+///
+/// struct Line(usize);
+///
+/// impl GridBuilder for Line {
+///     fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
+///         for _ in 0..self.0 {
+///             grid.add_cell(Cell::new());
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let grid = GridSystem::from_builder(ChainBuilder::new().then(Line(2)).then(Line(3))).unwrap();
+/// assert_eq!(grid.get_cells_count(), 5);
+/// ```
+pub struct ChainBuilder<T = ()> {
+    builders: Vec<Box<dyn GridBuilder<T>>>,
+}
+
+impl<T> ChainBuilder<T> {
+    /// 创建空的组合构建器
+    pub fn new() -> Self {
+        Self {
+            builders: Vec::new(),
+        }
+    }
+
+    /// 在末尾追加构建器
+    pub fn then<B: GridBuilder<T> + 'static>(mut self, builder: B) -> Self {
+        self.builders.push(Box::new(builder));
+        self
+    }
+
+    /// 在末尾追加已装箱的构建器
+    pub fn push(&mut self, builder: Box<dyn GridBuilder<T>>) {
+        self.builders.push(builder);
+    }
+}
+
+impl<T> Default for ChainBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Default> GridBuilder<T> for ChainBuilder<T> {
+    fn build_grid_system(&mut self, grid: &mut GridSystem<T>) -> Result<(), GridError> {
+        for builder in &mut self.builders {
+            builder.build_grid_system(grid)?;
+            grid.apply_builder_coordinates(builder.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// 使用第一个构建器的维度
+    fn get_dimensions(&self) -> Vec<usize> {
+        self.builders
+            .first()
+            .map_or_else(Vec::new, |builder| builder.get_dimensions())
+    }
+
+    fn get_grid_type_name(&self) -> &'static str {
+        "ChainGrid"
+    }
+
+    /// 各构建器估计值之和
+    fn estimated_capacity(&self) -> (usize, usize) {
+        self.builders
+            .iter()
+            .map(|builder| builder.estimated_capacity())
+            .fold((0, 0), |(nodes, edges), (n, e)| (nodes + n, edges + e))
+    }
+}
+
+// =============================================================================
+// 二维网格构建器
+// =============================================================================
+
+/// 二维网格的邻接方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    /// 四邻接，按东、南、西、北建边，邻居顺序`[北, 西, 南, 东]`与[`Direction4Full`]一致
+    Four,
+    /// 八邻接（含对角），邻居顺序与[`Direction8`]一致
+    Eight,
+}
+
+impl Connectivity {
+    /// 每个单元格的边数
+    pub fn direction_count(&self) -> usize {
+        match self {
+            Connectivity::Four => 4,
+            Connectivity::Eight => 8,
+        }
+    }
+
+    /// 按边创建顺序排列的坐标偏移`(dx, dy)`，x向东、y向南
+    fn offsets(&self) -> Vec<(i32, i32)> {
+        match self {
+            // 东、南、西、北，与Direction4Full::all_directions()相同
+            Connectivity::Four => vec![(1, 0), (0, 1), (-1, 0), (0, -1)],
+            Connectivity::Eight => Direction8::all_directions()
+                .iter()
+                .map(Direction8::offset)
+                .collect(),
+        }
+    }
+}
+
+/// 可选四邻接或八邻接的矩形二维网格构建器
+///
+/// 单元格按行优先创建，命名为`cell_{x}_{y}`并记录坐标`[x, y]`。
+/// 每个单元格按[`Direction4Full`]或[`Direction8`]的`all_directions()`顺序创建全部边，
+/// 超出边界的方向（包括边角处的对角方向）使用虚拟节点占位，
+/// 因此所有单元格的邻居列表具有相同的排列，可以直接用对应的方向系统按方向查询邻居，
+/// 也可以作为[`AdjacencyTileSet`](crate::AdjacencyTileSet)的方向参数。
+///
+/// # 示例
+///
+/// ```rust
+/// use rlwfc::{Connectivity, Direction8, GridBuilder2D, GridSystem};
+///
+/// let grid = GridSystem::from_builder(GridBuilder2D::new(3, 3, Connectivity::Eight)).unwrap();
+/// let center = grid.get_cell_at(&[1, 1]).unwrap();
+/// assert_eq!(grid.get_neighbors(center).len(), 8);
+/// assert_eq!(
+///     grid.get_neighbor_by_direction(center, Direction8::NorthEast),
+///     grid.get_cell_at(&[2, 0])
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct GridBuilder2D {
+    /// 宽度（x方向单元格数）
+    pub width: usize,
+    /// 高度（y方向单元格数）
+    pub height: usize,
+    /// 邻接方式
+    pub connectivity: Connectivity,
+    coords: HashMap<CellId, Vec<i32>>,
+}
+
+impl GridBuilder2D {
+    /// 创建指定尺寸和邻接方式的构建器
+    pub fn new(width: usize, height: usize, connectivity: Connectivity) -> Self {
+        Self {
+            width,
+            height,
+            connectivity,
+            coords: HashMap::new(),
+        }
+    }
+
+    /// 边界外的方向数，即需要的虚拟节点数
+    fn border_edge_count(&self) -> usize {
+        let (width, height) = (self.width as i32, self.height as i32);
+        let offsets = self.connectivity.offsets();
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                offsets
+                    .iter()
+                    .filter(|&&(dx, dy)| {
+                        !(0..width).contains(&(x + dx)) || !(0..height).contains(&(y + dy))
+                    })
+                    .count()
+            })
+            .sum()
+    }
+}
+
+impl GridBuilder for GridBuilder2D {
+    fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
+        self.coords.clear();
+        let mut cells = vec![Vec::with_capacity(self.width); self.height];
+        for (y, row) in cells.iter_mut().enumerate() {
+            for x in 0..self.width {
+                let cell_id = grid.add_cell_with_name(
+                    Cell::with_id((y * self.width + x) as u32),
+                    format!("cell_{}_{}", x, y),
+                );
+                self.coords.insert(cell_id, vec![x as i32, y as i32]);
+                row.push(cell_id);
+            }
+        }
+
+        let offsets = self.connectivity.offsets();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                for &(dx, dy) in &offsets {
+                    let nx = x as i32 + dx;
+                    let ny = y as i32 + dy;
+                    let target = ((0..self.width as i32).contains(&nx)
+                        && (0..self.height as i32).contains(&ny))
+                    .then(|| cells[ny as usize][nx as usize]);
+                    grid.create_edge(cells[y][x], target)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_dimensions(&self) -> Vec<usize> {
+        vec![self.width, self.height]
+    }
+
+    fn get_grid_type_name(&self) -> &'static str {
+        match self.connectivity {
+            Connectivity::Four => "Grid2D4",
+            Connectivity::Eight => "Grid2D8",
+        }
+    }
+
+    fn cell_coordinates(&self) -> Option<HashMap<CellId, Vec<i32>>> {
+        Some(self.coords.clone())
+    }
+
+    fn estimated_capacity(&self) -> (usize, usize) {
+        let cells = self.width * self.height;
+        (
+            cells + self.border_edge_count(),
+            cells * self.connectivity.direction_count(),
+        )
+    }
+}
+
+// =============================================================================
+// 序列化支持
+// =============================================================================
+
+/// 网格的序列化格式，节点和边都用插入顺序中的下标表示
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(deserialize = "T: serde::Deserialize<'de> + Default"))]
+struct GridSystemData<T> {
+    /// 按`CellId`顺序排列的节点数据
+    cells: Vec<Cell<T>>,
+    /// 虚拟节点下标，升序
+    virtual_nodes: Vec<usize>,
+    /// `(源, 目标, 边数据)`，同一源节点的边按创建顺序排列
+    edges: Vec<(usize, usize, GraphEdge)>,
+    /// 单元格名称映射，按名称排序
+    cell_lookup: Vec<(String, usize)>,
+    /// 单元格坐标，按下标排序
+    cell_coords: Vec<(usize, Vec<i32>)>,
+}
+
+#[cfg(feature = "serde")]
+impl<T: Clone> From<&GridSystem<T>> for GridSystemData<T> {
+    fn from(grid: &GridSystem<T>) -> Self {
+        let cells = grid
+            .graph
+            .node_indices()
+            .map(|cell_id| grid.graph[cell_id].clone())
+            .collect();
+
+        // petgraph按插入逆序返回出边，逐节点反转即可还原创建顺序
+        let mut edges = Vec::with_capacity(grid.graph.edge_count());
+        for cell_id in grid.graph.node_indices() {
+            let start = edges.len();
+            edges.extend(grid.graph.edges(cell_id).map(|edge| {
+                (
+                    cell_id.index(),
+                    edge.target().index(),
+                    edge.weight().clone(),
+                )
+            }));
+            edges[start..].reverse();
+        }
+
+        let mut virtual_nodes: Vec<usize> =
+            grid.virtual_nodes.iter().map(|id| id.index()).collect();
+        virtual_nodes.sort_unstable();
+        let mut cell_lookup: Vec<(String, usize)> = grid
+            .cell_lookup
+            .iter()
+            .map(|(name, id)| (name.clone(), id.index()))
+            .collect();
+        cell_lookup.sort_unstable();
+        let mut cell_coords: Vec<(usize, Vec<i32>)> = grid
+            .cell_coords
+            .iter()
+            .map(|(id, coords)| (id.index(), coords.clone()))
+            .collect();
+        cell_coords.sort_unstable();
+
+        Self {
+            cells,
+            virtual_nodes,
+            edges,
+            cell_lookup,
+            cell_coords,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Default> TryFrom<GridSystemData<T>> for GridSystem<T> {
+    type Error = GridError;
+
+    fn try_from(data: GridSystemData<T>) -> Result<Self, GridError> {
+        let mut grid =
+            GridSystem::with_graph(Graph::with_capacity(data.cells.len(), data.edges.len()));
+        for cell in data.cells {
+            grid.graph.add_node(cell);
+        }
+
+        let node_count = grid.graph.node_count();
+        let cell_id = |index: usize| {
+            (index < node_count)
+                .then(|| CellId::new(index))
+                .ok_or(GridError::NodeNotFound)
+        };
+        let virtual_nodes = data
+            .virtual_nodes
+            .into_iter()
+            .map(cell_id)
+            .collect::<Result<HashSet<_>, _>>()?;
+        let cell_lookup = data
+            .cell_lookup
+            .into_iter()
+            .map(|(name, index)| Ok((name, cell_id(index)?)))
+            .collect::<Result<HashMap<_, _>, GridError>>()?;
+        let cell_coords = data
+            .cell_coords
+            .into_iter()
+            .map(|(index, coords)| Ok((cell_id(index)?, coords)))
+            .collect::<Result<Vec<_>, GridError>>()?;
+
+        for (from, to, edge) in data.edges {
+            let (from, to) = (cell_id(from)?, cell_id(to)?);
+            if from == to {
+                return Err(GridError::SelfLoop);
+            }
+            if grid.graph.find_edge(from, to).is_some() {
+                return Err(GridError::EdgeAlreadyExists);
+            }
+            grid.graph.add_edge(from, to, edge);
+        }
+
+        grid.virtual_nodes = virtual_nodes;
+        grid.cell_lookup = cell_lookup;
+        for (cell_id, coords) in cell_coords {
+            grid.set_cell_coords(cell_id, coords)?;
+        }
+        grid.rebuild_direction_index();
+        Ok(grid)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + Clone> serde::Serialize for GridSystem<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GridSystemData::from(self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de> + Default> serde::Deserialize<'de> for GridSystem<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = GridSystemData::deserialize(deserializer)?;
+        GridSystem::try_from(data).map_err(serde::de::Error::custom)
+    }
+}
+
+// =============================================================================
+// 测试模块
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试用的简单网格构建器
+    struct SimpleGridBuilder {
+        width: usize,
+        height: usize,
+    }
+
+    impl SimpleGridBuilder {
+        fn new(width: usize, height: usize) -> Self {
+            Self { width, height }
+        }
+    }
+
+    impl GridBuilder for SimpleGridBuilder {
+        fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
+            // 创建一个简单的width x height网格
+            let mut cells = vec![vec![]; self.height];
+
+            // Step 1: 创建所有单元格
+            for (y, row) in cells.iter_mut().enumerate() {
+                *row = Vec::with_capacity(self.width);
+                for x in 0..self.width {
+                    let cell_id = grid.add_cell_with_name(
+                        Cell::with_id((y * self.width + x) as u32),
+                        format!("cell_{}_{}", x, y),
+                    );
+                    row.push(cell_id);
+                }
+            }
+
+            // Step 2: 创建连接（每个cell连接到右边和下面的邻居）
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let current = cells[y][x];
+
+                    // 连接到右边
+                    if x + 1 < self.width {
+                        grid.create_edge(current, Some(cells[y][x + 1]))?;
+                    } else {
+                        grid.create_edge(current, None)?;
+                    }
+
+                    // 连接到下面
+                    if y + 1 < self.height {
+                        grid.create_edge(current, Some(cells[y + 1][x]))?;
+                    } else {
+                        grid.create_edge(current, None)?;
+                    }
+                }
+            }
+
+            Ok(())
+        }
+
+        fn get_dimensions(&self) -> Vec<usize> {
+            vec![self.width, self.height]
+        }
+
+        fn get_grid_type_name(&self) -> &'static str {
+            "SimpleGrid"
+        }
+
+        fn estimated_capacity(&self) -> (usize, usize) {
+            // 东、南两条边，右侧和底部边界各需要一个虚拟节点
+            let cells = self.width * self.height;
+            (cells + self.width + self.height, 2 * cells)
+        }
+    }
+
+    #[test]
+    fn test_grid_system_creation() {
+        let grid = GridSystem::new();
+        assert_eq!(grid.get_cells_count(), 0);
+        assert_eq!(grid.get_edges_count(), 0);
+    }
+
+    #[test]
+    fn test_from_builder_preallocates_estimated_capacity() {
+        let builder = SimpleGridBuilder::new(64, 48);
+        let (nodes, edges) = builder.estimated_capacity();
+        let grid = GridSystem::from_builder(builder).unwrap();
+
+        let (node_capacity, edge_capacity) = grid.capacity();
+        assert!(node_capacity >= nodes);
+        assert!(edge_capacity >= edges);
+        // 估计值准确时构建完成后恰好填满
+        assert_eq!(grid.get_cells_count(), nodes);
+        assert_eq!(grid.get_edges_count(), edges);
+
+        let mut grid = GridSystem::new();
+        grid.build_with(SimpleGridBuilder::new(20, 10)).unwrap();
+        assert!(grid.capacity().0 >= 20 * 10 + 20 + 10);
+    }
+
+    #[test]
+    fn test_add_cells_and_edges() {
+        let mut grid = GridSystem::new();
+
+        // 添加单元格
+        let cell1 = grid.add_cell(Cell::with_id(1));
+        let cell2 = grid.add_cell(Cell::with_id(2));
+
+        assert_eq!(grid.get_cells_count(), 2);
+
+        // 创建边
+        let _edge = grid.create_edge(cell1, Some(cell2)).unwrap();
+        assert_eq!(grid.get_edges_count(), 1);
+
+        // 检查邻居
+        let neighbors = grid.get_neighbors(cell1);
+        assert_eq!(neighbors.len(), 1);
+        assert_eq!(neighbors[0], cell2);
+    }
+
+    #[test]
+    fn test_direction_queries() {
+        let mut grid = GridSystem::new();
+
+        // 创建2x2网格
+        let cells = [
+            vec![
+                grid.add_cell(Cell::with_id(0)),
+                grid.add_cell(Cell::with_id(1)),
+            ],
+            vec![
+                grid.add_cell(Cell::with_id(2)),
+                grid.add_cell(Cell::with_id(3)),
+            ],
+        ];
+
+        // 按标准顺序创建边：东向，然后南向
+        let center = cells[0][0];
+        let east = cells[0][1];
+        let south = cells[1][0];
+
+        grid.create_edge(center, Some(east)).unwrap(); // 东向边
+        grid.create_edge(center, Some(south)).unwrap(); // 南向边
+
+        // 测试方向查询
+        assert_eq!(
+            grid.get_neighbor_by_direction(center, Direction4::East),
+            Some(east)
+        );
+        assert_eq!(
+            grid.get_neighbor_by_direction(center, Direction4::South),
+            Some(south)
+        );
+        assert_eq!(
+            grid.get_neighbor_by_direction(center, Direction4::West),
+            None
+        );
+        assert_eq!(
+            grid.get_neighbor_by_direction(center, Direction4::North),
+            None
+        );
+    }
+
+    #[test]
+    fn test_error_handling() {
+        let mut grid = GridSystem::new();
+
+        let cell1 = grid.add_cell(Cell::new());
+
+        // 测试自循环错误
+        assert_eq!(
+            grid.create_edge(cell1, Some(cell1)),
+            Err(GridError::SelfLoop)
+        );
+
+        // 测试重复边错误
+        let cell2 = grid.add_cell(Cell::new());
+        grid.create_edge(cell1, Some(cell2)).unwrap();
+        assert_eq!(
+            grid.create_edge(cell1, Some(cell2)),
+            Err(GridError::EdgeAlreadyExists)
+        );
+    }
+
+    #[test]
+    fn test_named_cells() {
+        let mut grid = GridSystem::new();
+
+        let cell_id = grid.add_cell_with_name(Cell::new(), "test_cell".to_string());
+        assert_eq!(grid.get_cell_by_name("test_cell"), Some(cell_id));
+        assert_eq!(grid.get_cell_by_name("nonexistent"), None);
+    }
+
+    #[test]
+    fn test_set_cell_name() {
+        let mut grid = GridSystem::new();
+        let a = grid.add_cell_with_name(Cell::new(), "a".to_string());
+        let b = grid.add_cell_with_name(Cell::new(), "b".to_string());
+
+        // 重命名后旧名称不再可用
+        grid.set_cell_name(a, Some("start".to_string())).unwrap();
+        assert_eq!(grid.get_cell_by_name("start"), Some(a));
+        assert_eq!(grid.get_cell_by_name("a"), None);
+
+        // 名称已属于其他单元格时拒绝修改，两边都保持不变
+        assert_eq!(
+            grid.set_cell_name(a, Some("b".to_string())),
+            Err(GridError::NameAlreadyExists("b".to_string()))
+        );
+        assert_eq!(grid.get_cell_by_name("b"), Some(b));
+        assert_eq!(grid.get_cell_by_name("start"), Some(a));
+
+        // 设置为自己当前的名称没有影响，None移除名称，未命名的单元格也可以命名
+        grid.set_cell_name(b, Some("b".to_string())).unwrap();
+        assert_eq!(grid.get_cell_by_name("b"), Some(b));
+        grid.set_cell_name(a, None).unwrap();
+        assert_eq!(grid.get_cell_by_name("start"), None);
+        grid.set_cell_name(a, Some("a".to_string())).unwrap();
+        assert_eq!(grid.get_cell_by_name("a"), Some(a));
+        assert_eq!(grid.cell_lookup.len(), 2);
+
+        assert_eq!(
+            grid.set_cell_name(CellId::new(99), Some("x".to_string())),
+            Err(GridError::NodeNotFound)
+        );
+    }
+
+    #[test]
+    fn test_set_cell_coords() {
+        let mut grid = GridSystem::new();
+        let a = grid.add_cell(Cell::new());
+        let b = grid.add_cell(Cell::new());
+        grid.set_cell_coords(a, vec![0, 0]).unwrap();
+        grid.set_cell_coords(b, vec![1, 0]).unwrap();
+
+        // 坐标已属于其他单元格时拒绝修改，两边都保持不变
+        assert_eq!(
+            grid.set_cell_coords(b, vec![0, 0]),
+            Err(GridError::CoordsAlreadyExists(vec![0, 0]))
+        );
+        assert_eq!(grid.get_cell_at(&[0, 0]), Some(a));
+        assert_eq!(grid.get_cell_at(&[1, 0]), Some(b));
+        assert_eq!(grid.get_cell_coords(b), Some(&[1, 0][..]));
+
+        // 移动坐标后旧坐标释放，可以交给其他单元格
+        grid.set_cell_coords(a, vec![0, 1]).unwrap();
+        assert_eq!(grid.get_cell_at(&[0, 0]), None);
+        grid.set_cell_coords(b, vec![0, 0]).unwrap();
+        assert_eq!(grid.get_cell_at(&[0, 0]), Some(b));
+        assert_eq!(grid.get_cell_at(&[0, 1]), Some(a));
+        assert_eq!(grid.get_cell_at(&[1, 0]), None);
+
+        // 设置为自己当前的坐标没有影响
+        grid.set_cell_coords(b, vec![0, 0]).unwrap();
+        assert_eq!(grid.get_cell_at(&[0, 0]), Some(b));
+    }
+
+    #[test]
+    fn test_structure_validation() {
+        let mut grid = GridSystem::new();
+
+        let cell1 = grid.add_cell(Cell::new());
+        let cell2 = grid.add_cell(Cell::new());
+        grid.create_edge(cell1, Some(cell2)).unwrap();
+
+        // 验证应该成功
+        assert!(grid.validate_structure().is_ok());
+    }
+
+    #[test]
+    fn test_grid_builder_trait() {
+        // 测试使用builder构建网格
+        let builder = SimpleGridBuilder::new(3, 2);
+        let mut grid = GridSystem::new();
+
+        // 使用builder构建网格
+        grid.build_with(builder).unwrap();
+
+        // 验证构建结果：6个真实单元格 + 5个虚拟节点 = 11个节点
+        assert_eq!(grid.get_cells_count(), 11);
+
+        // 验证命名单元格（只有真实单元格有名称）
+        assert!(grid.get_cell_by_name("cell_0_0").is_some());
+        assert!(grid.get_cell_by_name("cell_2_1").is_some());
+        assert!(grid.get_cell_by_name("cell_3_0").is_none()); // 超出范围
+
+        // 在3x2网格中，实际创建了12条边（6个单元格 * 2条边/单元格）
+        let expected_edges = 12;
+        assert_eq!(grid.get_edges_count(), expected_edges);
+    }
+
+    #[test]
+    fn test_from_builder() {
+        // 测试从builder直接创建网格
+        let builder = SimpleGridBuilder::new(2, 2);
+        let grid = GridSystem::from_builder(builder).unwrap();
+
+        // 验证构建结果：4个真实单元格 + 4个虚拟节点 = 8个节点
+        assert_eq!(grid.get_cells_count(), 8);
+
+        // 测试连接性：每个内部单元格应该有邻居
+        let cell_0_0 = grid.get_cell_by_name("cell_0_0").unwrap();
+        let neighbors = grid.get_neighbors(cell_0_0);
+        assert_eq!(neighbors.len(), 2); // 连接到右边和下面
+
+        // 2x2网格创建了8条边（4个单元格 * 2条边/单元格）
+        let expected_edges = 8;
+        assert_eq!(grid.get_edges_count(), expected_edges);
+    }
+
+    #[test]
+    fn test_boundary_edges_keep_neighbor_order() {
+        let mut grid = GridSystem::new();
+        let center = grid.add_cell(Cell::with_id(0));
+        let east = grid.add_cell(Cell::with_id(1));
+        let north = grid.add_cell(Cell::with_id(2));
+
+        // 东、南、西、北，其中南和西位于边界
+        grid.create_edge_to(center, east).unwrap();
+        grid.create_edge(center, None).unwrap();
+        grid.create_edge(center, None).unwrap();
+        grid.create_edge_to(center, north).unwrap();
+
+        let neighbors = grid.get_neighbors(center);
+        assert_eq!(neighbors.len(), 4);
+        assert_eq!(neighbors[0], north);
+        assert!(grid.is_virtual_node(neighbors[1]));
+        assert!(grid.is_virtual_node(neighbors[2]));
+        assert_eq!(neighbors[3], east);
+
+        assert_eq!(
+            grid.create_edge_to(center, east),
+            Err(GridError::EdgeAlreadyExists)
+        );
+    }
+
+    #[test]
+    fn test_remove_cell() {
+        let builder = SimpleGridBuilder::new(3, 2);
+        let mut grid = GridSystem::from_builder(builder).unwrap();
+
+        let target = grid.get_cell_by_name("cell_1_0").unwrap();
+        let incoming = grid
+            .get_all_cells()
+            .filter(|&cell| grid.contains_edge(cell, target))
+            .count();
+        let degree = grid.get_cell_degree(target) + incoming;
+        let edges_before = grid.get_edges_count();
+        let cells_before = grid.get_cells_count();
+        let last = CellId::new(cells_before - 1);
+        let last_was_virtual = grid.is_virtual_node(last);
+
+        let (removed, remap) = grid.remove_cell(target).unwrap();
+        assert_eq!(removed.id, Some(1));
+        assert_eq!(grid.get_edges_count(), edges_before - degree);
+        assert_eq!(grid.get_cells_count(), cells_before - 1);
+        assert!(grid.get_cell_by_name("cell_1_0").is_none());
+
+        // 最后一个节点被移动到了被删除的位置，虚拟节点记录随之修正
+        assert_eq!(remap, vec![(last, target)]);
+        assert_eq!(grid.is_virtual_node(target), last_was_virtual);
+        assert!(!grid.is_virtual_node(last));
+        assert!(grid.validate_structure().is_ok());
+
+        assert_eq!(
+            grid.remove_cell(CellId::new(1000)),
+            Err(GridError::NodeNotFound)
+        );
+    }
+
+    #[test]
+    fn test_subgraph_extracts_corner() {
+        let grid = GridSystem::from_builder(SimpleGridBuilder::new(4, 4)).unwrap();
+        let corner: HashSet<CellId> = (0..2)
+            .flat_map(|y| (0..2).map(move |x| (x, y)))
+            .map(|(x, y)| grid.get_cell_by_name(&format!("cell_{}_{}", x, y)).unwrap())
+            .collect();
+        let (sub, remap) = grid.subgraph(&corner);
+
+        assert_eq!(sub.get_cells_count(), 4);
+        assert_eq!(remap.len(), 4);
+        // 左上角的东、南边都保留；右侧和下侧单元格指向区域外的边被丢弃
+        assert_eq!(sub.get_edges_count(), 4);
+        assert!(sub.validate_structure().is_ok());
+
+        let cell = |x: usize, y: usize| sub.get_cell_by_name(&format!("cell_{}_{}", x, y)).unwrap();
+        assert_eq!(sub.get_neighbors(cell(0, 0)), vec![cell(0, 1), cell(1, 0)]);
+        assert_eq!(sub.get_neighbors(cell(1, 0)), vec![cell(1, 1)]);
+        assert_eq!(sub.get_neighbors(cell(0, 1)), vec![cell(1, 1)]);
+        assert!(sub.get_neighbors(cell(1, 1)).is_empty());
+
+        // 内部邻居关系与原网格一致
+        for (&old, &new) in &remap {
+            let expected: Vec<CellId> = grid
+                .get_neighbors(old)
+                .into_iter()
+                .filter_map(|neighbor| remap.get(&neighbor).copied())
+                .collect();
+            assert_eq!(sub.get_neighbors(new), expected);
+            assert_eq!(sub.get_cell_coords(new), grid.get_cell_coords(old));
+        }
+    }
+
+    #[test]
+    fn test_merge_grids() {
+        let mut top = GridSystem::from_builder(SimpleGridBuilder::new(2, 2)).unwrap();
+        let bottom = GridSystem::from_builder(SimpleGridBuilder::new(2, 2)).unwrap();
+        let (cells_before, edges_before) = (top.get_cells_count(), top.get_edges_count());
+        let (other_cells, other_edges) = (bottom.get_cells_count(), bottom.get_edges_count());
+        let bottom_neighbors: Vec<Vec<CellId>> = bottom
+            .get_all_cells()
+            .map(|c| bottom.get_neighbors(c))
+            .collect();
+
+        // 上方网格的最后一行连接到下方网格的第一行，组成2x4布局
+        let connections: Vec<(CellId, CellId)> = (0..2)
+            .map(|x| {
+                (
+                    top.get_cell_by_name(&format!("cell_{}_1", x)).unwrap(),
+                    bottom.get_cell_by_name(&format!("cell_{}_0", x)).unwrap(),
+                )
+            })
+            .collect();
+        let remap = top.merge(bottom, &connections).unwrap();
+
+        assert_eq!(remap.len(), other_cells);
+        assert_eq!(top.get_cells_count(), cells_before + other_cells);
+        assert_eq!(
+            top.get_edges_count(),
+            edges_before + other_edges + 2 * connections.len()
+        );
+        assert!(top.validate_structure().is_ok());
+
+        // 下方网格内部的邻居顺序保持不变
+        for (index, neighbors) in bottom_neighbors.iter().enumerate() {
+            let expected: Vec<CellId> = neighbors.iter().map(|n| remap[n]).collect();
+            let merged = top.get_neighbors(remap[&CellId::new(index)]);
+            if connections.iter().any(|&(_, b)| b.index() == index) {
+                assert_eq!(merged[1..], expected[..]);
+            } else {
+                assert_eq!(merged, expected);
+            }
+        }
+
+        // 桥接边位于邻居列表最前面，且是双向的
+        let (above, below) = connections[0];
+        assert_eq!(top.get_neighbors(above)[0], remap[&below]);
+        assert_eq!(top.get_neighbors(remap[&below])[0], above);
+
+        // 冲突的名称追加后缀，虚拟节点记录随单元格一起导入
+        assert_eq!(top.get_cell_by_name("cell_0_1"), Some(above));
+        assert_eq!(top.get_cell_by_name("cell_0_0#2"), Some(remap[&below]));
+        assert!(remap
+            .values()
+            .all(|&id| top.is_virtual_node(id) == top.get_cell(id).unwrap().id.is_none()));
+
+        let mut other = GridSystem::new();
+        let lonely = other.add_cell(Cell::new());
+        assert_eq!(
+            top.merge(other, &[(CellId::new(1000), lonely)]),
+            Err(GridError::NodeNotFound)
+        );
+        assert_eq!(top.get_cells_count(), cells_before + other_cells);
+    }
+
+    #[test]
+    fn test_remove_edge() {
+        let mut grid = GridSystem::new();
+        let a = grid.add_cell(Cell::with_id(0));
+        let b = grid.add_cell(Cell::with_id(1));
+        let c = grid.add_cell(Cell::with_id(2));
+        grid.create_edge_to(a, b).unwrap();
+        grid.create_edge_to(a, c).unwrap();
+        grid.create_edge_to(b, a).unwrap();
+
+        let removed = grid.remove_edge(a, b).unwrap();
+        assert_eq!(removed, GraphEdge::new());
+        assert_eq!(grid.get_neighbors(a), vec![c]);
+        assert_eq!(grid.get_edges_count(), 2);
+
+        // 反向边不受影响
+        assert!(grid.contains_edge(b, a));
+
+        // 重复删除
+        assert_eq!(grid.remove_edge(a, b), Err(GridError::EdgeNotFound));
+    }
+
+    #[test]
+    fn test_edge_data() {
+        let mut grid = GridSystem::new();
+        let a = grid.add_cell(Cell::with_id(0));
+        let b = grid.add_cell(Cell::with_id(1));
+
+        // 普通建边使用默认边数据
+        let plain = grid.create_edge_to(a, b).unwrap();
+        assert_eq!(grid.get_edge_data(plain), Some(&GraphEdge::new()));
+
+        let door = GraphEdge::with_type("door".to_string());
+        let tagged = grid
+            .create_edge_with_data(b, Some(a), door.clone())
+            .unwrap();
+        assert_eq!(grid.get_edge_data(tagged), Some(&door));
+
+        // 修改边数据后可以读回，邻居顺序不变
+        let wall = GraphEdge {
+            weight: Some(3),
+            edge_type: Some("wall".to_string()),
+        };
+        grid.set_edge_data(plain, wall.clone()).unwrap();
+        assert_eq!(grid.get_edge_data(plain), Some(&wall));
+        assert_eq!(grid.get_neighbors(a), vec![b]);
+
+        // 出边ID与邻居一一对应
+        let c = grid.add_cell(Cell::with_id(2));
+        let to_c = grid
+            .create_edge_with_data(a, Some(c), door.clone())
+            .unwrap();
+        let edges = grid.edges_from(a);
+        assert_eq!(edges, vec![(to_c, c), (plain, b)]);
+        let targets: Vec<CellId> = edges.iter().map(|&(_, target)| target).collect();
+        assert_eq!(targets, grid.get_neighbors(a));
+        assert_eq!(grid.get_edge_data(edges[0].0), Some(&door));
+        assert_eq!(grid.get_edge_data(edges[1].0), Some(&wall));
+
+        let missing = EdgeId::new(10);
+        assert_eq!(grid.get_edge_data(missing), None);
+        assert_eq!(
+            grid.set_edge_data(missing, GraphEdge::new()),
+            Err(GridError::EdgeNotFound)
+        );
+    }
+
+    #[test]
+    fn test_bfs_on_linear_grid() {
+        let mut grid = GridSystem::new();
+        let cells: Vec<CellId> = (0..6).map(|i| grid.add_cell(Cell::with_id(i))).collect();
+        for pair in cells.windows(2) {
+            grid.create_edge_to(pair[0], pair[1]).unwrap();
+            grid.create_edge_to(pair[1], pair[0]).unwrap();
+        }
+
+        let distances = grid.bfs_distances(cells[1]);
+        assert_eq!(distances.len(), cells.len());
+        for (i, cell) in cells.iter().enumerate() {
+            assert_eq!(distances[cell], i.abs_diff(1));
+        }
+
+        assert_eq!(
+            grid.shortest_path(cells[4], cells[1]),
+            Some(cells[1..5].iter().rev().copied().collect())
+        );
+        assert_eq!(grid.shortest_path(cells[2], cells[2]), Some(vec![cells[2]]));
+    }
+
+    #[test]
+    fn test_bfs_on_directed_grid() {
+        // SimpleGridBuilder只创建向右和向下的边
+        let grid = GridSystem::from_builder(SimpleGridBuilder::new(3, 3)).unwrap();
+        let cell =
+            |x: usize, y: usize| grid.get_cell_by_name(&format!("cell_{}_{}", x, y)).unwrap();
+
+        let distances = grid.bfs_distances(cell(0, 0));
+        assert_eq!(distances.len(), 9);
+        for y in 0..3 {
+            for x in 0..3 {
+                assert_eq!(distances[&cell(x, y)], x + y);
+            }
+        }
+        assert!(distances.keys().all(|&id| !grid.is_virtual_node(id)));
+
+        let path = grid.shortest_path(cell(0, 0), cell(2, 2)).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path.first(), Some(&cell(0, 0)));
+        assert_eq!(path.last(), Some(&cell(2, 2)));
+        for pair in path.windows(2) {
+            assert!(grid.contains_edge(pair[0], pair[1]));
+        }
+
+        // 沿出边方向搜索，反方向不可达
+        assert_eq!(grid.shortest_path(cell(2, 2), cell(0, 0)), None);
+        assert_eq!(grid.bfs_distances(cell(2, 2)).len(), 1);
+    }
+
+    #[test]
+    fn test_connected_components() {
+        // 线性网格（只有单向边）是一个分量，虚拟节点不计入
+        let mut grid = GridSystem::new();
+        let line: Vec<CellId> = (0..4).map(|i| grid.add_cell(Cell::with_id(i))).collect();
+        for pair in line.windows(2) {
+            grid.create_edge_to(pair[0], pair[1]).unwrap();
+        }
+        grid.create_edge(line[3], None).unwrap();
+        assert_eq!(grid.connected_components(), vec![line.clone()]);
+        assert!(grid.is_connected());
+
+        // 加入一个独立的簇和一个孤立单元格
+        let cluster: Vec<CellId> = (4..6).map(|i| grid.add_cell(Cell::with_id(i))).collect();
+        grid.create_edge_to(cluster[1], cluster[0]).unwrap();
+        let isolated = grid.add_cell(Cell::with_id(6));
+
+        let components = grid.connected_components();
+        assert_eq!(components, vec![line, cluster, vec![isolated]]);
+        assert!(!grid.is_connected());
+
+        assert!(GridSystem::new().is_connected());
+    }
+
+    /// 构建3个单元格的线性网格，漏掉中间单元格指回左侧的反向边
+    fn grid_missing_reverse_edge() -> (GridSystem, Vec<CellId>) {
+        let mut grid = GridSystem::new();
+        let cells: Vec<CellId> = (0..3).map(|i| grid.add_cell(Cell::with_id(i))).collect();
+        grid.create_edge_to(cells[0], cells[1]).unwrap();
+        grid.create_edge_to(cells[1], cells[2]).unwrap();
+        grid.create_edge_to(cells[2], cells[1]).unwrap();
+        grid.create_edge(cells[2], None).unwrap();
+        (grid, cells)
+    }
+
+    #[test]
+    fn test_validate_undirected_pairs() {
+        let (mut grid, cells) = grid_missing_reverse_edge();
+        assert_eq!(
+            grid.validate_undirected_pairs(),
+            Err(vec![(cells[0], cells[1])])
+        );
+
+        // 补上反向边后通过，指向虚拟节点的占位边不参与检查
+        grid.create_edge_to(cells[1], cells[0]).unwrap();
+        assert_eq!(grid.validate_undirected_pairs(), Ok(()));
+    }
+
+    // 发布构建中debug_assert_direction_consistency不做检查，以下测试只在调试构建中运行
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "Edges without a reverse edge")]
+    fn test_debug_assert_direction_consistency() {
+        let (grid, _) = grid_missing_reverse_edge();
+        grid.debug_assert_direction_consistency::<Direction4>();
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_debug_assert_direction_roundtrip() {
+        // 八邻接网格的边都成对，每个方向都能沿相反方向回到原单元格
+        let grid = GridSystem::from_builder(GridBuilder2D::new(3, 3, Connectivity::Eight)).unwrap();
+        grid.debug_assert_direction_consistency::<Direction8>();
     }
-}
 
-// =============================================================================
-// 测试模块
-// =============================================================================
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "the opposite direction does not lead back")]
+    fn test_debug_assert_direction_roundtrip_mismatch() {
+        // a和d的南方都是b，b沿北方只能回到其中一个
+        let mut grid = GridSystem::new();
+        let a = grid.add_cell(Cell::with_id(0));
+        let b = grid.add_cell(Cell::with_id(1));
+        let d = grid.add_cell(Cell::with_id(2));
+        grid.create_edge(a, Some(b)).unwrap();
+        grid.create_edge(d, Some(b)).unwrap();
+        grid.create_edge(b, Some(a)).unwrap();
+        grid.create_edge(b, Some(d)).unwrap();
+        assert!(grid.validate_undirected_pairs().is_ok());
+
+        grid.debug_assert_direction_consistency::<Direction4>();
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_iter_edges() {
+        let grid = GridSystem::from_builder(SimpleGridBuilder::new(2, 2)).unwrap();
+        let edges: Vec<_> = grid.iter_edges().collect();
+        assert_eq!(edges.len(), grid.get_edges_count());
+
+        for &(edge_id, source, target, data) in &edges {
+            assert!(grid.contains_cell(source));
+            assert!(grid.contains_cell(target));
+            assert!(!grid.is_virtual_node(source));
+            assert_eq!(grid.find_edge(source, target), Some(edge_id));
+            assert_eq!(grid.get_edge_data(edge_id), Some(data));
+        }
 
-    // 测试用的简单网格构建器
-    struct SimpleGridBuilder {
+        // 每个单元格向右、向下各一条边，其中4条指向虚拟边界节点
+        let virtual_targets = edges
+            .iter()
+            .filter(|&&(_, _, target, _)| grid.is_virtual_node(target))
+            .count();
+        assert_eq!(virtual_targets, 4);
+    }
+
+    #[test]
+    fn test_add_cells() {
+        let mut grid = GridSystem::new();
+        let cells = grid.add_cells((0..100).map(Cell::with_id));
+        assert_eq!(cells.len(), 100);
+        assert_eq!(grid.get_cells_count(), 100);
+        assert_eq!(cells, grid.get_all_cells().collect::<Vec<_>>());
+
+        let named = grid.add_named_cells(
+            ["left", "right"]
+                .into_iter()
+                .map(|name| (Cell::new(), name.to_string())),
+        );
+        assert_eq!(grid.get_cells_count(), 102);
+        assert_eq!(grid.get_cell_by_name("left"), Some(named[0]));
+        assert_eq!(grid.get_cell_by_name("right"), Some(named[1]));
+    }
+
+    /// 在单元格数据中记录坐标的构建器
+    struct CoordGridBuilder {
         width: usize,
         height: usize,
     }
 
-    impl SimpleGridBuilder {
-        fn new(width: usize, height: usize) -> Self {
-            Self { width, height }
+    impl GridBuilder<(usize, usize)> for CoordGridBuilder {
+        fn build_grid_system(
+            &mut self,
+            grid: &mut GridSystem<(usize, usize)>,
+        ) -> Result<(), GridError> {
+            let cells: Vec<CellId> = (0..self.height)
+                .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+                .map(|coords| grid.add_cell(Cell::with_data(coords)))
+                .collect();
+            for pair in cells.windows(2) {
+                grid.create_edge_to(pair[0], pair[1])?;
+            }
+            grid.create_edge(cells[cells.len() - 1], None)?;
+            Ok(())
         }
     }
 
-    impl GridBuilder for SimpleGridBuilder {
-        fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
-            // 创建一个简单的width x height网格
-            let mut cells = vec![vec![]; self.height];
+    #[test]
+    fn test_cell_payload() {
+        let grid = GridSystem::from_builder(CoordGridBuilder {
+            width: 3,
+            height: 2,
+        })
+        .unwrap();
+
+        let real_cells: Vec<CellId> = grid
+            .get_all_cells()
+            .filter(|&cell_id| !grid.is_virtual_node(cell_id))
+            .collect();
+        assert_eq!(real_cells.len(), 6);
+        for (index, &cell_id) in real_cells.iter().enumerate() {
+            let cell = grid.get_cell(cell_id).unwrap();
+            assert_eq!(cell.data, (index % 3, index / 3));
+        }
 
-            // Step 1: 创建所有单元格
-            for y in 0..self.height {
-                cells[y] = Vec::with_capacity(self.width);
-                for x in 0..self.width {
-                    let cell_id = grid.add_cell_with_name(
-                        Cell::with_id((y * self.width + x) as u32),
-                        format!("cell_{}_{}", x, y),
-                    );
-                    cells[y].push(cell_id);
-                }
-            }
+        // 虚拟节点使用默认数据占位
+        let boundary = grid.get_neighbors(real_cells[5])[0];
+        assert!(grid.is_virtual_node(boundary));
+        assert_eq!(grid.get_cell(boundary).unwrap().data, (0, 0));
+        assert!(grid.get_cell(CellId::new(100)).is_none());
 
-            // Step 2: 创建连接（每个cell连接到右边和下面的邻居）
-            for y in 0..self.height {
-                for x in 0..self.width {
-                    let current = cells[y][x];
+        let mut manual = GridSystem::<&str>::default();
+        let forest = manual.add_cell(Cell::with_data("forest"));
+        assert_eq!(manual.get_cell(forest).unwrap().data, "forest");
+    }
 
-                    // 连接到右边
-                    if x + 1 < self.width {
-                        grid.create_edge(current, Some(cells[y][x + 1]))?;
-                    } else {
-                        grid.create_edge(current, None)?;
-                    }
+    #[test]
+    fn test_get_cell() {
+        let mut grid = GridSystem::new();
+        let cell_id = grid.add_cell(Cell::with_id(7));
+        assert_eq!(grid.get_cell(cell_id).unwrap().id, Some(7));
 
-                    // 连接到下面
-                    if y + 1 < self.height {
-                        grid.create_edge(current, Some(cells[y + 1][x]))?;
-                    } else {
-                        grid.create_edge(current, None)?;
-                    }
-                }
-            }
+        grid.get_cell_mut(cell_id).unwrap().name = Some("renamed".to_string());
+        let cell = grid.get_cell(cell_id).unwrap();
+        assert_eq!(cell.id, Some(7));
+        assert_eq!(cell.name.as_deref(), Some("renamed"));
 
-            Ok(())
-        }
+        assert!(grid.get_cell_mut(CellId::new(5)).is_none());
+    }
 
-        fn get_dimensions(&self) -> Vec<usize> {
-            vec![self.width, self.height]
-        }
+    #[test]
+    fn test_to_dot() {
+        let mut grid = GridSystem::from_builder(SimpleGridBuilder::new(2, 2)).unwrap();
+        let dot = grid.to_dot();
+
+        assert!(dot.starts_with("digraph grid {"));
+        let node_lines = dot.lines().filter(|line| line.contains("[label=")).count();
+        let edge_lines = dot.lines().filter(|line| line.contains(" -> ")).count();
+        assert_eq!(node_lines, grid.get_cells_count());
+        assert_eq!(edge_lines, grid.get_edges_count());
+        assert_eq!(dot.matches("style=dashed").count(), 4);
+
+        // 命名单元格带有ID和名称，边尾标注邻居索引
+        let cell = grid.get_cell_by_name("cell_1_0").unwrap();
+        assert!(dot.contains(&format!("n{} [label=\"1: cell_1_0\"]", cell.index())));
+        let first = grid.get_neighbors(cell)[0];
+        assert!(dot.contains(&format!(
+            "n{} -> n{} [taillabel=\"0\"]",
+            cell.index(),
+            first.index()
+        )));
+
+        // 带类型的边按类型着色
+        let a = grid.add_cell_with_name(Cell::new(), "say \"hi\"".to_string());
+        grid.create_edge_with_data(a, Some(cell), GraphEdge::with_type("road".to_string()))
+            .unwrap();
+        let dot = grid.to_dot();
+        assert!(dot.contains("[label=\"say \\\"hi\\\"\"]"));
+        assert!(dot.contains("color=red, label=\"road\""));
+    }
 
-        fn get_grid_type_name(&self) -> &'static str {
-            "SimpleGrid"
-        }
+    #[test]
+    fn test_neighbors_with_directions() {
+        let grid = GridSystem::from_builder(SimpleGridBuilder::new(3, 3)).unwrap();
+        let cell = |x: usize, y: usize| grid.get_cell_by_name(&format!("cell_{}_{}", x, y));
+        let center = cell(1, 1).unwrap();
+
+        let neighbors = grid.neighbors_with_directions::<Direction4>(center);
+        assert_eq!(
+            neighbors,
+            vec![
+                (Direction4::East, cell(2, 1).unwrap()),
+                (Direction4::South, cell(1, 2).unwrap()),
+                (Direction4::West, cell(0, 1).unwrap()),
+                (Direction4::North, cell(1, 0).unwrap()),
+            ]
+        );
+
+        // 角落单元格：西、北没有指向它的边，东、南为真实邻居
+        let corner = grid.neighbors_with_directions::<Direction4>(cell(0, 0).unwrap());
+        assert_eq!(
+            corner,
+            vec![
+                (Direction4::East, cell(1, 0).unwrap()),
+                (Direction4::South, cell(0, 1).unwrap()),
+            ]
+        );
     }
 
     #[test]
-    fn test_grid_system_creation() {
-        let grid = GridSystem::new();
-        assert_eq!(grid.get_cells_count(), 0);
+    fn test_clear_edges() {
+        let mut grid = GridSystem::from_builder(SimpleGridBuilder::new(3, 2)).unwrap();
+        let cells_count = grid.get_cells_count();
+        let cell = grid.get_cell_by_name("cell_1_0").unwrap();
+        let east = grid.get_cell_by_name("cell_2_0").unwrap();
+
+        grid.clear_edges();
         assert_eq!(grid.get_edges_count(), 0);
+        assert_eq!(grid.get_cells_count(), cells_count);
+        assert_eq!(grid.get_cell_by_name("cell_1_0"), Some(cell));
+        assert!(grid.get_neighbors(cell).is_empty());
+        assert_eq!(grid.get_neighbor_by_direction(east, Direction4::West), None);
+
+        // 清空后可以重新建边，反向索引随之更新
+        grid.create_edge_to(cell, east).unwrap();
+        grid.create_edge(cell, None).unwrap();
+        assert_eq!(
+            grid.get_neighbor_by_direction(east, Direction4::West),
+            Some(cell)
+        );
     }
 
     #[test]
-    fn test_add_cells_and_edges() {
+    fn test_degree_histogram() {
         let mut grid = GridSystem::new();
+        let cells: Vec<Vec<CellId>> = (0..3)
+            .map(|y| {
+                (0..3)
+                    .map(|x| grid.add_cell(Cell::with_id(y * 3 + x)))
+                    .collect()
+            })
+            .collect();
+
+        // 只为真实邻居建边（东、南、西、北），不使用边界占位
+        for y in 0..3 {
+            for x in 0..3 {
+                let neighbors = [
+                    (x + 1 < 3).then(|| cells[y][x + 1]),
+                    (y + 1 < 3).then(|| cells[y + 1][x]),
+                    (x > 0).then(|| cells[y][x - 1]),
+                    (y > 0).then(|| cells[y - 1][x]),
+                ];
+                for neighbor in neighbors.into_iter().flatten() {
+                    grid.create_edge_to(cells[y][x], neighbor).unwrap();
+                }
+            }
+        }
 
-        // 添加单元格
-        let cell1 = grid.add_cell(Cell::with_id(1));
-        let cell2 = grid.add_cell(Cell::with_id(2));
-
-        assert_eq!(grid.get_cells_count(), 2);
-
-        // 创建边
-        let _edge = grid.create_edge(cell1, Some(cell2)).unwrap();
-        assert_eq!(grid.get_edges_count(), 1);
+        // 4个角落2度、4个边缘3度、1个中心4度
+        let expected: BTreeMap<usize, usize> = [(2, 4), (3, 4), (4, 1)].into_iter().collect();
+        assert_eq!(grid.degree_histogram(), expected);
 
-        // 检查邻居
-        let neighbors = grid.get_neighbors(cell1);
-        assert_eq!(neighbors.len(), 1);
-        assert_eq!(neighbors[0], cell2);
+        // 边界占位使角落变为3度，虚拟节点本身不计入
+        grid.create_edge(cells[0][0], None).unwrap();
+        let expected: BTreeMap<usize, usize> = [(2, 3), (3, 5), (4, 1)].into_iter().collect();
+        assert_eq!(grid.degree_histogram(), expected);
     }
 
     #[test]
-    fn test_direction_queries() {
+    fn test_incoming_neighbors() {
         let mut grid = GridSystem::new();
+        let cells: Vec<Vec<CellId>> = (0..2)
+            .map(|y| {
+                (0..2)
+                    .map(|x| grid.add_cell(Cell::with_id(y * 2 + x)))
+                    .collect()
+            })
+            .collect();
+
+        // 按东、南、西、北顺序建边，边界使用虚拟节点
+        for y in 0..2 {
+            for x in 0..2 {
+                let current = cells[y][x];
+                grid.create_edge(current, (x + 1 < 2).then(|| cells[y][x + 1]))
+                    .unwrap();
+                grid.create_edge(current, (y + 1 < 2).then(|| cells[y + 1][x]))
+                    .unwrap();
+                grid.create_edge(current, (x > 0).then(|| cells[y][x - 1]))
+                    .unwrap();
+                grid.create_edge(current, (y > 0).then(|| cells[y - 1][x]))
+                    .unwrap();
+            }
+        }
 
-        // 创建2x2网格
-        let cells = vec![
-            vec![
-                grid.add_cell(Cell::with_id(0)),
-                grid.add_cell(Cell::with_id(1)),
-            ],
-            vec![
-                grid.add_cell(Cell::with_id(2)),
-                grid.add_cell(Cell::with_id(3)),
-            ],
-        ];
+        for &cell in cells.iter().flatten() {
+            let mut incoming = grid.get_incoming_neighbors(cell);
+            incoming.sort();
 
-        // 按标准顺序创建边：东向，然后南向
-        let center = cells[0][0];
-        let east = cells[0][1];
-        let south = cells[1][0];
+            let mut expected: Vec<CellId> = grid
+                .get_all_cells()
+                .filter(|&other| grid.get_neighbors(other).contains(&cell))
+                .collect();
+            expected.sort();
 
-        grid.create_edge(center, Some(east)).unwrap(); // 东向边
-        grid.create_edge(center, Some(south)).unwrap(); // 南向边
+            assert_eq!(incoming, expected);
+            assert_eq!(incoming.len(), 2);
+        }
 
-        // 测试方向查询
-        assert_eq!(
-            grid.get_neighbor_by_direction(center, Direction4::East),
-            Some(east)
-        );
-        assert_eq!(
-            grid.get_neighbor_by_direction(center, Direction4::South),
-            Some(south)
-        );
-        assert_eq!(
-            grid.get_neighbor_by_direction(center, Direction4::West),
-            None
-        );
-        assert_eq!(
-            grid.get_neighbor_by_direction(center, Direction4::North),
-            None
-        );
+        // 虚拟节点只有入边
+        let virtual_node = grid.get_neighbors(cells[0][0])[0];
+        assert!(grid.is_virtual_node(virtual_node));
+        assert_eq!(grid.get_incoming_neighbors(virtual_node), vec![cells[0][0]]);
     }
 
     #[test]
-    fn test_error_handling() {
+    fn test_direction_index_large_grid() {
+        let size = 50;
+        let mut grid = GridSystem::from_builder(SimpleGridBuilder::new(size, size)).unwrap();
+        let cell_at = |grid: &GridSystem, x: usize, y: usize| {
+            grid.get_cell_by_name(&format!("cell_{}_{}", x, y)).unwrap()
+        };
+
+        let check_interior = |grid: &GridSystem| {
+            for y in 1..size - 1 {
+                for x in 1..size - 1 {
+                    let cell = cell_at(grid, x, y);
+                    assert_eq!(
+                        grid.get_neighbor_by_direction(cell, Direction4::East),
+                        Some(cell_at(grid, x + 1, y))
+                    );
+                    assert_eq!(
+                        grid.get_neighbor_by_direction(cell, Direction4::South),
+                        Some(cell_at(grid, x, y + 1))
+                    );
+                    assert_eq!(
+                        grid.get_neighbor_by_direction(cell, Direction4::West),
+                        Some(cell_at(grid, x - 1, y))
+                    );
+                    assert_eq!(
+                        grid.get_neighbor_by_direction(cell, Direction4::North),
+                        Some(cell_at(grid, x, y - 1))
+                    );
+                }
+            }
+        };
+
+        check_interior(&grid);
+
+        // 强制重建后结果不变
+        grid.rebuild_direction_index();
+        check_interior(&grid);
+    }
+
+    #[test]
+    fn test_direction6_lattice() {
+        // 2x2x2立方体，按上、下、东、南、西、北顺序建边，边界使用虚拟节点
+        let (w, h, d) = (2usize, 2usize, 2usize);
         let mut grid = GridSystem::new();
+        let index = |x: usize, y: usize, z: usize| (z * h + y) * w + x;
+        let cells: Vec<CellId> = (0..w * h * d)
+            .map(|i| grid.add_cell(Cell::with_id(i as u32)))
+            .collect();
+
+        for z in 0..d {
+            for y in 0..h {
+                for x in 0..w {
+                    let current = cells[index(x, y, z)];
+                    let targets = [
+                        (z + 1 < d).then(|| cells[index(x, y, z + 1)]),
+                        (z > 0).then(|| cells[index(x, y, z - 1)]),
+                        (x + 1 < w).then(|| cells[index(x + 1, y, z)]),
+                        (y + 1 < h).then(|| cells[index(x, y + 1, z)]),
+                        (x > 0).then(|| cells[index(x - 1, y, z)]),
+                        (y > 0).then(|| cells[index(x, y - 1, z)]),
+                    ];
+                    for target in targets {
+                        grid.create_edge(current, target).unwrap();
+                    }
+                }
+            }
+        }
 
-        let cell1 = grid.add_cell(Cell::new());
+        // 角落单元格：三个方向为真实邻居，另外三个方向为虚拟边界
+        let corner = cells[index(0, 0, 0)];
+        let expect = [
+            (Direction6::East, Some(cells[index(1, 0, 0)])),
+            (Direction6::South, Some(cells[index(0, 1, 0)])),
+            (Direction6::Up, Some(cells[index(0, 0, 1)])),
+            (Direction6::West, None),
+            (Direction6::North, None),
+            (Direction6::Down, None),
+        ];
+        for (direction, expected) in expect {
+            let neighbor = grid.get_neighbor_by_direction(corner, direction).unwrap();
+            match expected {
+                Some(cell) => assert_eq!(neighbor, cell, "{}", direction.name()),
+                None => assert!(grid.is_virtual_node(neighbor), "{}", direction.name()),
+            }
+        }
 
-        // 测试自循环错误
+        // 对角单元格的西、北、下方向回到相邻单元格
+        let far = cells[index(1, 1, 1)];
         assert_eq!(
-            grid.create_edge(cell1, Some(cell1)),
-            Err(GridError::SelfLoop)
+            grid.get_neighbor_by_direction(far, Direction6::West),
+            Some(cells[index(0, 1, 1)])
         );
-
-        // 测试重复边错误
-        let cell2 = grid.add_cell(Cell::new());
-        grid.create_edge(cell1, Some(cell2)).unwrap();
         assert_eq!(
-            grid.create_edge(cell1, Some(cell2)),
-            Err(GridError::EdgeAlreadyExists)
+            grid.get_neighbor_by_direction(far, Direction6::North),
+            Some(cells[index(1, 0, 1)])
+        );
+        assert_eq!(
+            grid.get_neighbor_by_direction(far, Direction6::Down),
+            Some(cells[index(1, 1, 0)])
         );
     }
 
     #[test]
-    fn test_named_cells() {
+    fn test_direction_hex_rhombus() {
+        use crate::DirectionHex;
+
+        // 3x3菱形六边形地图，轴向坐标(q, r)，按DirectionHex::all_directions()顺序建边
+        let size = 3i32;
         let mut grid = GridSystem::new();
+        let index = |q: i32, r: i32| (r * size + q) as usize;
+        let cells: Vec<CellId> = (0..size * size)
+            .map(|i| grid.add_cell(Cell::with_id(i as u32)))
+            .collect();
+
+        for r in 0..size {
+            for q in 0..size {
+                for direction in DirectionHex::all_directions() {
+                    let (dq, dr) = direction.axial_offset();
+                    let (nq, nr) = (q + dq, r + dr);
+                    let target = ((0..size).contains(&nq) && (0..size).contains(&nr))
+                        .then(|| cells[index(nq, nr)]);
+                    grid.create_edge(cells[index(q, r)], target).unwrap();
+                }
+            }
+        }
 
-        let cell_id = grid.add_cell_with_name(Cell::new(), "test_cell".to_string());
-        assert_eq!(grid.get_cell_by_name("test_cell"), Some(cell_id));
-        assert_eq!(grid.get_cell_by_name("nonexistent"), None);
+        // 中心单元格的六个方向都能解析为真实邻居
+        let center = cells[index(1, 1)];
+        for direction in DirectionHex::all_directions() {
+            let (dq, dr) = direction.axial_offset();
+            assert_eq!(
+                grid.get_neighbor_by_direction(center, direction),
+                Some(cells[index(1 + dq, 1 + dr)]),
+                "{}",
+                direction.name()
+            );
+        }
+
+        // 边界单元格缺失的方向为虚拟节点，其余方向映射不变
+        let corner = cells[index(0, 0)];
+        let east = grid
+            .get_neighbor_by_direction(corner, DirectionHex::East)
+            .unwrap();
+        assert_eq!(east, cells[index(1, 0)]);
+        let north_east = grid
+            .get_neighbor_by_direction(corner, DirectionHex::NorthEast)
+            .unwrap();
+        assert!(grid.is_virtual_node(north_east));
     }
 
     #[test]
-    fn test_structure_validation() {
+    fn test_direction_tri_grid() {
+        use crate::{DirectionTri, TriOrientation};
+
+        // 4x2三角形网格，按DirectionTri::all_directions()顺序建边
+        let (width, height) = (4i32, 2i32);
         let mut grid = GridSystem::new();
+        let index = |x: i32, y: i32| (y * width + x) as usize;
+        let contains = |x: i32, y: i32| (0..width).contains(&x) && (0..height).contains(&y);
+        let cells: Vec<CellId> = (0..width * height)
+            .map(|i| grid.add_cell(Cell::with_id(i as u32)))
+            .collect();
+
+        for y in 0..height {
+            for x in 0..width {
+                let orientation = TriOrientation::at(x, y);
+                for direction in DirectionTri::all_directions() {
+                    let (dx, dy) = direction.offset(orientation);
+                    let target = contains(x + dx, y + dy).then(|| cells[index(x + dx, y + dy)]);
+                    grid.create_edge(cells[index(x, y)], target).unwrap();
+                }
+            }
+        }
+        grid.debug_assert_direction_consistency::<DirectionTri>();
 
-        let cell1 = grid.add_cell(Cell::new());
-        let cell2 = grid.add_cell(Cell::new());
-        grid.create_edge(cell1, Some(cell2)).unwrap();
+        // 朝上的(2, 0)与正下方朝下的(2, 1)通过底边互相解析
+        let up = cells[index(2, 0)];
+        let down = cells[index(2, 1)];
+        assert_eq!(TriOrientation::at(2, 0), TriOrientation::Up);
+        assert_eq!(
+            grid.get_neighbor_by_direction(up, DirectionTri::Base),
+            Some(down)
+        );
+        assert_eq!(
+            grid.get_neighbor_by_direction(down, DirectionTri::Base),
+            Some(up)
+        );
 
-        // 验证应该成功
-        assert!(grid.validate_structure().is_ok());
+        // 左右邻居朝向相反，沿相反方向回到原三角形
+        let right = grid
+            .get_neighbor_by_direction(up, DirectionTri::Right)
+            .unwrap();
+        assert_eq!(right, cells[index(3, 0)]);
+        assert_eq!(
+            grid.get_neighbor_by_direction(right, DirectionTri::Left),
+            Some(up)
+        );
+
+        // 第一行朝下三角形的底边在网格之外
+        let top_down = cells[index(1, 0)];
+        let boundary = grid
+            .get_neighbor_by_direction(top_down, DirectionTri::Base)
+            .unwrap();
+        assert!(grid.is_virtual_node(boundary));
     }
 
     #[test]
-    fn test_grid_builder_trait() {
-        // 测试使用builder构建网格
-        let builder = SimpleGridBuilder::new(3, 2);
-        let mut grid = GridSystem::new();
-
-        // 使用builder构建网格
-        grid.build_with(builder).unwrap();
+    fn test_ordered_edge_buffer_matches_manual_order() {
+        let (width, height) = (4, 3);
+        let order = [
+            Direction4::East,
+            Direction4::South,
+            Direction4::West,
+            Direction4::North,
+        ];
+        let add_cells = |grid: &mut GridSystem| -> Vec<CellId> {
+            (0..width * height)
+                .map(|i| grid.add_cell(Cell::with_id(i as u32)))
+                .collect()
+        };
 
-        // 验证构建结果：6个真实单元格 + 5个虚拟节点 = 11个节点
-        assert_eq!(grid.get_cells_count(), 11);
+        // 手动按东、南、西、北建边
+        let mut manual = GridSystem::new();
+        let cells = add_cells(&mut manual);
+        for y in 0..height {
+            for x in 0..width {
+                let current = cells[y * width + x];
+                let targets = [
+                    (x + 1 < width).then(|| cells[y * width + x + 1]),
+                    (y + 1 < height).then(|| cells[(y + 1) * width + x]),
+                    (x > 0).then(|| cells[y * width + x - 1]),
+                    (y > 0).then(|| cells[(y - 1) * width + x]),
+                ];
+                for target in targets {
+                    manual.create_edge(current, target).unwrap();
+                }
+            }
+        }
 
-        // 验证命名单元格（只有真实单元格有名称）
-        assert!(grid.get_cell_by_name("cell_0_0").is_some());
-        assert!(grid.get_cell_by_name("cell_2_1").is_some());
-        assert!(grid.get_cell_by_name("cell_3_0").is_none()); // 超出范围
+        // 只登记向东、向南的连接，且故意打乱登记顺序
+        let mut buffered = GridSystem::new();
+        let cells = add_cells(&mut buffered);
+        let mut edges = OrderedEdgeBuffer::new(&order);
+        for y in (0..height).rev() {
+            for x in (0..width).rev() {
+                let current = cells[y * width + x];
+                edges.add_cell(current);
+                if y + 1 < height {
+                    edges
+                        .connect(current, cells[(y + 1) * width + x], Direction4::South)
+                        .unwrap();
+                }
+                if x + 1 < width {
+                    edges
+                        .connect(current, cells[y * width + x + 1], Direction4::East)
+                        .unwrap();
+                }
+            }
+        }
+        edges.flush(&mut buffered).unwrap();
 
-        // 在3x2网格中，实际创建了12条边（6个单元格 * 2条边/单元格）
-        let expected_edges = 12;
-        assert_eq!(grid.get_edges_count(), expected_edges);
+        assert_eq!(buffered.get_cells_count(), manual.get_cells_count());
+        assert_eq!(buffered.get_edges_count(), manual.get_edges_count());
+        for cell in manual.get_all_cells() {
+            assert_eq!(buffered.get_neighbors(cell), manual.get_neighbors(cell));
+            assert_eq!(buffered.is_virtual_node(cell), manual.is_virtual_node(cell));
+        }
     }
 
     #[test]
-    fn test_from_builder() {
-        // 测试从builder直接创建网格
-        let builder = SimpleGridBuilder::new(2, 2);
-        let grid = GridSystem::from_builder(builder).unwrap();
+    fn test_ordered_edge_buffer_errors() {
+        let mut grid = GridSystem::new();
+        let a = grid.add_cell(Cell::with_id(0));
+        let b = grid.add_cell(Cell::with_id(1));
+        let c = grid.add_cell(Cell::with_id(2));
 
-        // 验证构建结果：4个真实单元格 + 4个虚拟节点 = 8个节点
-        assert_eq!(grid.get_cells_count(), 8);
+        let mut edges = OrderedEdgeBuffer::new(&[Direction4::East, Direction4::West]);
+        assert_eq!(
+            edges.connect(a, a, Direction4::East),
+            Err(GridError::SelfLoop)
+        );
+        assert_eq!(
+            edges.connect(a, b, Direction4::South),
+            Err(GridError::InvalidDirection)
+        );
+        edges.connect(a, b, Direction4::East).unwrap();
+        // b的西侧已经是a
+        assert_eq!(
+            edges.connect(c, b, Direction4::East),
+            Err(GridError::EdgeAlreadyExists)
+        );
 
-        // 测试连接性：每个内部单元格应该有邻居
-        let cell_0_0 = grid.get_cell_by_name("cell_0_0").unwrap();
-        let neighbors = grid.get_neighbors(cell_0_0);
-        assert_eq!(neighbors.len(), 2); // 连接到右边和下面
+        // 失败的连接没有登记c，flush只为a、b建边
+        edges.flush(&mut grid).unwrap();
+        assert!(grid.get_neighbors(c).is_empty());
+        assert_eq!(grid.get_edges_count(), 4);
+        assert_eq!(grid.get_cells_count(), 5);
 
-        // 2x2网格创建了8条边（4个单元格 * 2条边/单元格）
-        let expected_edges = 8;
-        assert_eq!(grid.get_edges_count(), expected_edges);
+        let mut missing = OrderedEdgeBuffer::new(&[Direction4::East, Direction4::West]);
+        missing.add_cell(CellId::new(99));
+        assert_eq!(missing.flush(&mut grid), Err(GridError::NodeNotFound));
     }
 }
@@ -106,6 +106,8 @@
 //! - [`grid_system`] - 网格管理和图结构操作
 //! - [`wfc_util`] - WFC算法核心实现  
 //! - [`tile_set`] - 瓦片管理和兼容性规则
+//! - [`testing`] - 瓷砖集鲁棒性测试辅助
+//! - [`render`] - 二维网格的文本渲染
 //! - [`Cell`] - 单元格数据结构
 //! - [`Tile`] - 瓦片数据结构
 //!
@@ -145,7 +147,7 @@
 //! - **类型别名**：`CellId`, `EdgeId`, `TileId` 等核心类型
 //! - **数据结构**：`Cell`, `GraphEdge`, `Tile` 等基础结构
 //! - **错误处理**：`GridError` 枚举，提供详细的错误分类
-//! - **方向系统**：`DirectionTrait` 和 `Direction4` 实现
+//! - **方向系统**：`DirectionTrait` 以及 `Direction4`、`Direction4Full`、`Direction6`、`Direction8`、`DirectionHex`、`DirectionTri` 实现
 //!
 //! ### [`grid_system`] - 网格系统模块
 //!
@@ -333,13 +335,42 @@
 //! - **petgraph**: 高性能图数据结构库
 //! - **Rust Edition**: 2021及以上
 //! - **最低支持版本**: Rust 1.70+
+//! - **no_std**: 关闭默认的`std`特性并启用`alloc`特性后只依赖`alloc`，哈希表改用`hashbrown`，
+//!   错误类型只实现`Display`，`debug_print_*`方法和`testing`模块不可用，
+//!   未配置随机种子时使用固定种子
 //!
 //! ## 贡献和支持
 //!
 //! 欢迎贡献代码、报告问题或提出改进建议。项目遵循Rust社区的行为准则和贡献指南。
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("rlwfc requires either the `std` or the `alloc` feature");
+
+// 两个特性都未启用时只保留上面的编译错误，以下模块和导出都不参与编译
+
+/// 集合类型：启用`std`时使用标准库的哈希表，否则使用`hashbrown`
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod collections {
+    pub use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque};
+    #[cfg(not(feature = "std"))]
+    pub use hashbrown::{HashMap, HashSet};
+    #[cfg(feature = "std")]
+    pub use std::collections::{HashMap, HashSet};
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub mod grid_system;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod render;
+#[cfg(feature = "std")]
+pub mod testing;
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub mod tile_set;
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub mod wfc_manager;
 /**
  * @file lib.rs
@@ -351,21 +382,30 @@ pub mod wfc_manager;
  *
  * @copyright Copyright (c) 2025
  */
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub mod wfc_util;
 
 // 重新导出主要类型，方便使用
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub use wfc_util::{
     // 工具函数
+    find_all_in_2d_vector,
     find_in_2d_vector,
     // 数据结构
     Cell,
     // 基础类型
     CellId,
+    CellRemap,
     Cells,
     Direction4,
+    Direction4Full,
+    Direction6,
+    Direction8,
+    DirectionHex,
 
     // 方向系统
     DirectionTrait,
+    DirectionTri,
     EdgeId,
     Edges,
     GraphEdge,
@@ -376,12 +416,23 @@ pub use wfc_util::{
 
     TileId,
     Tiles,
+    TriOrientation,
     WFCGraph,
 };
 
-pub use grid_system::{GridBuilder, GridSystem};
-pub use tile_set::{TileSet, TileSetVirtual};
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use grid_system::{
+    ChainBuilder, Connectivity, GridBuilder, GridBuilder2D, GridSystem, OrderedEdgeBuffer,
+};
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use tile_set::{
+    extract_patterns, learn_adjacency_from_sample, learn_weights_from_sample, AdjacencyRules,
+    AdjacencyTileSet, CompatibilityFn, PatternData, TileSet, TileSetIter, TileSetVirtual,
+};
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub use wfc_manager::{
-    CellState, CellWfcData, DefaultInitializer, StepResult, WfcConfig, WfcError, WfcInitializer,
-    WfcManager,
+    CellConflict, CellState, CellWfcData, ConflictReport, ConflictStrategy, DefaultInitializer,
+    GlobalConstraint, ObservationHeuristic, PreflightIssue, PropagationMode, RunOutcome, ScoringFn,
+    StepResult, SystemSnapshot, TieBreak, WfcConfig, WfcConfigBuilder, WfcError, WfcInitializer,
+    WfcManager, WfcProgress, WfcStatistics,
 };
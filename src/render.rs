@@ -0,0 +1,129 @@
+//! # 文本渲染模块
+//!
+//! 本模块把二维网格的WFC状态渲染为字符串，供示例、调试输出和测试使用。
+//!
+//! ## 核心组件
+//!
+//! - [`render_ascii`] - 按`cell_{x}_{y}`命名约定逐行渲染二维网格
+//!
+//! ## 渲染规则
+//!
+//! - 已坍塌的单元格使用调用方提供的符号映射
+//! - 未坍塌的单元格显示为`?`，冲突的单元格显示为`X`
+//! - 网格中找不到对应名称的单元格显示为空格
+//!
+//! 每行以换行符结尾，结果不包含边框，调用方可以自行添加。
+//!
+//! ## 使用示例
+//!
+//! ```rust,no_run
+//! use rlwfc::render::render_ascii;
+//! # use rlwfc::WfcManager;
+//! # fn demo(manager: &WfcManager<i32>) {
+//! let text = render_ascii(manager, 10, 8, |tile_id| if tile_id == 0 { '.' } else { '#' });
+//! print!("{}", text);
+//! # }
+//! ```
+
+/**
+ * @file render.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief WFC系统文本渲染 - 二维网格的ASCII输出
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+use crate::wfc_manager::{CellState, WfcManager};
+use crate::wfc_util::TileId;
+use alloc::format;
+use alloc::string::String;
+
+/// 渲染`width × height`二维网格的当前状态
+///
+/// 单元格通过名称`cell_{x}_{y}`查找，与示例中正交网格构建器的命名一致。
+/// 返回`height`行文本，每行`width`个字符并以换行符结尾。
+pub fn render_ascii<EdgeData, F>(
+    manager: &WfcManager<EdgeData>,
+    width: usize,
+    height: usize,
+    symbol_fn: F,
+) -> String
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug + Send + Sync,
+    F: Fn(TileId) -> char,
+{
+    let grid = manager.get_grid();
+    let mut output = String::with_capacity((width + 1) * height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let symbol = match grid.get_cell_by_name(&format!("cell_{}_{}", x, y)) {
+                Some(cell_id) => match manager.get_cell_state(cell_id) {
+                    Ok(CellState::Collapsed) => match manager.get_collapsed_cell_tile(cell_id) {
+                        Ok(tile_id) => symbol_fn(tile_id),
+                        Err(_) => 'X',
+                    },
+                    Ok(CellState::Uncollapsed) => '?',
+                    Ok(CellState::Conflict) => 'X',
+                    Err(_) => ' ',
+                },
+                None => ' ',
+            };
+            output.push(symbol);
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+// =============================================================================
+// 测试模块
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid_system::{Connectivity, GridBuilder2D, GridSystem};
+    use crate::tile_set::AdjacencyTileSet;
+    use crate::wfc_manager::DefaultInitializer;
+    use crate::wfc_util::Direction4Full;
+
+    fn named_manager(width: usize, height: usize) -> WfcManager<i32> {
+        // GridBuilder2D按cell_{x}_{y}命名单元格
+        let grid = GridSystem::from_builder(GridBuilder2D::new(width, height, Connectivity::Four))
+            .unwrap();
+
+        let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+        tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        let mut manager = WfcManager::new(grid, Box::new(tile_set)).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let mut manager = named_manager(2, 2);
+        let symbol = |tile_id: TileId| if tile_id == 0 { '.' } else { '#' };
+        assert_eq!(render_ascii(&manager, 2, 2, symbol), "??\n??\n");
+
+        let grid = manager.get_grid();
+        let assignments: Vec<_> = [(0, 0, 0), (1, 0, 1), (0, 1, 1), (1, 1, 0)]
+            .into_iter()
+            .map(|(x, y, tile)| {
+                let cell = grid.get_cell_by_name(&format!("cell_{}_{}", x, y));
+                (cell.unwrap(), tile)
+            })
+            .collect();
+        manager.pre_collapse_many(&assignments).unwrap();
+
+        let text = render_ascii(&manager, 2, 2, symbol);
+        assert_eq!(text.lines().count(), 2);
+        assert_eq!(text, ".#\n#.\n");
+
+        // 超出网格范围的位置显示为空格
+        assert_eq!(render_ascii(&manager, 3, 1, symbol), ".# \n");
+    }
+}
@@ -0,0 +1,225 @@
+//! # 测试辅助模块
+//!
+//! 本模块为瓷砖集作者提供开箱即用的鲁棒性测试工具，无需自己编写多种子循环。
+//!
+//! ## 核心组件
+//!
+//! - [`fuzz_tileset`] - 在多个随机种子下运行完整的WFC流程（包括冲突修复）
+//! - [`FuzzSummary`] - 运行结果汇总：成功率与冲突统计
+//!
+//! ## 判定规则
+//!
+//! 每一次运行必须满足以下两种结局之一：
+//!
+//! - **成功完成**：所有单元格都处于`Collapsed`状态，坍塌结果指向有效瓷砖，
+//!   且每个真实单元格的瓷砖在邻居的最终瓷砖下仍能通过`judge_possibility`
+//! - **干净失败**：[`WfcManager::run_until`]返回`WfcError`
+//!
+//! 冲突修复失败后会按[`WfcConfig::max_conflict_retries`]重试，只有最终返回错误才算失败。
+//! 如果运行发生panic、声称完成但系统状态不一致，或在宽松的步数上限内既没有完成也没有报错，
+//! [`fuzz_tileset`]会直接panic，并在消息中给出出问题的种子，便于复现。
+//!
+//! ## 使用示例
+//!
+//! ```rust,no_run
+//! use rlwfc::testing::fuzz_tileset;
+//! # use rlwfc::{GridBuilder, GridSystem, GridError, TileSetVirtual, Tile, TileId};
+//! # struct MyBuilder;
+//! # impl GridBuilder for MyBuilder {
+//! #     fn build_grid_system(&mut self, _: &mut GridSystem) -> Result<(), GridError> { Ok(()) }
+//! # }
+//! # struct MyTileSet;
+//! # impl TileSetVirtual<i32> for MyTileSet {
+//! #     fn build_tile_set(&mut self) -> Result<(), GridError> { Ok(()) }
+//! #     fn judge_possibility(&self, _: &[Vec<TileId>], _: TileId) -> bool { true }
+//! #     fn get_tile(&self, _: TileId) -> Option<&Tile<i32>> { None }
+//! #     fn get_tile_count(&self) -> usize { 0 }
+//! #     fn get_all_tile_ids(&self) -> Vec<TileId> { vec![] }
+//! # }
+//!
+//! let summary = fuzz_tileset(|| MyTileSet, || MyBuilder, 0..100);
+//! assert!(summary.success_rate() > 0.9);
+//! ```
+
+/**
+ * @file testing.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief WFC系统测试辅助 - 多种子鲁棒性测试
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+use crate::grid_system::{GridBuilder, GridSystem};
+use crate::tile_set::TileSetVirtual;
+use crate::wfc_manager::{DefaultInitializer, RunOutcome, WfcConfig, WfcError, WfcManager};
+use crate::wfc_util::TileId;
+use std::panic::{self, AssertUnwindSafe};
+
+/// 每个单元格允许的坍塌与冲突修复步数，只用于发现无法结束的运行
+const STEPS_PER_CELL: usize = 64;
+
+/// 多种子测试的结果汇总
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FuzzSummary {
+    /// 总运行次数
+    pub runs: usize,
+    /// 成功完成的次数
+    pub successes: usize,
+    /// 干净失败的次数
+    pub failures: usize,
+    /// 冲突修复的轮数，包括失败后的重试（累计所有运行）
+    pub conflict_repairs: usize,
+    /// 因冲突无法解决而失败的运行次数
+    pub conflict_resolution_failures: usize,
+    /// 失败运行对应的种子，便于复现
+    pub failed_seeds: Vec<u64>,
+}
+
+impl FuzzSummary {
+    /// 成功率，范围`[0.0, 1.0]`；没有运行时返回`0.0`
+    pub fn success_rate(&self) -> f64 {
+        if self.runs == 0 {
+            return 0.0;
+        }
+        self.successes as f64 / self.runs as f64
+    }
+}
+
+/// 在多个随机种子下测试瓷砖集的鲁棒性
+///
+/// 由于[`WfcManager`]会获取网格和瓷砖集的所有权，这里接收两个工厂闭包，
+/// 每个种子都会构建全新的网格和瓷砖集。
+///
+/// # 参数
+///
+/// * `make_tile_set` - 瓷砖集工厂
+/// * `make_builder` - 网格构建器工厂
+/// * `seeds` - 要测试的随机种子
+///
+/// # Panics
+///
+/// - 网格构建失败
+/// - 某次运行发生panic
+/// - 某次运行声称完成但系统状态不一致
+/// - 某次运行在步数上限内既没有完成也没有返回错误
+pub fn fuzz_tileset<EdgeData, T, B>(
+    mut make_tile_set: impl FnMut() -> T,
+    mut make_builder: impl FnMut() -> B,
+    seeds: impl IntoIterator<Item = u64>,
+) -> FuzzSummary
+where
+    EdgeData: Clone + PartialEq + std::fmt::Debug + Send + Sync,
+    T: TileSetVirtual<EdgeData> + 'static,
+    B: GridBuilder,
+{
+    let mut summary = FuzzSummary::default();
+
+    for seed in seeds {
+        let grid = GridSystem::from_builder(make_builder())
+            .unwrap_or_else(|e| panic!("seed {}: grid builder failed: {}", seed, e));
+        let config = WfcConfig {
+            random_seed: Some(seed),
+            ..WfcConfig::default()
+        };
+        let mut manager = WfcManager::with_config(grid, Box::new(make_tile_set()), config)
+            .unwrap_or_else(|e| panic!("seed {}: manager creation failed: {}", seed, e));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            drive_to_end(&mut manager, &mut summary)
+        }));
+
+        summary.runs += 1;
+        match result {
+            Ok(Ok(RunOutcome::Completed)) => {
+                if let Err(reason) = check_consistency(manager) {
+                    panic!(
+                        "seed {}: inconsistent state after completion: {}",
+                        seed, reason
+                    );
+                }
+                summary.successes += 1;
+            }
+            Ok(Ok(outcome)) => panic!("seed {}: run did not finish: {:?}", seed, outcome),
+            Ok(Err(error)) => {
+                if matches!(error, WfcError::UnresolvableConflicts(_)) {
+                    summary.conflict_resolution_failures += 1;
+                }
+                summary.failures += 1;
+                summary.failed_seeds.push(seed);
+            }
+            Err(_) => panic!("seed {}: WFC run panicked", seed),
+        }
+    }
+
+    summary
+}
+
+/// 运行完整流程（包括冲突修复和重试），直到完成、返回错误或达到步数上限
+fn drive_to_end<EdgeData>(
+    manager: &mut WfcManager<EdgeData>,
+    summary: &mut FuzzSummary,
+) -> Result<RunOutcome, WfcError>
+where
+    EdgeData: Clone + PartialEq + std::fmt::Debug + Send + Sync,
+{
+    manager.initialize_with(&mut DefaultInitializer)?;
+
+    let max_steps = manager.get_grid().get_cells_count().max(1) * STEPS_PER_CELL;
+    let mut steps = 0;
+    manager.run_until(|progress| {
+        // 没有坍塌单元格的回调对应一轮冲突修复
+        if progress.collapsed.is_none() {
+            summary.conflict_repairs += 1;
+        }
+        steps += 1;
+        steps < max_steps
+    })
+}
+
+/// 检查完成后的系统状态是否一致，包括每个真实单元格与邻居的相容性
+fn check_consistency<EdgeData>(manager: WfcManager<EdgeData>) -> Result<(), String>
+where
+    EdgeData: Clone + PartialEq + std::fmt::Debug + Send + Sync,
+{
+    let tiles = manager.try_collapsed_tiles().map_err(|e| e.to_string())?;
+    let (grid, tile_set) = manager.into_parts();
+
+    for (&cell_id, &tile_id) in &tiles {
+        if tile_set.get_tile(tile_id).is_none() {
+            return Err(format!(
+                "cell {:?} collapsed to unknown tile {}",
+                cell_id, tile_id
+            ));
+        }
+        if grid.is_virtual_node(cell_id) {
+            continue;
+        }
+
+        // 按邻居顺序收集最终瓷砖，虚拟边界节点不构成约束
+        let neighbor_tiles: Vec<Vec<TileId>> = grid
+            .get_neighbors(cell_id)
+            .into_iter()
+            .map(|neighbor| match tiles.get(&neighbor) {
+                Some(&neighbor_tile) if !grid.is_virtual_node(neighbor) => vec![neighbor_tile],
+                _ => vec![],
+            })
+            .collect();
+        let compatible = if tile_set.uses_edge_labels() {
+            tile_set.judge_possibility_labeled(
+                &grid.get_neighbor_edge_types(cell_id),
+                &neighbor_tiles,
+                tile_id,
+            )
+        } else {
+            tile_set.judge_possibility(&neighbor_tiles, tile_id)
+        };
+        if !compatible {
+            return Err(format!(
+                "cell {:?} has tile {} incompatible with neighbors {:?}",
+                cell_id, tile_id, neighbor_tiles
+            ));
+        }
+    }
+    Ok(())
+}
@@ -19,6 +19,7 @@
 //!
 //! - [`TileSetVirtual`] trait：仅包含原C++的两个虚函数
 //! - [`TileSet`] struct：包含所有固定方法和数据存储
+//! - [`AdjacencyTileSet`] struct：内置边匹配规则的通用`TileSetVirtual`实现
 //!
 //! 这种设计的优势：
 //!
@@ -200,6 +201,11 @@
 //! - **不同约束规则**：通过实现`TileSetVirtual`支持各种约束逻辑
 //! - **多种边数据类型**：泛型设计支持任意边数据
 //! - **性能优化**：可以在具体实现中添加缓存、索引等优化
+//!
+//! ## 序列化
+//!
+//! 启用`serde`特性后，[`TileSet`]可以从JSON、RON等格式加载，便于在运行时读取
+//! 瓷砖配置而不必硬编码。瓷砖ID即其在列表中的位置，加载时拒绝ID不连续的数据。
 
 /**
  * @file tile_set.rs
@@ -211,8 +217,18 @@
  *
  * @copyright Copyright (c) 2025
  */
+use crate::grid_system::GridSystem;
 use crate::wfc_util::*;
 
+use crate::collections::{BTreeSet, HashMap, HashSet};
+use alloc::boxed::Box;
+#[cfg(feature = "serde")]
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
 // =============================================================================
 // 虚函数特性 - 仅包含原C++的两个虚函数
 // =============================================================================
@@ -249,6 +265,12 @@ use crate::wfc_util::*;
 /// - `PartialEq`：支持相等性比较
 /// - `Debug`：支持调试输出
 ///
+/// ## 线程安全
+///
+/// 瓷砖集需要满足`Send + Sync`，与[`WfcManager`](crate::WfcManager)对`EdgeData`的要求一致。
+/// 这一约束不随特性变化：开启`parallel`特性时约束传播会在多个线程中同时调用
+/// [`judge_possibility(...)`]，而依赖图中任何一个crate开启该特性都会对所有使用者生效。
+///
 /// ## 实现示例
 ///
 /// ```rust,no_run
@@ -300,9 +322,9 @@ use crate::wfc_util::*;
 ///
 /// [`build_tile_set()`]: TileSetVirtual::build_tile_set
 /// [`judge_possibility(...)`]: TileSetVirtual::judge_possibility
-pub trait TileSetVirtual<EdgeData>
+pub trait TileSetVirtual<EdgeData>: Send + Sync
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug,
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
 {
     /// 构建瓷砖集 - 对应C++的buildTileSet()虚函数
     ///
@@ -469,6 +491,47 @@ where
     /// - 利用边数据顺序约定避免额外的映射开销
     fn judge_possibility(&self, neighbor_possibilities: &[Vec<TileId>], candidate: TileId) -> bool;
 
+    /// 瓷砖集是否按边标签判断可能性
+    ///
+    /// 返回`true`时，[`WfcManager`](crate::WfcManager)在传播约束时改为调用
+    /// [`judge_possibility_labeled`](Self::judge_possibility_labeled)。
+    /// 默认返回`false`，管理器不会收集边标签。
+    fn uses_edge_labels(&self) -> bool {
+        false
+    }
+
+    /// 按边标签判断瓷砖可能性，适用于各单元格邻居数量不同的不规则图
+    ///
+    /// `edge_labels[i]`是通向第`i`个邻居的边的[`GraphEdge::edge_type`]，
+    /// 与`neighbor_possibilities[i]`一一对应，长度等于该单元格的实际邻居数量。
+    /// Voronoi、Delaunay等网格没有固定的方向顺序，实现可以按标签而不是位置决定兼容性。
+    ///
+    /// 默认实现忽略标签，直接调用按位置判断的[`judge_possibility`](Self::judge_possibility)，
+    /// 现有瓷砖集无需修改即可继续使用。
+    ///
+    /// 预检和[`PropagationMode::ArcConsistency`](crate::PropagationMode::ArcConsistency)
+    /// 按位置探测两两兼容关系，对使用边标签的瓷砖集没有意义：
+    /// 预检仍调用位置版本，弧一致性模式会退化为逐一判断。
+    fn judge_possibility_labeled(
+        &self,
+        edge_labels: &[Option<&str>],
+        neighbor_possibilities: &[Vec<TileId>],
+        candidate: TileId,
+    ) -> bool {
+        let _ = edge_labels;
+        self.judge_possibility(neighbor_possibilities, candidate)
+    }
+
+    /// 每个单元格应有的邻居数量
+    ///
+    /// 返回`Some(n)`时，[`WfcManager::initialize_with`](crate::WfcManager::initialize_with)
+    /// 会检查每个真实单元格的邻居数量，不等于`n`时返回
+    /// [`WfcError::NeighborCountMismatch`](crate::WfcError::NeighborCountMismatch)，
+    /// 避免按位置比较边数据时静默地用错方向。默认返回`None`，不做检查。
+    fn expected_neighbor_count(&self) -> Option<usize> {
+        None
+    }
+
     /// 获取指定ID的瓷砖
     fn get_tile(&self, tile_id: TileId) -> Option<&Tile<EdgeData>>;
 
@@ -477,6 +540,78 @@ where
 
     /// 获取所有瓷砖ID列表
     fn get_all_tile_ids(&self) -> Vec<TileId>;
+
+    /// 检查瓷砖集的自洽性，返回永远无法放置的"死"瓷砖
+    ///
+    /// 对每个瓷砖的每条边，逐一尝试把其他瓷砖（包括它自己）放在该方向上，
+    /// 只要有一个能通过[`judge_possibility`](Self::judge_possibility)即可。
+    /// 某个方向上没有任何可相邻瓷砖的瓷砖在该方向有邻居时必然冲突，
+    /// 这类瓷砖按ID升序返回。
+    ///
+    /// 方向数量取自瓷砖自身的边数，无需指定方向类型；需要同时检查网格结构时
+    /// 使用[`WfcManager::preflight`](crate::WfcManager::preflight)。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{AdjacencyTileSet, Direction4Full, TileSetVirtual};
+    ///
+    /// // 边数据顺序：[北, 西, 南, 东]
+    /// let mut tile_set = AdjacencyTileSet::<&str, Direction4Full>::new();
+    /// tile_set.add_tile(vec!["grass"; 4], 1);
+    /// let cliff = tile_set.add_tile(vec!["cliff", "grass", "grass", "grass"], 1);
+    ///
+    /// assert_eq!(tile_set.validate(), Err(vec![cliff]));
+    /// ```
+    fn validate(&self) -> Result<(), Vec<TileId>> {
+        let tile_ids = self.get_all_tile_ids();
+        let dead_tiles: Vec<TileId> = tile_ids
+            .iter()
+            .copied()
+            .filter(|&tile_id| {
+                let edge_count = self.get_tile(tile_id).map_or(0, |tile| tile.edge_count());
+                (0..edge_count).any(|direction_index| {
+                    !tile_ids.iter().any(|&other| {
+                        let mut neighbor_possibilities = vec![vec![]; edge_count];
+                        neighbor_possibilities[direction_index] = vec![other];
+                        self.judge_possibility(&neighbor_possibilities, tile_id)
+                    })
+                })
+            })
+            .collect();
+
+        if dead_tiles.is_empty() {
+            Ok(())
+        } else {
+            Err(dead_tiles)
+        }
+    }
+
+    /// 复制瓷砖集，供[`WfcManager::clone_for_new_run`](crate::WfcManager::clone_for_new_run)
+    /// 为每次独立运行准备副本
+    ///
+    /// 默认返回`None`，表示不支持复制；实现了`Clone`的瓷砖集通常返回
+    /// `Some(Box::new(self.clone()))`。
+    fn clone_box(&self) -> Option<Box<dyn TileSetVirtual<EdgeData>>> {
+        None
+    }
+
+    /// 瓷砖权重的版本号
+    ///
+    /// 权重可能变化的瓷砖集应在每次修改后返回不同的值，
+    /// 管理器在计算熵值时发现版本号变化会丢弃熵值缓存。
+    /// 默认返回0，表示权重不会变化。
+    fn weight_generation(&self) -> u64 {
+        0
+    }
+
+    /// 可修改的瓷砖存储，供[`WfcManager::set_tile_weight`](crate::WfcManager::set_tile_weight)
+    /// 在瓷砖集交给管理器之后调整权重
+    ///
+    /// 默认返回`None`，表示不支持修改。
+    fn tile_set_mut(&mut self) -> Option<&mut TileSet<EdgeData>> {
+        None
+    }
 }
 
 // =============================================================================
@@ -485,21 +620,31 @@ where
 
 /// 瓷砖集具体实现 - 包含所有固定方法和数据存储
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TileSet<EdgeData>
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug,
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
 {
     /// 瓷砖列表 - 对应C++的tiles_成员
     tiles: Vec<Tile<EdgeData>>,
+    /// 已删除瓷砖的ID（墓碑），保证其余瓷砖ID不变
+    removed: BTreeSet<TileId>,
+    /// 权重版本号，每次修改权重或删除瓷砖时递增
+    #[cfg_attr(feature = "serde", serde(skip))]
+    generation: u64,
 }
 
 impl<EdgeData> TileSet<EdgeData>
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug,
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
 {
     /// 创建新的瓷砖集
     pub fn new() -> Self {
-        Self { tiles: Vec::new() }
+        Self {
+            tiles: Vec::new(),
+            removed: BTreeSet::new(),
+            generation: 0,
+        }
     }
 
     /// 添加瓷砖 - 对应C++的addTile方法
@@ -623,51 +768,971 @@ where
         tile_id
     }
 
+    /// 添加带名称和标签的瓷砖
+    ///
+    /// 名称和标签只是附加在瓷砖上的用户数据，不影响兼容性判断和权重，
+    /// 导出结果给渲染器时可以通过[`TileSet::get_tile`]读回，无需另建以`TileId`为键的表。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::TileSet;
+    ///
+    /// let mut tile_set = TileSet::new();
+    /// let road = tile_set.add_tile_with_meta(
+    ///     vec![1, 0, 1, 0],
+    ///     2,
+    ///     Some("road".to_string()),
+    ///     vec!["sprite:12".to_string()],
+    /// );
+    ///
+    /// let tile = tile_set.get_tile(road).unwrap();
+    /// assert_eq!(tile.name.as_deref(), Some("road"));
+    /// assert_eq!(tile.tags, ["sprite:12"]);
+    /// ```
+    pub fn add_tile_with_meta(
+        &mut self,
+        edges: Vec<EdgeData>,
+        weight: i32,
+        name: Option<String>,
+        tags: Vec<String>,
+    ) -> TileId {
+        let tile_id = self.add_tile(edges, weight);
+        let tile = &mut self.tiles[tile_id];
+        tile.name = name;
+        tile.tags = tags;
+        tile_id
+    }
+
+    /// 添加瓷砖及其顺时针旋转90°、180°、270°的变体
+    ///
+    /// 在 [北, 西, 南, 东] 顺序下，顺时针旋转90°后新的北边来自原来的西边，
+    /// 即`rotated[i] = edges[(i + 1) % 4]`。旋转后边数据完全相同的变体只添加一次，
+    /// 所有变体使用相同的权重。
+    ///
+    /// 边数量不为4时无法按该约定旋转，只添加原始瓷砖。
+    ///
+    /// # 返回值
+    ///
+    /// 实际添加的瓷砖ID列表，第一个为原始瓷砖
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::TileSet;
+    ///
+    /// let mut tile_set = TileSet::new();
+    ///
+    /// // 拐角瓷砖的四个旋转各不相同
+    /// let corner = tile_set.add_tile_with_rotations(vec![1, 1, 0, 0], 1);
+    /// assert_eq!(corner.len(), 4);
+    /// assert_eq!(tile_set.get_tile(corner[1]).unwrap().edges, vec![1, 0, 0, 1]);
+    /// ```
+    pub fn add_tile_with_rotations(&mut self, edges: Vec<EdgeData>, weight: i32) -> Vec<TileId> {
+        let mut variants = vec![edges];
+        if variants[0].len() == 4 {
+            for _ in 0..3 {
+                let mut rotated = variants[variants.len() - 1].clone();
+                rotated.rotate_left(1);
+                variants.push(rotated);
+            }
+        }
+
+        self.add_unique_variants(variants, weight)
+    }
+
+    /// 添加瓷砖及其水平、垂直镜像变体
+    ///
+    /// 在 [北, 西, 南, 东] 顺序下，水平镜像交换西边和东边（索引1和3），
+    /// 垂直镜像交换北边和南边（索引0和2）。适用于具有手性的瓷砖集。
+    /// 与[`add_tile_with_rotations`](Self::add_tile_with_rotations)相同，
+    /// 边数据相同的变体只添加一次，所有变体使用相同的权重。
+    ///
+    /// 边数量不为4时无法按该约定镜像，只添加原始瓷砖。
+    ///
+    /// # 返回值
+    ///
+    /// 实际添加的瓷砖ID列表，依次为原始瓷砖、水平镜像、垂直镜像（去重后）
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::TileSet;
+    ///
+    /// let mut tile_set = TileSet::new();
+    ///
+    /// // 北、西开口的L形瓷砖
+    /// let l_tile = tile_set.add_tile_with_reflections(vec![1, 1, 0, 0], 1);
+    /// assert_eq!(l_tile.len(), 3);
+    /// assert_eq!(tile_set.get_tile(l_tile[1]).unwrap().edges, vec![1, 0, 0, 1]);
+    /// ```
+    pub fn add_tile_with_reflections(&mut self, edges: Vec<EdgeData>, weight: i32) -> Vec<TileId> {
+        let mut variants = vec![edges];
+        if variants[0].len() == 4 {
+            let mut horizontal = variants[0].clone();
+            horizontal.swap(1, 3);
+            let mut vertical = variants[0].clone();
+            vertical.swap(0, 2);
+            variants.push(horizontal);
+            variants.push(vertical);
+        }
+
+        self.add_unique_variants(variants, weight)
+    }
+
+    /// 依次添加互不相同的边数据变体，返回新瓷砖的ID
+    fn add_unique_variants(&mut self, variants: Vec<Vec<EdgeData>>, weight: i32) -> Vec<TileId> {
+        let mut added: Vec<TileId> = Vec::new();
+        for edges in variants {
+            let duplicate = added
+                .iter()
+                .any(|&tile_id| self.tiles[tile_id].edges == edges);
+            if !duplicate {
+                added.push(self.add_tile(edges, weight));
+            }
+        }
+        added
+    }
+
     /// 获取所有瓷砖 - 对应C++的getAllTiles()方法
+    ///
+    /// 切片按瓷砖ID索引，[`remove_tile`](Self::remove_tile)删除的瓷砖仍然保留在原位置。
+    /// 需要跳过已删除的瓷砖时使用[`iter`](Self::iter)。
     pub fn get_all_tiles(&self) -> &[Tile<EdgeData>] {
         &self.tiles
     }
 
-    /// 获取所有瓷砖ID
+    /// 按ID顺序遍历`(瓷砖ID, 瓷砖)`，不含已删除的瓷砖
+    ///
+    /// 与[`get_all_tiles`](Self::get_all_tiles)不同，已删除的瓷砖不会出现。
+    /// `&TileSet`也实现了`IntoIterator`，可以直接用于`for`循环。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::TileSet;
+    ///
+    /// let mut tile_set = TileSet::new();
+    /// tile_set.add_tile(vec!["grass"; 4], 1);
+    /// let road = tile_set.add_tile(vec!["road", "grass", "road", "grass"], 1);
+    ///
+    /// let roads: Vec<_> = tile_set
+    ///     .iter()
+    ///     .filter(|(_, tile)| tile.edges.contains(&"road"))
+    ///     .map(|(tile_id, _)| tile_id)
+    ///     .collect();
+    /// assert_eq!(roads, vec![road]);
+    ///
+    /// for (tile_id, tile) in &tile_set {
+    ///     assert_eq!(tile.id, tile_id);
+    /// }
+    /// ```
+    pub fn iter(&self) -> TileSetIter<'_, EdgeData> {
+        TileSetIter {
+            tiles: self.tiles.iter(),
+            removed: &self.removed,
+        }
+    }
+
+    /// 获取所有瓷砖ID，不含已删除的瓷砖
     pub fn get_all_tile_ids(&self) -> Vec<TileId> {
-        (0..self.tiles.len()).collect()
+        (0..self.tiles.len())
+            .filter(|tile_id| !self.removed.contains(tile_id))
+            .collect()
     }
 
-    /// 根据ID获取瓷砖
+    /// 根据ID获取瓷砖，已删除的瓷砖返回`None`
     pub fn get_tile(&self, tile_id: TileId) -> Option<&Tile<EdgeData>> {
+        if self.removed.contains(&tile_id) {
+            return None;
+        }
         self.tiles.get(tile_id)
     }
 
-    /// 获取瓷砖数量
+    /// 获取瓷砖数量，不含已删除的瓷砖
+    ///
+    /// 删除瓷砖后ID不再连续，最大ID可能大于等于该数量。
     pub fn get_tile_count(&self) -> usize {
-        self.tiles.len()
+        self.tiles.len() - self.removed.len()
+    }
+
+    /// 删除瓷砖
+    ///
+    /// 瓷砖ID就是其在内部向量中的索引，为了不让后续瓷砖的ID移位，
+    /// 删除采用墓碑方式：被删除的瓷砖只做标记，不再出现在
+    /// [`get_all_tile_ids`](Self::get_all_tile_ids)中，
+    /// [`get_tile`](Self::get_tile)也返回`None`。
+    /// 因此基于`get_tile`的`judge_possibility`实现（如[`AdjacencyTileSet`]）
+    /// 会自动拒绝已删除的瓷砖。之后添加的瓷砖使用新的ID，不会复用被删除的ID。
+    ///
+    /// # 返回值
+    ///
+    /// * `Some(tile)` - 被删除瓷砖的数据
+    /// * `None` - 瓷砖不存在或已被删除
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::TileSet;
+    ///
+    /// let mut tile_set = TileSet::new();
+    /// let grass = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+    /// let water = tile_set.add_tile(vec![1, 1, 1, 1], 1);
+    ///
+    /// let removed = tile_set.remove_tile(grass).unwrap();
+    /// assert_eq!(removed.edges, vec![0, 0, 0, 0]);
+    /// assert_eq!(tile_set.get_all_tile_ids(), vec![water]);
+    /// assert!(tile_set.get_tile(grass).is_none());
+    /// ```
+    pub fn remove_tile(&mut self, tile_id: TileId) -> Option<Tile<EdgeData>> {
+        let tile = self.get_tile(tile_id)?.clone();
+        self.removed.insert(tile_id);
+        self.generation += 1;
+        Some(tile)
+    }
+
+    /// 修改瓷砖权重
+    ///
+    /// 权重只在计算熵值和选择瓷砖时读取。每次修改都会递增[`generation`](Self::generation)，
+    /// [`WfcManager`](crate::WfcManager)据此丢弃按旧权重缓存的熵值。
+    /// 瓷砖集已经交给管理器时，应通过[`WfcManager::set_tile_weight`](crate::WfcManager::set_tile_weight)
+    /// 修改，管理器会同时更新各单元格上增量维护的权重和。
+    ///
+    /// # 错误情况
+    ///
+    /// - `GridError::IndexOutOfBounds` - 瓷砖不存在或已被删除
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::TileSet;
+    ///
+    /// let mut tile_set = TileSet::new();
+    /// let grass = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+    ///
+    /// tile_set.set_weight(grass, 5).unwrap();
+    /// assert_eq!(tile_set.get_tile(grass).unwrap().weight, 5);
+    /// assert!(tile_set.set_weight(42, 5).is_err());
+    /// ```
+    pub fn set_weight(&mut self, tile_id: TileId, weight: i32) -> Result<(), GridError> {
+        if self.removed.contains(&tile_id) {
+            return Err(GridError::IndexOutOfBounds);
+        }
+        let tile = self
+            .tiles
+            .get_mut(tile_id)
+            .ok_or(GridError::IndexOutOfBounds)?;
+        tile.weight = weight;
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// 权重版本号
+    ///
+    /// 每次[`set_weight`](Self::set_weight)、[`apply_sample_weights`](Self::apply_sample_weights)、
+    /// [`remove_tile`](Self::remove_tile)或[`clear`](Self::clear)后递增，
+    /// 版本号相同时按可能性集合缓存的熵值仍然有效。
+    pub fn generation(&self) -> u64 {
+        self.generation
     }
 
     /// 清空瓷砖集
     pub fn clear(&mut self) {
         self.tiles.clear();
+        self.removed.clear();
+        self.generation += 1;
     }
 
-    /// 检查瓷砖是否存在
+    /// 检查瓷砖是否存在，已删除的瓷砖视为不存在
     pub fn contains_tile(&self, tile_id: TileId) -> bool {
-        tile_id < self.tiles.len()
+        self.get_tile(tile_id).is_some()
     }
 
     /// 检查瓷砖集是否为空
     pub fn is_empty(&self) -> bool {
-        self.tiles.is_empty()
+        self.get_tile_count() == 0
+    }
+
+    /// 判断瓷砖`b`能否位于瓷砖`a`的`direction`方向一侧
+    ///
+    /// 比较`a`在`direction`方向的边与`b`在相反方向的边是否相等，
+    /// 无需构造完整的`neighbor_possibilities`数组，便于直接测试瓷砖集。
+    ///
+    /// # 参数
+    ///
+    /// * `a` - 中心瓷砖ID
+    /// * `b` - 邻居瓷砖ID
+    /// * `direction` - 邻居相对于`a`的方向索引
+    /// * `opposite` - 方向索引到相反方向索引的映射，
+    ///   例如 [北, 西, 南, 东] 顺序下为 `|i| (i + 2) % 4`
+    ///
+    /// # 返回值
+    ///
+    /// 任一瓷砖不存在或边索引越界时返回`false`
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::TileSet;
+    ///
+    /// let mut tile_set = TileSet::new();
+    /// let road = tile_set.add_tile(vec![0, 1, 0, 1], 1);
+    /// let end = tile_set.add_tile(vec![0, 1, 0, 0], 1);
+    ///
+    /// let opposite = |i| (i + 2) % 4;
+    /// assert!(tile_set.are_compatible(road, end, 3, opposite)); // end在road东侧
+    /// assert!(!tile_set.are_compatible(end, road, 3, opposite)); // road在end东侧
+    /// ```
+    pub fn are_compatible(
+        &self,
+        a: TileId,
+        b: TileId,
+        direction: usize,
+        opposite: impl Fn(usize) -> usize,
+    ) -> bool {
+        let (Some(tile_a), Some(tile_b)) = (self.get_tile(a), self.get_tile(b)) else {
+            return false;
+        };
+
+        match (
+            tile_a.edges.get(direction),
+            tile_b.edges.get(opposite(direction)),
+        ) {
+            (Some(edge_a), Some(edge_b)) => edge_a == edge_b,
+            _ => false,
+        }
+    }
+
+    /// 按样例中各瓷砖的出现次数设置权重
+    ///
+    /// 权重取值为[`learn_weights_from_sample`]统计的出现次数，使生成结果的
+    /// 瓷砖比例接近样例。样例中未出现的瓷砖保持原有权重，
+    /// 不属于本瓷砖集或已删除的ID会被忽略。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::TileSet;
+    ///
+    /// let mut tile_set = TileSet::new();
+    /// let grass = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+    /// let water = tile_set.add_tile(vec![1, 1, 1, 1], 1);
+    ///
+    /// tile_set.apply_sample_weights(&[grass, grass, water, grass]);
+    /// assert_eq!(tile_set.get_tile(grass).unwrap().weight, 3);
+    /// assert_eq!(tile_set.get_tile(water).unwrap().weight, 1);
+    /// ```
+    pub fn apply_sample_weights(&mut self, samples: &[TileId]) {
+        for (tile_id, weight) in learn_weights_from_sample(samples) {
+            if self.removed.contains(&tile_id) {
+                continue;
+            }
+            if let Some(tile) = self.tiles.get_mut(tile_id) {
+                tile.weight = weight;
+                self.generation += 1;
+            }
+        }
     }
 }
 
 impl<EdgeData> Default for TileSet<EdgeData>
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug,
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
 {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// [`TileSet::iter`]返回的迭代器，按ID顺序产生`(瓷砖ID, 瓷砖)`并跳过已删除的瓷砖
+#[derive(Debug, Clone)]
+pub struct TileSetIter<'a, EdgeData>
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
+{
+    tiles: core::slice::Iter<'a, Tile<EdgeData>>,
+    removed: &'a BTreeSet<TileId>,
+}
+
+impl<'a, EdgeData> Iterator for TileSetIter<'a, EdgeData>
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
+{
+    type Item = (TileId, &'a Tile<EdgeData>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let removed = self.removed;
+        self.tiles
+            .find(|tile| !removed.contains(&tile.id))
+            .map(|tile| (tile.id, tile))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.tiles.size_hint().1)
+    }
+}
+
+impl<'a, EdgeData> IntoIterator for &'a TileSet<EdgeData>
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
+{
+    type Item = (TileId, &'a Tile<EdgeData>);
+    type IntoIter = TileSetIter<'a, EdgeData>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// 从样例赋值中统计瓷砖频率，作为权重提示
+///
+/// `samples`是一次已知结果中每个单元格的瓷砖ID（顺序无关），
+/// 返回值中每个瓷砖的权重等于其出现次数，因此权重之比就是频率之比。
+/// 未出现的瓷砖不会出现在返回值中。
+///
+/// # 示例
+///
+/// ```rust
+/// use rlwfc::learn_weights_from_sample;
+///
+/// let weights = learn_weights_from_sample(&[0, 0, 0, 1]);
+/// assert_eq!(weights[&0], 3);
+/// assert_eq!(weights[&1], 1);
+/// ```
+pub fn learn_weights_from_sample(samples: &[TileId]) -> HashMap<TileId, i32> {
+    let mut weights = HashMap::new();
+    for &tile_id in samples {
+        *weights.entry(tile_id).or_insert(0) += 1;
+    }
+    weights
+}
+
+#[cfg(feature = "serde")]
+impl<'de, EdgeData> serde::Deserialize<'de> for TileSet<EdgeData>
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct TileSetData<EdgeData: Clone + PartialEq + core::fmt::Debug> {
+            tiles: Vec<Tile<EdgeData>>,
+            #[serde(default)]
+            removed: BTreeSet<TileId>,
+        }
+
+        // 瓷砖ID必须与列表位置一致，否则get_tile(id)会取到错误的瓷砖
+        let data = TileSetData::deserialize(deserializer)?;
+        if let Some((index, tile)) = data
+            .tiles
+            .iter()
+            .enumerate()
+            .find(|(index, tile)| tile.id != *index)
+        {
+            return Err(serde::de::Error::custom(format!(
+                "tile at position {} has non-contiguous id {}",
+                index, tile.id
+            )));
+        }
+        if let Some(&tile_id) = data.removed.iter().find(|&&id| id >= data.tiles.len()) {
+            return Err(serde::de::Error::custom(format!(
+                "removed tile id {} is out of range",
+                tile_id
+            )));
+        }
+        Ok(Self {
+            tiles: data.tiles,
+            removed: data.removed,
+            generation: 0,
+        })
+    }
+}
+
+// =============================================================================
+// 基于边匹配规则的通用瓷砖集
+// =============================================================================
+
+/// 边兼容性判断函数
+///
+/// 参数依次为候选瓷砖在某方向的边、邻居瓷砖在相反方向的边，
+/// 返回两条边能否相接。用于表达非对称规则（如插座"A"只能连接插座"a"）。
+///
+/// 与瓷砖集一样需要满足`Send + Sync`。
+pub type CompatibilityFn<EdgeData> = Box<dyn Fn(&EdgeData, &EdgeData) -> bool + Send + Sync>;
+
+/// 瓷砖集内部共享的边兼容性判断函数，由[`CompatibilityFn`]转换而来
+type SharedCompatibilityFn<EdgeData> = Arc<dyn Fn(&EdgeData, &EdgeData) -> bool + Send + Sync>;
+
+/// 基于边匹配规则的通用瓷砖集
+///
+/// 内置最常见的约束规则：候选瓷砖在某方向的边必须与该方向邻居瓷砖
+/// 相反方向的边相等。只需要简单边匹配的应用无需再手写`judge_possibility`。
+/// 需要非对称规则时，可以通过[`with_compatibility`](Self::with_compatibility)
+/// 提供自定义的[`CompatibilityFn`]替代相等判断。
+///
+/// 还可以通过[`set_wildcard`](Self::set_wildcard)指定一个通配边值，
+/// 通配边与任何相对的边都兼容，适合表示"空"或"任意"插槽。
+///
+/// 相反方向的边索引由方向系统`D`的[`DirectionTrait::opposite_edge_index`]提供，
+/// 因此同一实现可用于[`Direction4Full`]、[`Direction6`]、[`Direction8`]等网格。
+/// 邻居数量必须等于`D`的方向数（没有出边的边界虚拟节点除外），
+/// 否则`judge_possibility`返回`false`，交给管理器时初始化会报错。
+///
+/// 瓷砖需要在交给[`WfcManager`](crate::WfcManager)之前添加完毕，
+/// `build_tile_set()`不会修改已有瓷砖。
+///
+/// # 示例
+///
+/// ```rust
+/// use rlwfc::{AdjacencyTileSet, Direction4Full, TileSetVirtual};
+///
+/// let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+/// let road = tile_set.add_tile(vec![0, 1, 0, 1], 1);
+/// let end = tile_set.add_tile(vec![0, 1, 0, 0], 1);
+///
+/// // 东侧邻居只能是end时，道路瓷砖仍然可行；反过来则不行
+/// let neighbors = vec![vec![], vec![], vec![], vec![end]];
+/// assert!(tile_set.judge_possibility(&neighbors, road));
+/// assert!(!tile_set.judge_possibility(&neighbors, end));
+/// ```
+#[derive(Clone)]
+pub struct AdjacencyTileSet<EdgeData, D>
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
+    D: DirectionTrait,
+{
+    /// 瓷砖存储
+    tiles: TileSet<EdgeData>,
+    /// 自定义边兼容性规则，`None`表示使用相等判断；复制瓷砖集时共享同一个闭包
+    compatibility: Option<SharedCompatibilityFn<EdgeData>>,
+    /// 通配边值，与任何边都兼容
+    wildcard: Option<EdgeData>,
+    /// 方向系统标记
+    _direction: core::marker::PhantomData<D>,
+}
+
+impl<EdgeData, D> AdjacencyTileSet<EdgeData, D>
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
+    D: DirectionTrait,
+{
+    /// 创建空的瓷砖集
+    pub fn new() -> Self {
+        Self::from_tile_set(TileSet::new())
+    }
+
+    /// 使用已有的瓷砖创建瓷砖集
+    pub fn from_tile_set(tiles: TileSet<EdgeData>) -> Self {
+        Self {
+            tiles,
+            compatibility: None,
+            wildcard: None,
+            _direction: core::marker::PhantomData,
+        }
+    }
+
+    /// 使用自定义边兼容性规则创建空的瓷砖集
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{AdjacencyTileSet, Direction4Full, TileSetVirtual};
+    ///
+    /// // 插座只能连接对应的插头："A" ↔ "a"
+    /// let mut tile_set = AdjacencyTileSet::<&str, Direction4Full>::with_compatibility(|a, b| {
+    ///     a.eq_ignore_ascii_case(b) && a != b
+    /// });
+    /// let socket = tile_set.add_tile(vec!["A", "A", "A", "A"], 1);
+    /// let plug = tile_set.add_tile(vec!["a", "a", "a", "a"], 1);
+    ///
+    /// let neighbors = vec![vec![socket], vec![], vec![], vec![]];
+    /// assert!(tile_set.judge_possibility(&neighbors, plug));
+    /// assert!(!tile_set.judge_possibility(&neighbors, socket));
+    /// ```
+    pub fn with_compatibility(
+        compatibility: impl Fn(&EdgeData, &EdgeData) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let mut tile_set = Self::new();
+        let compatibility: CompatibilityFn<EdgeData> = Box::new(compatibility);
+        tile_set.compatibility = Some(Arc::from(compatibility));
+        tile_set
+    }
+
+    /// 添加瓷砖，边数据顺序与[`TileSet::add_tile`]相同
+    pub fn add_tile(&mut self, edges: Vec<EdgeData>, weight: i32) -> TileId {
+        self.tiles.add_tile(edges, weight)
+    }
+
+    /// 添加带名称和标签的瓷砖，参见[`TileSet::add_tile_with_meta`]
+    pub fn add_tile_with_meta(
+        &mut self,
+        edges: Vec<EdgeData>,
+        weight: i32,
+        name: Option<String>,
+        tags: Vec<String>,
+    ) -> TileId {
+        self.tiles.add_tile_with_meta(edges, weight, name, tags)
+    }
+
+    /// 设置通配边值
+    ///
+    /// 候选边或邻居边等于通配值时，两条边总是兼容，优先于相等判断和自定义规则。
+    /// 通配边不会排除任何邻居瓷砖，因此也不会降低邻居的可能性和熵值：
+    /// 四边都是通配边的瓷砖可以出现在任何位置，它自身也会一直保留在邻居的可能性中。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{AdjacencyTileSet, Direction4Full, TileSetVirtual};
+    ///
+    /// let mut tile_set = AdjacencyTileSet::<&str, Direction4Full>::new();
+    /// tile_set.set_wildcard("*");
+    /// let grass = tile_set.add_tile(vec!["grass"; 4], 1);
+    /// let empty = tile_set.add_tile(vec!["*"; 4], 1);
+    ///
+    /// let neighbors = vec![vec![grass], vec![], vec![], vec![]];
+    /// assert!(tile_set.judge_possibility(&neighbors, empty));
+    /// ```
+    pub fn set_wildcard(&mut self, wildcard: EdgeData) {
+        self.wildcard = Some(wildcard);
+    }
+
+    /// 获取底层瓷砖集
+    pub fn tiles(&self) -> &TileSet<EdgeData> {
+        &self.tiles
+    }
+
+    /// 获取底层瓷砖集的可变引用，可用于批量生成旋转、镜像变体
+    pub fn tiles_mut(&mut self) -> &mut TileSet<EdgeData> {
+        &mut self.tiles
+    }
+
+    /// 判断候选边能否与邻居的相反边相接
+    fn edges_compatible(&self, candidate_edge: &EdgeData, neighbor_edge: &EdgeData) -> bool {
+        if let Some(wildcard) = &self.wildcard {
+            if candidate_edge == wildcard || neighbor_edge == wildcard {
+                return true;
+            }
+        }
+        match &self.compatibility {
+            Some(compatibility) => compatibility(candidate_edge, neighbor_edge),
+            None => candidate_edge == neighbor_edge,
+        }
+    }
+}
+
+impl<EdgeData, D> core::fmt::Debug for AdjacencyTileSet<EdgeData, D>
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
+    D: DirectionTrait,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AdjacencyTileSet")
+            .field("tiles", &self.tiles)
+            .field("custom_compatibility", &self.compatibility.is_some())
+            .field("wildcard", &self.wildcard)
+            .finish()
+    }
+}
+
+impl<EdgeData, D> Default for AdjacencyTileSet<EdgeData, D>
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
+    D: DirectionTrait,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<EdgeData, D> TileSetVirtual<EdgeData> for AdjacencyTileSet<EdgeData, D>
+where
+    EdgeData: Clone + PartialEq + core::fmt::Debug + Send + Sync + 'static,
+    D: DirectionTrait + Send + Sync + 'static,
+{
+    fn build_tile_set(&mut self) -> Result<(), GridError> {
+        // 瓷砖在构造时已经添加完毕
+        Ok(())
+    }
+
+    fn judge_possibility(&self, neighbor_possibilities: &[Vec<TileId>], candidate: TileId) -> bool {
+        // 邻居数量与方向系统不一致时，边下标无法对应到方向；
+        // 没有出边的边界虚拟节点不受约束
+        if !neighbor_possibilities.is_empty()
+            && neighbor_possibilities.len() != D::all_directions().len()
+        {
+            return false;
+        }
+        let Some(candidate_tile) = self.tiles.get_tile(candidate) else {
+            return false;
+        };
+
+        for (direction_index, neighbor_tiles) in neighbor_possibilities.iter().enumerate() {
+            // 空列表表示该方向没有约束
+            if neighbor_tiles.is_empty() {
+                continue;
+            }
+
+            let (Some(candidate_edge), Some(opposite_index)) = (
+                candidate_tile.get_edge(direction_index),
+                D::opposite_edge_index(direction_index),
+            ) else {
+                return false;
+            };
+            let compatible = neighbor_tiles.iter().any(|&neighbor_id| {
+                self.tiles
+                    .get_tile(neighbor_id)
+                    .and_then(|neighbor| neighbor.get_edge(opposite_index))
+                    .is_some_and(|neighbor_edge| {
+                        self.edges_compatible(candidate_edge, neighbor_edge)
+                    })
+            });
+
+            if !compatible {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn get_tile(&self, tile_id: TileId) -> Option<&Tile<EdgeData>> {
+        self.tiles.get_tile(tile_id)
+    }
+
+    fn get_tile_count(&self) -> usize {
+        self.tiles.get_tile_count()
+    }
+
+    fn get_all_tile_ids(&self) -> Vec<TileId> {
+        self.tiles.get_all_tile_ids()
+    }
+
+    fn clone_box(&self) -> Option<Box<dyn TileSetVirtual<EdgeData>>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn weight_generation(&self) -> u64 {
+        self.tiles.generation()
+    }
+
+    fn tile_set_mut(&mut self) -> Option<&mut TileSet<EdgeData>> {
+        Some(&mut self.tiles)
+    }
+
+    fn expected_neighbor_count(&self) -> Option<usize> {
+        Some(D::all_directions().len())
+    }
+}
+
+/// 从样例赋值中学习相邻规则，构建只允许样例中出现过的相邻关系的瓷砖集
+///
+/// 对样例中每个单元格，按`get_neighbors()`的顺序遍历邻居，若邻居也有赋值，
+/// 就记录"瓷砖`a`的第`i`个邻居位置上可以是瓷砖`b`"。虚拟节点和未赋值的邻居会被跳过。
+/// 邻居位置与`judge_possibility`收到的`neighbor_possibilities`下标一致，
+/// 因此学到的规则只适用于与样例使用相同建边顺序的网格。
+///
+/// 返回的瓷砖集中，瓷砖`t`第`i`条边的数据为`(t, i)`，
+/// 边兼容性规则只认可记录过的`(候选瓷砖, 邻居位置, 邻居瓷砖)`组合，
+/// 因此[`judge_possibility`](TileSetVirtual::judge_possibility)只允许观察到的相邻关系。
+/// 边的数量取方向系统`D`的方向数与样例中最大邻居数的较大者。
+/// 瓷砖ID覆盖`0..=样例中最大的ID`，权重取自[`learn_weights_from_sample`]；
+/// ID不连续时，未出现的ID权重为1，且不能与任何瓷砖相邻。
+///
+/// # 参数
+///
+/// * `grid` - 样例所在的网格
+/// * `sample` - 样例中每个单元格的瓷砖
+pub fn learn_adjacency_from_sample<D>(
+    grid: &GridSystem,
+    sample: &HashMap<CellId, TileId>,
+) -> AdjacencyTileSet<(TileId, usize), D>
+where
+    D: DirectionTrait,
+{
+    let mut observed: HashSet<(TileId, usize, TileId)> = HashSet::new();
+    let mut edge_count = D::all_directions().len();
+    for (&cell, &tile) in sample {
+        let neighbors = grid.get_neighbors(cell);
+        edge_count = edge_count.max(neighbors.len());
+        for (index, neighbor) in neighbors.into_iter().enumerate() {
+            if grid.is_virtual_node(neighbor) {
+                continue;
+            }
+            if let Some(&neighbor_tile) = sample.get(&neighbor) {
+                observed.insert((tile, index, neighbor_tile));
+            }
+        }
+    }
+
+    let mut tile_set = AdjacencyTileSet::with_compatibility(
+        move |&(candidate, index): &(TileId, usize), &(neighbor, _): &(TileId, usize)| {
+            observed.contains(&(candidate, index, neighbor))
+        },
+    );
+
+    let weights = learn_weights_from_sample(&sample.values().copied().collect::<Vec<_>>());
+    let tile_count = sample.values().max().map_or(0, |&max| max + 1);
+    for tile in 0..tile_count {
+        let edges = (0..edge_count).map(|index| (tile, index)).collect();
+        tile_set.add_tile(edges, weights.get(&tile).copied().unwrap_or(1));
+    }
+
+    tile_set
+}
+
+// =============================================================================
+// 重叠模型
+// =============================================================================
+
+/// 重叠模型瓷砖的边数据：图案在该侧与邻居重叠的部分，按行优先展开
+///
+/// N×N图案的北、南边为去掉最下/最上一行后的`(N-1)×N`条带，
+/// 西、东边为去掉最右/最左一列后的`N×(N-1)`条带。
+/// 两个图案沿某方向错开一格后重叠部分一致，当且仅当候选边与邻居的相反边相等。
+pub type PatternData = Vec<TileId>;
+
+/// 按边索引记录的相邻规则：候选瓷砖的第`i`个邻居可以是哪些瓷砖
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AdjacencyRules {
+    /// `allowed[i]`包含所有允许的`(候选瓷砖, 第i个邻居的瓷砖)`组合
+    allowed: Vec<BTreeSet<(TileId, TileId)>>,
+}
+
+impl AdjacencyRules {
+    /// 创建没有任何允许组合的规则
+    pub fn new(direction_count: usize) -> Self {
+        Self {
+            allowed: vec![BTreeSet::new(); direction_count],
+        }
+    }
+
+    /// 允许`candidate`的第`direction_index`个邻居为`neighbor`
+    ///
+    /// 边索引超出方向数量时自动扩展。
+    pub fn allow(&mut self, candidate: TileId, direction_index: usize, neighbor: TileId) {
+        if direction_index >= self.allowed.len() {
+            self.allowed.resize(direction_index + 1, BTreeSet::new());
+        }
+        self.allowed[direction_index].insert((candidate, neighbor));
+    }
+
+    /// 判断`candidate`的第`direction_index`个邻居能否为`neighbor`
+    pub fn is_allowed(&self, candidate: TileId, direction_index: usize, neighbor: TileId) -> bool {
+        self.allowed
+            .get(direction_index)
+            .is_some_and(|pairs| pairs.contains(&(candidate, neighbor)))
+    }
+
+    /// 获取`candidate`的第`direction_index`个邻居允许的瓷砖，按瓷砖ID升序排列
+    pub fn allowed_neighbors(&self, candidate: TileId, direction_index: usize) -> Vec<TileId> {
+        self.allowed
+            .get(direction_index)
+            .map_or_else(Vec::new, |pairs| {
+                pairs
+                    .range((candidate, 0)..=(candidate, TileId::MAX))
+                    .map(|&(_, neighbor)| neighbor)
+                    .collect()
+            })
+    }
+
+    /// 规则覆盖的方向数量
+    pub fn direction_count(&self) -> usize {
+        self.allowed.len()
+    }
+}
+
+/// 从样例网格中提取重叠模型的N×N图案
+///
+/// 在样例上滑动N×N窗口（不环绕边界），每种不同的图案生成一个瓷砖，
+/// 权重为该图案出现的次数。瓷砖ID按图案首次出现的行优先顺序分配，
+/// 四条边按 [北, 西, 南, 东] 顺序保存[`PatternData`]重叠条带。
+///
+/// 返回的[`AdjacencyRules`]记录两个图案沿各方向错开一格后能否重叠。
+/// 由于边数据就是重叠部分，`AdjacencyTileSet::<PatternData, Direction4Full>::from_tile_set`
+/// 使用默认的相等判断即可得到完全相同的约束，可以直接交给[`WfcManager`](crate::WfcManager)。
+/// 生成结果中每个单元格的像素取其图案左上角的值，即北边条带的第一个元素。
+///
+/// 各行长度不一致时按最短的行计算；样例小于N×N时返回空瓷砖集。
+///
+/// # Panics
+///
+/// `n`小于2时图案之间没有重叠，函数会panic。
+///
+/// # 示例
+///
+/// ```rust
+/// use rlwfc::extract_patterns;
+///
+/// let sample = vec![vec![0, 0, 1], vec![0, 0, 1], vec![0, 0, 1]];
+/// let (tiles, rules) = extract_patterns(&sample, 2);
+///
+/// // [[0, 0], [0, 0]] 和 [[0, 1], [0, 1]] 两种图案
+/// assert_eq!(tiles.get_tile_count(), 2);
+/// // 全0图案的东侧可以是任一图案，条纹图案的东侧不能再有图案
+/// assert_eq!(rules.allowed_neighbors(0, 3), vec![0, 1]);
+/// assert!(rules.allowed_neighbors(1, 3).is_empty());
+/// ```
+pub fn extract_patterns(
+    sample: &[Vec<TileId>],
+    n: usize,
+) -> (TileSet<PatternData>, AdjacencyRules) {
+    assert!(n >= 2, "overlapping patterns need n >= 2, got {}", n);
+
+    let height = sample.len();
+    let width = sample.iter().map(Vec::len).min().unwrap_or(0);
+    let mut patterns: Vec<Vec<TileId>> = Vec::new();
+    let mut weights: Vec<i32> = Vec::new();
+    let mut pattern_ids: HashMap<Vec<TileId>, TileId> = HashMap::new();
+    for y in 0..(height + 1).saturating_sub(n) {
+        for x in 0..(width + 1).saturating_sub(n) {
+            let pattern: Vec<TileId> = sample[y..y + n]
+                .iter()
+                .flat_map(|row| row[x..x + n].iter().copied())
+                .collect();
+            match pattern_ids.get(&pattern) {
+                Some(&tile_id) => weights[tile_id] += 1,
+                None => {
+                    pattern_ids.insert(pattern.clone(), patterns.len());
+                    patterns.push(pattern);
+                    weights.push(1);
+                }
+            }
+        }
+    }
+
+    // 按 [北, 西, 南, 东] 顺序截取重叠条带
+    let strip =
+        |pattern: &[TileId], rows: core::ops::Range<usize>, cols: core::ops::Range<usize>| {
+            rows.flat_map(|row| cols.clone().map(move |col| pattern[row * n + col]))
+                .collect::<PatternData>()
+        };
+    let mut tile_set = TileSet::new();
+    for (pattern, weight) in patterns.iter().zip(weights) {
+        let edges = vec![
+            strip(pattern, 0..n - 1, 0..n),
+            strip(pattern, 0..n, 0..n - 1),
+            strip(pattern, 1..n, 0..n),
+            strip(pattern, 0..n, 1..n),
+        ];
+        tile_set.add_tile(edges, weight);
+    }
+
+    let mut rules = AdjacencyRules::new(4);
+    for candidate in tile_set.get_all_tile_ids() {
+        for neighbor in tile_set.get_all_tile_ids() {
+            let candidate_edges = &tile_set.get_tile(candidate).unwrap().edges;
+            let neighbor_edges = &tile_set.get_tile(neighbor).unwrap().edges;
+            for direction_index in 0..4 {
+                if candidate_edges[direction_index] == neighbor_edges[(direction_index + 2) % 4] {
+                    rules.allow(candidate, direction_index, neighbor);
+                }
+            }
+        }
+    }
+
+    (tile_set, rules)
+}
+
 // =============================================================================
 // 测试模块
 // =============================================================================
@@ -751,6 +1816,49 @@ mod tests {
         assert_eq!(tile1.edges, vec!["A", "B", "C", "D"]);
     }
 
+    #[test]
+    fn test_iter_tiles() {
+        let mut tile_set = TileSet::new();
+        for weight in 1..=4 {
+            tile_set.add_tile(vec!["A"; 4], weight);
+        }
+
+        let ids: Vec<TileId> = tile_set.iter().map(|(tile_id, _)| tile_id).collect();
+        assert_eq!(ids, (0..tile_set.get_tile_count()).collect::<Vec<_>>());
+
+        // for循环中每一项都是ID对应的瓷砖
+        let mut total_weight = 0;
+        for (tile_id, tile) in &tile_set {
+            assert_eq!(tile_set.get_tile(tile_id), Some(tile));
+            total_weight += tile.weight;
+        }
+        assert_eq!(total_weight, 10);
+        assert_eq!(TileSet::<&str>::new().iter().count(), 0);
+    }
+
+    #[test]
+    fn test_add_tile_with_meta() {
+        let mut tile_set = TileSet::new();
+        let plain = tile_set.add_tile(vec!["A", "A", "A", "A"], 1);
+        let bridge = tile_set.add_tile_with_meta(
+            vec!["A", "B", "A", "B"],
+            3,
+            Some("bridge".to_string()),
+            vec!["sprite:7".to_string(), "rotation:90".to_string()],
+        );
+
+        let tile = tile_set.get_tile(bridge).unwrap();
+        assert_eq!(tile.id, bridge);
+        assert_eq!(tile.weight, 3);
+        assert_eq!(tile.name.as_deref(), Some("bridge"));
+        assert_eq!(tile.tags, ["sprite:7", "rotation:90"]);
+
+        // 普通瓷砖没有名称和标签
+        let tile = tile_set.get_tile(plain).unwrap();
+        assert_eq!(tile.name, None);
+        assert!(tile.tags.is_empty());
+    }
+
     #[test]
     fn test_tile_set_virtual_implementation() {
         let mut test_tile_set = TestTileSet::new();
@@ -768,4 +1876,300 @@ mod tests {
         let is_possible = test_tile_set.judge_possibility(&neighbor_possibilities, 10);
         assert!(!is_possible);
     }
+
+    #[test]
+    fn test_add_tile_with_rotations() {
+        let mut tile_set = TileSet::new();
+
+        // 直通走廊只有横竖两种朝向
+        let corridor = tile_set.add_tile_with_rotations(vec![1, 0, 1, 0], 2);
+        assert_eq!(corridor.len(), 2);
+        assert_eq!(
+            tile_set.get_tile(corridor[1]).unwrap().edges,
+            vec![0, 1, 0, 1]
+        );
+        assert_eq!(tile_set.get_tile(corridor[1]).unwrap().weight, 2);
+
+        // 完全对称的瓷砖只添加一次
+        let full = tile_set.add_tile_with_rotations(vec![1, 1, 1, 1], 1);
+        assert_eq!(full, vec![2]);
+
+        // T形瓷砖的四个旋转各不相同，旋转四次回到原始边数据
+        let t = tile_set.add_tile_with_rotations(vec![1, 1, 0, 1], 1);
+        assert_eq!(t.len(), 4);
+        assert_eq!(tile_set.get_tile(t[1]).unwrap().edges, vec![1, 0, 1, 1]);
+        assert_eq!(tile_set.get_tile_count(), 7);
+    }
+
+    #[test]
+    fn test_add_tile_with_reflections() {
+        let mut tile_set = TileSet::new();
+
+        // 北、东开口的L形瓷砖：水平镜像后开口朝北、西，垂直镜像后朝南、东
+        let l_tile = tile_set.add_tile_with_reflections(vec!["open", "wall", "wall", "open"], 3);
+        assert_eq!(l_tile.len(), 3);
+        assert_eq!(
+            tile_set.get_tile(l_tile[1]).unwrap().edges,
+            vec!["open", "open", "wall", "wall"]
+        );
+        assert_eq!(
+            tile_set.get_tile(l_tile[2]).unwrap().edges,
+            vec!["wall", "wall", "open", "open"]
+        );
+        assert_eq!(tile_set.get_tile(l_tile[2]).unwrap().weight, 3);
+
+        // 左右、上下都对称的瓷砖只添加自身
+        let symmetric = tile_set.add_tile_with_reflections(vec!["open", "wall", "open", "wall"], 1);
+        assert_eq!(symmetric, vec![3]);
+        assert_eq!(tile_set.get_tile_count(), 4);
+    }
+
+    #[test]
+    fn test_adjacency_tile_set() {
+        // 与正交示例中SquareTileSet相同的瓷砖，边数据顺序：[北, 西, 南, 东]
+        let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+        let edges = [
+            [0, 0, 0, 0],
+            [1, 0, 1, 0],
+            [0, 1, 0, 1],
+            [1, 1, 1, 0],
+            [1, 0, 1, 1],
+            [0, 1, 1, 1],
+            [1, 1, 0, 1],
+            [1, 1, 1, 1],
+        ];
+        for edge in edges {
+            tile_set.add_tile(edge.to_vec(), 1);
+        }
+        tile_set.build_tile_set().unwrap();
+        assert_eq!(tile_set.get_tile_count(), 8);
+
+        // 手写的边匹配规则作为参照
+        let reference = |neighbors: &[Vec<TileId>], candidate: TileId| {
+            neighbors.iter().enumerate().all(|(i, tiles)| {
+                tiles.is_empty()
+                    || tiles
+                        .iter()
+                        .any(|&t| edges[candidate][i] == edges[t][(i + 2) % 4])
+            })
+        };
+
+        let cases: Vec<Vec<Vec<TileId>>> = vec![
+            vec![vec![], vec![], vec![], vec![]],
+            vec![vec![0], vec![], vec![], vec![]],
+            vec![vec![1], vec![2], vec![], vec![]],
+            vec![vec![7], vec![7], vec![7], vec![7]],
+            vec![vec![0, 7], vec![3], vec![5, 6], vec![2]],
+        ];
+        for neighbors in &cases {
+            for candidate in tile_set.get_all_tile_ids() {
+                assert_eq!(
+                    tile_set.judge_possibility(neighbors, candidate),
+                    reference(neighbors, candidate),
+                    "neighbors {:?}, candidate {}",
+                    neighbors,
+                    candidate
+                );
+            }
+        }
+
+        // 不存在的瓷砖
+        assert!(!tile_set.judge_possibility(&cases[0], 99));
+
+        // 邻居数量与方向系统不一致
+        assert_eq!(tile_set.expected_neighbor_count(), Some(4));
+        assert!(!tile_set.judge_possibility(&[vec![], vec![]], 0));
+        assert!(!tile_set.judge_possibility(&vec![vec![]; 8], 0));
+    }
+
+    #[test]
+    fn test_adjacency_custom_compatibility() {
+        // 边0只能连接边1，边1只能连接边0
+        let mut tile_set =
+            AdjacencyTileSet::<i32, Direction4Full>::with_compatibility(|a, b| a != b);
+        let zero = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        let one = tile_set.add_tile(vec![1, 1, 1, 1], 1);
+
+        for direction in 0..4 {
+            let mut neighbors = vec![vec![]; 4];
+            neighbors[direction] = vec![zero];
+            assert!(tile_set.judge_possibility(&neighbors, one));
+            assert!(!tile_set.judge_possibility(&neighbors, zero));
+        }
+
+        // 默认规则仍然是相等判断
+        let mut equal = AdjacencyTileSet::<i32, Direction4Full>::new();
+        equal.add_tile(vec![0, 0, 0, 0], 1);
+        equal.add_tile(vec![1, 1, 1, 1], 1);
+        let neighbors = vec![vec![zero], vec![], vec![], vec![]];
+        assert!(equal.judge_possibility(&neighbors, zero));
+        assert!(!equal.judge_possibility(&neighbors, one));
+    }
+
+    #[test]
+    fn test_adjacency_wildcard_edges() {
+        let mut tile_set = AdjacencyTileSet::<&str, Direction4Full>::new();
+        tile_set.set_wildcard("*");
+        let grass = tile_set.add_tile(vec!["grass"; 4], 1);
+        let water = tile_set.add_tile(vec!["water"; 4], 1);
+        let empty = tile_set.add_tile(vec!["*"; 4], 1);
+
+        // 通配瓷砖可以与草地、水面相邻
+        for neighbor in [grass, water] {
+            let neighbors = vec![vec![neighbor], vec![], vec![neighbor], vec![]];
+            assert!(tile_set.judge_possibility(&neighbors, empty));
+        }
+        let mixed = vec![vec![grass], vec![water], vec![], vec![]];
+        assert!(tile_set.judge_possibility(&mixed, empty));
+        assert!(!tile_set.judge_possibility(&mixed, grass));
+
+        // 邻居是通配瓷砖时也不约束候选瓷砖
+        let beside_empty = vec![vec![empty], vec![], vec![], vec![empty]];
+        assert!(tile_set.judge_possibility(&beside_empty, grass));
+        assert!(tile_set.judge_possibility(&beside_empty, water));
+
+        // 非通配边仍按相等判断
+        let neighbors = vec![vec![water], vec![], vec![], vec![]];
+        assert!(!tile_set.judge_possibility(&neighbors, grass));
+    }
+
+    #[test]
+    fn test_extract_patterns_from_checkerboard() {
+        let sample: Vec<Vec<TileId>> = (0..4)
+            .map(|y| (0..4).map(|x| (x + y) % 2).collect())
+            .collect();
+        let (tiles, rules) = extract_patterns(&sample, 2);
+
+        // 3×3个窗口中只有两种图案，左上角为0的有5个
+        assert_eq!(tiles.get_tile_count(), 2);
+        let first = tiles.get_tile(0).unwrap();
+        assert_eq!(first.weight, 5);
+        assert_eq!(first.edges[0], vec![0, 1]);
+        assert_eq!(tiles.get_tile(1).unwrap().weight, 4);
+
+        // 每个方向上两种图案只能与对方相邻
+        assert_eq!(rules.direction_count(), 4);
+        for direction_index in 0..4 {
+            assert_eq!(rules.allowed_neighbors(0, direction_index), vec![1]);
+            assert_eq!(rules.allowed_neighbors(1, direction_index), vec![0]);
+            assert!(!rules.is_allowed(0, direction_index, 0));
+        }
+
+        // 直接用边相等判断得到相同的约束
+        let tile_set = AdjacencyTileSet::<PatternData, Direction4Full>::from_tile_set(tiles);
+        for direction_index in 0..4 {
+            for (candidate, neighbor) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+                let mut neighbors = vec![vec![]; 4];
+                neighbors[direction_index] = vec![neighbor];
+                assert_eq!(
+                    tile_set.judge_possibility(&neighbors, candidate),
+                    rules.is_allowed(candidate, direction_index, neighbor)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_reports_dead_tiles() {
+        // 边数据顺序：[北, 西, 南, 东]
+        let mut tile_set = AdjacencyTileSet::<&str, Direction4Full>::new();
+        tile_set.add_tile(vec!["grass"; 4], 1);
+        tile_set.add_tile(vec!["road", "grass", "road", "grass"], 1);
+        assert_eq!(tile_set.validate(), Ok(()));
+
+        // 北边是"cliff"，但没有任何瓷砖的南边是"cliff"
+        let cliff = tile_set.add_tile(vec!["cliff", "grass", "grass", "grass"], 1);
+        assert_eq!(tile_set.validate(), Err(vec![cliff]));
+
+        // 补上南边匹配的瓷砖后恢复自洽
+        tile_set.add_tile(vec!["grass", "grass", "cliff", "grass"], 1);
+        assert_eq!(tile_set.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_are_compatible() {
+        let mut tile_set = TileSet::new();
+        // 边数据顺序：[北, 西, 南, 东]
+        let road = tile_set.add_tile(vec!["grass", "road", "grass", "road"], 1);
+        let dead_end = tile_set.add_tile(vec!["grass", "road", "grass", "grass"], 1);
+        let opposite = |i| (i + 2) % 4;
+
+        // dead_end的西边与road的东边相接
+        assert!(tile_set.are_compatible(road, dead_end, 3, opposite));
+        assert!(tile_set.are_compatible(dead_end, road, 1, opposite));
+
+        // 反过来放置时道路断开
+        assert!(!tile_set.are_compatible(dead_end, road, 3, opposite));
+        assert!(!tile_set.are_compatible(road, dead_end, 1, opposite));
+
+        // 不存在的瓷砖或越界的方向
+        assert!(!tile_set.are_compatible(road, 99, 3, opposite));
+        assert!(!tile_set.are_compatible(road, dead_end, 7, opposite));
+    }
+
+    #[test]
+    fn test_remove_tile() {
+        let mut tile_set = TileSet::new();
+        let grass = tile_set.add_tile(vec!["grass"; 4], 1);
+        let water = tile_set.add_tile(vec!["water"; 4], 2);
+        let sand = tile_set.add_tile(vec!["sand"; 4], 3);
+
+        let removed = tile_set.remove_tile(water).unwrap();
+        assert_eq!(removed.id, water);
+        assert_eq!(removed.weight, 2);
+
+        // 其余瓷砖的ID保持不变
+        assert_eq!(tile_set.get_all_tile_ids(), vec![grass, sand]);
+        assert_eq!(tile_set.get_tile(sand).unwrap().edges, vec!["sand"; 4]);
+        assert_eq!(tile_set.get_tile_count(), 2);
+        assert_eq!(tile_set.iter().count(), 2);
+        // get_all_tiles按ID索引，保留已删除瓷砖的位置
+        assert_eq!(tile_set.get_all_tiles().len(), 3);
+        assert_eq!(tile_set.get_all_tiles()[sand].id, sand);
+        let remaining: Vec<TileId> = tile_set.iter().map(|(tile_id, _)| tile_id).collect();
+        assert_eq!(remaining, tile_set.get_all_tile_ids());
+
+        // 被删除的ID不再可用，也不会被新瓷砖复用
+        assert!(tile_set.get_tile(water).is_none());
+        assert!(!tile_set.contains_tile(water));
+        assert!(tile_set.remove_tile(water).is_none());
+        assert!(tile_set.remove_tile(99).is_none());
+        assert_eq!(tile_set.add_tile(vec!["road"; 4], 1), 3);
+
+        tile_set.remove_tile(grass);
+        tile_set.remove_tile(sand);
+        tile_set.remove_tile(3);
+        assert!(tile_set.is_empty());
+    }
+
+    #[test]
+    fn test_adjacency_rejects_removed_tile() {
+        let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+        let zero = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        let also_zero = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        tile_set.tiles_mut().remove_tile(zero);
+
+        let neighbors = vec![vec![zero], vec![], vec![], vec![]];
+        assert!(!tile_set.judge_possibility(&neighbors, also_zero));
+        assert!(!tile_set.judge_possibility(&[vec![], vec![], vec![], vec![]], zero));
+        assert_eq!(tile_set.get_all_tile_ids(), vec![also_zero]);
+    }
+
+    #[test]
+    fn test_learn_weights_from_sample() {
+        let weights = learn_weights_from_sample(&[0, 0, 0, 1]);
+        assert_eq!(weights.len(), 2);
+        assert_eq!(weights[&0], 3 * weights[&1]);
+
+        let mut tile_set = TileSet::new();
+        let grass = tile_set.add_tile(vec!["grass"; 4], 1);
+        let water = tile_set.add_tile(vec!["water"; 4], 1);
+        let sand = tile_set.add_tile(vec!["sand"; 4], 7);
+        tile_set.apply_sample_weights(&[grass, grass, grass, water, 99]);
+
+        let weight = |id| tile_set.get_tile(id).unwrap().weight;
+        assert_eq!(weight(grass), 3 * weight(water));
+        // 样例中未出现的瓷砖保持原权重
+        assert_eq!(weight(sand), 7);
+    }
 }
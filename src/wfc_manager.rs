@@ -77,9 +77,46 @@ use crate::tile_set::TileSetVirtual;
  * @copyright Copyright (c) 2025
  */
 use crate::wfc_util::*;
+use fixedbitset::FixedBitSet;
 use rand::prelude::*;
 use rand::rngs::StdRng;
-use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
+
+#[cfg(feature = "parallel")]
+use petgraph::unionfind::UnionFind;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// 熵值缓存的最大条目数，超过后整体清空
+const ENTROPY_CACHE_CAPACITY: usize = 4096;
+
+/// 未配置种子时使用的随机种子
+///
+/// no_std环境没有系统熵源，使用固定种子，需要不同结果时应显式配置种子。
+fn default_seed() -> u64 {
+    #[cfg(feature = "std")]
+    return rand::rng().random();
+    #[cfg(not(feature = "std"))]
+    0
+}
+
+/// 以2为底的对数，no_std环境使用`libm`实现
+fn log2(value: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return value.log2();
+    #[cfg(not(feature = "std"))]
+    libm::log2(value)
+}
+
+/// 增量维护的权重和低于此值时视为全零权重
+const WEIGHT_SUM_EPSILON: f64 = 1e-9;
 
 // =============================================================================
 // 基础数据结构 - 对应原C++的枚举和结构体
@@ -96,6 +133,17 @@ pub enum CellState {
     Conflict,
 }
 
+impl core::fmt::Display for CellState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let text = match self {
+            CellState::Uncollapsed => "uncollapsed",
+            CellState::Collapsed => "collapsed",
+            CellState::Conflict => "conflict",
+        };
+        f.write_str(text)
+    }
+}
+
 /// 单元格WFC附加数据，对应C++的CellwfcData
 #[derive(Debug, Clone)]
 pub struct CellWfcData {
@@ -107,8 +155,18 @@ pub struct CellWfcData {
     pub rand_seed: u64,
     /// 预计算的随机数，对应C++的randNum
     pub rand_num: i32,
-    /// 可能的瓷砖列表，对应C++的possibility
-    pub possibilities: Vec<TileId>,
+    /// 可能的瓷砖集合，对应C++的possibility
+    ///
+    /// 以瓷砖ID为下标的位集，为空与单一可能性的判断只需检查少量字块，
+    /// 约束过滤时直接在位集上清除不兼容的瓷砖，无需重新分配列表。
+    possibilities: FixedBitSet,
+    /// 可能瓷砖的（有效）权重之和
+    weight_sum: f64,
+    /// 可能瓷砖的`weight * log2(weight)`之和
+    ///
+    /// 与`weight_sum`一起在移除瓷砖时增量维护，熵值可直接由两者算出：
+    /// `H = log2(Σw) - Σ(w·log2 w) / Σw`。
+    weight_log_weight_sum: f64,
 }
 
 impl CellWfcData {
@@ -123,30 +181,313 @@ impl CellWfcData {
             entropy: 0.0, // 将在初始化时计算
             rand_seed,
             rand_num,
-            possibilities,
+            possibilities: possibilities.into_iter().collect(),
+            weight_sum: 0.0,
+            weight_log_weight_sum: 0.0,
         }
     }
+
+    /// 获取可能的瓷砖列表，按瓷砖ID升序排列
+    pub fn possibilities(&self) -> Vec<TileId> {
+        self.possibilities.ones().collect()
+    }
+
+    /// 获取可能的瓷砖数量
+    pub fn possibility_count(&self) -> usize {
+        self.possibilities.count_ones(..)
+    }
+
+    /// 检查瓷砖是否仍是该单元格的可能性
+    pub fn is_possible(&self, tile_id: TileId) -> bool {
+        self.possibilities.contains(tile_id)
+    }
+
+    /// 由增量维护的权重和计算香农熵
+    ///
+    /// 与逐瓷砖求和的结果只在浮点误差范围内不同。
+    fn incremental_entropy(&self) -> f64 {
+        let count = self.possibility_count();
+        if count <= 1 {
+            return 0.0;
+        }
+        // 反复相减后可能残留极小的正数，此时按全零权重处理
+        if self.weight_sum <= WEIGHT_SUM_EPSILON {
+            return log2(count as f64);
+        }
+        (log2(self.weight_sum) - self.weight_log_weight_sum / self.weight_sum).max(0.0)
+    }
+
+    /// 创建与当前可能性位集长度相同的瓷砖集合，保证熵值缓存的键一致
+    fn possibility_set(&self, tiles: impl IntoIterator<Item = TileId>) -> FixedBitSet {
+        let mut set = FixedBitSet::with_capacity(self.possibilities.len());
+        set.extend(tiles);
+        set
+    }
 }
 
 /// WFC系统完整状态，对应C++的WFCSystemData
 pub type WfcSystemData = HashMap<CellId, CellWfcData>;
 
+/// 软约束评分函数
+///
+/// 参数依次为候选瓷砖和`(邻居下标, 邻居当前的可能性)`列表，返回乘到瓷砖权重上的倍数。
+/// 邻居下标与[`TileSetVirtual::judge_possibility`]的下标相同，可以用
+/// [`DirectionTrait::from_neighbor_index`]换算为方向；虚拟边界节点不出现在列表中。
+/// 负数和非有限值按0处理。
+///
+/// 与瓷砖集一样需要满足`Send + Sync`。使用`Arc`共享，复制管理器时无需复制闭包。
+pub type ScoringFn = Arc<dyn Fn(TileId, &[(usize, &[TileId])]) -> f64 + Send + Sync>;
+
 /// 系统状态快照，用于回溯
+///
+/// 内容不对外公开，通过[`WfcManager::checkpoint`]创建、
+/// [`WfcManager::restore`]恢复。
 #[derive(Debug, Clone)]
 pub struct SystemSnapshot {
     /// WFC系统数据快照
     data: WfcSystemData,
     /// 已完成单元计数
     completed_count: usize,
+    /// 快照时已记录的坍塌事件数量
+    collapse_event_count: usize,
+}
+
+impl SystemSnapshot {
+    /// 估算快照占用的内存字节数
+    ///
+    /// 只统计单元格数据本身和可能性位集，不计哈希表的额外开销。
+    pub fn estimated_bytes(&self) -> usize {
+        let entry_size = core::mem::size_of::<CellId>() + core::mem::size_of::<CellWfcData>();
+        self.data
+            .values()
+            .map(|data| entry_size + core::mem::size_of_val(data.possibilities.as_slice()))
+            .sum()
+    }
+}
+
+/// 回溯策略下的一次坍塌决策
+#[derive(Debug, Clone)]
+struct Decision {
+    /// 坍塌的单元格
+    cell: CellId,
+    /// 已经尝试过的瓷砖
+    tried: Vec<TileId>,
+    /// 决策之前的系统状态
+    snapshot: SystemSnapshot,
+    /// 快照登记的内存占用（字节）
+    snapshot_bytes: usize,
+}
+
+/// 最小熵优先队列中的条目：(熵值, 并列排序键, 单元格)
+type EntropyHeapEntry = Reverse<(OrderedEntropy, u64, CellId)>;
+
+/// 可全序比较的熵值，用于优先队列
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OrderedEntropy(f64);
+
+impl Eq for OrderedEntropy {}
+
+impl PartialOrd for OrderedEntropy {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedEntropy {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// 弧一致性传播使用的支持计数（AC-4）
+///
+/// `counts[cell][d][a]`为单元格第`d`个邻居的当前可能性中与瓷砖`a`兼容的瓷砖数量。
+/// 计数为零说明`a`在该方向失去了全部支持，只有这样的瓷砖需要重新判断。
+/// 计数依据`seen`中记录的可能性，使用前与当前可能性比较并增量更新，
+/// 因此快照恢复、预设等任何修改都不会让计数失效。
+#[derive(Debug, Clone, Default)]
+struct SupportCounts {
+    /// 每个单元格按邻居顺序、以瓷砖ID为下标的支持计数
+    counts: HashMap<CellId, Vec<Vec<u32>>>,
+    /// 计数所依据的各单元格可能性
+    seen: HashMap<CellId, FixedBitSet>,
+    /// 以该单元格为第`d`个邻居的`(单元格, d)`列表
+    dependents: HashMap<CellId, Vec<(CellId, usize)>>,
+    /// 按`(邻居数量, 方向)`缓存的兼容表，`table[b]`为被邻居瓷砖`b`支持的瓷砖集合
+    tables: HashMap<(usize, usize), Vec<FixedBitSet>>,
+}
+
+/// 并行传播中单个分区在只读视图上的传播结果
+#[cfg(feature = "parallel")]
+#[derive(Debug, Default)]
+struct PartitionPropagation {
+    /// 按更新顺序记录的新可能性
+    updates: Vec<(CellId, FixedBitSet)>,
+    /// 传播过程中读取过的单元格
+    reads: HashSet<CellId>,
+    /// 起始单元格和被更新的单元格
+    writes: HashSet<CellId>,
+    /// 传播步数
+    steps: usize,
+}
+
+/// 冲突处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictStrategy {
+    /// 坍塌结束后对冲突单元格进行分层修复（对应原C++实现）
+    #[default]
+    LayeredRepair,
+    /// 每次坍塌前保存快照，出现冲突时回退到最近的决策点并换用其他瓷砖
+    ///
+    /// 只要存在解就一定能找到，但最坏情况下耗时随网格规模指数增长。
+    Backtrack,
+}
+
+/// 最小熵单元格并列时的选择规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    /// 选择`CellId`索引最小的单元格
+    ///
+    /// 与随机种子无关，相同的网格和种子在每次运行中产生相同的坍塌顺序。
+    #[default]
+    LowestId,
+    /// 按内部数据的遍历顺序选择，不保证跨运行一致
+    Unordered,
+    /// 按单元格坐标与主种子的哈希值选择，哈希最小者优先
+    ///
+    /// 坐标来自[`GridSystem::get_cell_coords`]，没有坐标的单元格使用其索引。
+    /// 相同种子下结果可复现，同时避免总是偏向某个角落。
+    SpatialHash,
+}
+
+/// 约束传播方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PropagationMode {
+    /// 对每个受影响的邻居，用全部邻居的可能性重新判断它的每个瓷砖（对应原C++实现）
+    #[default]
+    Naive,
+    /// AC-4风格的弧一致性传播
+    ///
+    /// 为每个单元格的每个方向和瓷砖维护支持计数，只对失去全部支持的瓷砖调用
+    /// [`TileSetVirtual::judge_possibility`]，瓷砖较多时大幅减少判断次数。
+    /// 两两兼容关系通过只约束一个方向的探测调用得到，要求`judge_possibility`
+    /// 是各方向"存在兼容邻居瓷砖"条件的合取；满足这一点时结果与`Naive`完全相同。
+    ArcConsistency,
+}
+
+/// 每一步选择下一个坍塌单元格的观察策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ObservationHeuristic {
+    /// 选择熵值最小的单元格，并列时按[`TieBreak`]选择
+    #[default]
+    MinEntropy,
+    /// 按`CellId`索引顺序坍塌，得到逐行填充的确定性结果
+    Scanline,
+    /// 选择剩余可能性最少的单元格，并列时优先已坍塌邻居较多者，再按[`TieBreak`]选择
+    MostConstrained,
+    /// 按到种子单元格的广度优先距离由近到远坍塌，距离相同时选择熵值最小者，
+    /// 再按[`TieBreak`]选择
+    ///
+    /// 通常先用[`WfcManager::pre_collapse`]确定种子单元格，使结果从中心向外生长。
+    /// 距离由[`GridSystem::bfs_distances`]计算并缓存，虚拟节点和不可达的单元格排在最后。
+    RadialFrom(CellId),
+}
+
+/// 整张地图上某个瓷砖出现次数的全局约束
+///
+/// 只统计非虚拟节点上已坍塌的单元格。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobalConstraint {
+    /// 受约束的瓷砖
+    pub tile: TileId,
+    /// 最少出现次数，`None`表示不限制
+    pub min: Option<usize>,
+    /// 最多出现次数，`None`表示不限制
+    pub max: Option<usize>,
+}
+
+impl GlobalConstraint {
+    /// 瓷砖最多出现`max`次
+    pub fn at_most(tile: TileId, max: usize) -> Self {
+        Self {
+            tile,
+            min: None,
+            max: Some(max),
+        }
+    }
+
+    /// 瓷砖至少出现`min`次
+    pub fn at_least(tile: TileId, min: usize) -> Self {
+        Self {
+            tile,
+            min: Some(min),
+            max: None,
+        }
+    }
 }
 
 /// WFC算法配置参数
+///
+/// 可以直接构造结构体，也可以通过[`WfcConfig::builder`]逐项设置：
+///
+/// ```rust
+/// use rlwfc::WfcConfig;
+///
+/// let config = WfcConfig::builder().seed(42).max_recursion_depth(5).build();
+/// assert_eq!(config.random_seed, Some(42));
+/// assert_eq!(config.max_recursion_depth, 5);
+/// ```
 #[derive(Debug, Clone)]
 pub struct WfcConfig {
     /// 最大递归深度
     pub max_recursion_depth: usize,
-    /// 随机种子
+    /// 随机种子，未设置时从系统熵源取得（未启用`std`特性时固定为0）
     pub random_seed: Option<u64>,
+    /// 冲突修复期间同时存活的快照内存上限（字节），`None`表示不限制
+    ///
+    /// 超出上限时修复中止并返回[`WfcError::SnapshotMemoryExceeded`]。
+    pub max_snapshot_memory: Option<usize>,
+    /// 最小熵单元格并列时的选择规则
+    pub tie_break: TieBreak,
+    /// 选择下一个坍塌单元格的观察策略
+    pub observation_heuristic: ObservationHeuristic,
+    /// 约束传播方式
+    pub propagation: PropagationMode,
+    /// 分层修复连续失败的最大重试次数
+    ///
+    /// 修复失败后会再次尝试，连续失败次数超过该值时返回
+    /// [`WfcError::UnresolvableConflicts`]，避免在无解的瓷砖集上反复修复。
+    pub max_conflict_retries: usize,
+    /// 冲突处理策略
+    pub conflict_strategy: ConflictStrategy,
+    /// 熵值噪声幅度，大于0时为每次更新的熵值加上`[0, entropy_noise)`的随机噪声
+    ///
+    /// 与经典WFC一样用噪声打破熵值并列，使输出更加多样。为0时不消耗随机数，
+    /// 结果与不加噪声时完全相同。
+    pub entropy_noise: f64,
+    /// 坍塌时是否从管理器的随机数生成器实时抽取加权样本
+    ///
+    /// 默认沿用C++的行为：每个单元格在初始化时预计算一个随机数，坍塌时对当前总权重取模。
+    /// 这个随机数不随可能性集合的收缩而更新，回溯重试时同一单元格总是得到相同的选择，
+    /// 取模还会带来轻微的偏差。开启后每次坍塌都在当前可能性上独立抽样，
+    /// 选中各瓷砖的概率严格正比于其权重。
+    pub live_random: bool,
+    /// 坍塌时是否前瞻一步，避开会立即使邻居失去全部可能性的瓷砖
+    ///
+    /// 开启后，加权选择只在"放置后每个未坍塌的邻居仍至少剩一种可能性"的瓷砖中进行；
+    /// 没有这样的瓷砖时退回全部可能性，照常交给冲突处理。
+    /// 每次坍塌需要对每个候选瓷砖、每个邻居的每个可能瓷砖各调用一次
+    /// `judge_possibility`，代价约为`候选数 × 邻居数 × 邻居可能性数`，
+    /// 瓷砖很多时会明显拖慢坍塌。
+    pub look_ahead: bool,
+    /// 全局瓷砖数量约束，在初始化后和每次坍塌后执行
+    ///
+    /// 瓷砖达到上限后从所有未坍塌的单元格中移除并传播约束；已坍塌数加上仍可能放置
+    /// 该瓷砖的单元格数少于下限时视为冲突：回溯策略下回退到上一个决策点，
+    /// 分层修复下返回[`WfcError::GlobalConstraintUnsatisfiable`]。
+    /// 分层修复从邻居重新推导可能性时同样排除已达上限的瓷砖，修复成功后再次执行约束。
+    /// 每次坍塌需要为每个约束遍历一次全部单元格。
+    pub global_constraints: Vec<GlobalConstraint>,
 }
 
 impl Default for WfcConfig {
@@ -154,10 +495,114 @@ impl Default for WfcConfig {
         Self {
             max_recursion_depth: 3, // 对应C++的硬编码深度限制
             random_seed: None,
+            max_snapshot_memory: None,
+            tie_break: TieBreak::default(),
+            observation_heuristic: ObservationHeuristic::default(),
+            propagation: PropagationMode::default(),
+            max_conflict_retries: 3,
+            conflict_strategy: ConflictStrategy::default(),
+            entropy_noise: 0.0,
+            live_random: false,
+            look_ahead: false,
+            global_constraints: Vec::new(),
         }
     }
 }
 
+impl WfcConfig {
+    /// 创建以默认值为起点的配置构建器
+    pub fn builder() -> WfcConfigBuilder {
+        WfcConfigBuilder::default()
+    }
+}
+
+/// [`WfcConfig`]构建器，未设置的项保持默认值
+#[derive(Debug, Clone, Default)]
+pub struct WfcConfigBuilder {
+    config: WfcConfig,
+}
+
+impl WfcConfigBuilder {
+    /// 设置随机种子
+    ///
+    /// 相同的种子、网格和瓷砖集在确定的并列选择规则下产生完全相同的结果。
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.random_seed = Some(seed);
+        self
+    }
+
+    /// 设置冲突修复的最大递归深度
+    pub fn max_recursion_depth(mut self, depth: usize) -> Self {
+        self.config.max_recursion_depth = depth;
+        self
+    }
+
+    /// 设置冲突修复期间的快照内存上限（字节）
+    pub fn max_snapshot_memory(mut self, bytes: usize) -> Self {
+        self.config.max_snapshot_memory = Some(bytes);
+        self
+    }
+
+    /// 设置最小熵单元格并列时的选择规则
+    pub fn tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.config.tie_break = tie_break;
+        self
+    }
+
+    /// 设置选择下一个坍塌单元格的观察策略
+    pub fn observation_heuristic(mut self, heuristic: ObservationHeuristic) -> Self {
+        self.config.observation_heuristic = heuristic;
+        self
+    }
+
+    /// 设置约束传播方式
+    pub fn propagation(mut self, mode: PropagationMode) -> Self {
+        self.config.propagation = mode;
+        self
+    }
+
+    /// 设置分层修复连续失败的最大重试次数
+    pub fn max_conflict_retries(mut self, retries: usize) -> Self {
+        self.config.max_conflict_retries = retries;
+        self
+    }
+
+    /// 设置冲突处理策略
+    pub fn conflict_strategy(mut self, strategy: ConflictStrategy) -> Self {
+        self.config.conflict_strategy = strategy;
+        self
+    }
+
+    /// 设置熵值噪声幅度
+    pub fn entropy_noise(mut self, noise: f64) -> Self {
+        self.config.entropy_noise = noise;
+        self
+    }
+
+    /// 设置是否在坍塌时实时抽取加权样本
+    pub fn live_random(mut self, live_random: bool) -> Self {
+        self.config.live_random = live_random;
+        self
+    }
+
+    /// 设置坍塌时是否前瞻一步
+    pub fn look_ahead(mut self, look_ahead: bool) -> Self {
+        self.config.look_ahead = look_ahead;
+        self
+    }
+
+    /// 追加一条全局瓷砖数量约束
+    pub fn global_constraint(mut self, constraint: GlobalConstraint) -> Self {
+        self.config.global_constraints.push(constraint);
+        self
+    }
+
+    /// 生成配置
+    pub fn build(self) -> WfcConfig {
+        self.config
+    }
+}
+
 // =============================================================================
 // WFC错误类型
 // =============================================================================
@@ -171,18 +616,45 @@ pub enum WfcError {
     NoUncollapsedCells,
     /// 单元格未找到
     CellNotFound(CellId),
-    /// 瓷砖未找到
-    TileNotFound,
+    /// 瓷砖未找到，附带无效的瓷砖ID
+    TileNotFound(TileId),
     /// 单元格已坍塌
     CellAlreadyCollapsed,
-    /// 无效的瓷砖选择
-    InvalidTileChoice,
-    /// 无法解决的冲突
-    UnresolvableConflicts,
+    /// 无效的瓷砖选择，附带单元格和被拒绝的瓷砖
+    InvalidTileChoice {
+        /// 目标单元格
+        cell: CellId,
+        /// 被拒绝的瓷砖
+        tile: TileId,
+    },
+    /// 无法解决的冲突，附带冲突单元格的诊断报告
+    UnresolvableConflicts(ConflictReport),
     /// 系统状态不一致
     InconsistentState,
     /// 初始化失败
     InitializationFailed(String),
+    /// 冲突修复所需的快照内存超出配置上限
+    SnapshotMemoryExceeded {
+        /// 配置的上限（字节）
+        limit: usize,
+        /// 继续修复所需的内存（字节）
+        required: usize,
+    },
+    /// 全局数量约束的下限已无法满足
+    GlobalConstraintUnsatisfiable(GlobalConstraint),
+    /// 无效的权重倍数（负数或不是有限值）
+    InvalidWeightMultiplier(f64),
+    /// 瓷砖集没有提供可修改的瓷砖存储，见[`TileSetVirtual::tile_set_mut`]
+    TileSetNotMutable,
+    /// 单元格的邻居数量与瓷砖集要求的不一致，见[`TileSetVirtual::expected_neighbor_count`]
+    NeighborCountMismatch {
+        /// 邻居数量不一致的单元格
+        cell: CellId,
+        /// 实际邻居数量
+        neighbor_count: usize,
+        /// 瓷砖集要求的邻居数量
+        expected: usize,
+    },
 }
 
 impl From<GridError> for WfcError {
@@ -191,25 +663,66 @@ impl From<GridError> for WfcError {
     }
 }
 
-impl std::fmt::Display for WfcError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for WfcError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             WfcError::Grid(e) => write!(f, "Grid error: {}", e),
             WfcError::NoUncollapsedCells => write!(f, "No uncollapsed cells available"),
             WfcError::CellNotFound(cell_id) => {
                 write!(f, "Cell not found in WFC data: {:?}", cell_id)
             }
-            WfcError::TileNotFound => write!(f, "Tile not found in tile set"),
+            WfcError::TileNotFound(tile) => write!(f, "Tile {} not found in tile set", tile),
             WfcError::CellAlreadyCollapsed => write!(f, "Cell is already collapsed"),
-            WfcError::InvalidTileChoice => write!(f, "Invalid tile choice for cell"),
-            WfcError::UnresolvableConflicts => write!(f, "Conflicts cannot be resolved"),
+            WfcError::InvalidTileChoice { cell, tile } => {
+                write!(f, "Invalid tile choice {} for cell {:?}", tile, cell)
+            }
+            WfcError::UnresolvableConflicts(report) => write!(
+                f,
+                "Conflicts cannot be resolved in {} cells",
+                report.conflicts.len()
+            ),
             WfcError::InconsistentState => write!(f, "WFC system state is inconsistent"),
             WfcError::InitializationFailed(msg) => write!(f, "Initialization failed: {}", msg),
+            WfcError::SnapshotMemoryExceeded { limit, required } => write!(
+                f,
+                "Conflict resolution needs {} bytes of snapshots, exceeding the limit of {} bytes",
+                required, limit
+            ),
+            WfcError::GlobalConstraintUnsatisfiable(constraint) => write!(
+                f,
+                "Tile {} can no longer appear at least {} times",
+                constraint.tile,
+                constraint.min.unwrap_or(0)
+            ),
+            WfcError::InvalidWeightMultiplier(factor) => write!(
+                f,
+                "Weight multiplier must be finite and non-negative, got {}",
+                factor
+            ),
+            WfcError::TileSetNotMutable => write!(f, "Tile set does not support modifying tiles"),
+            WfcError::NeighborCountMismatch {
+                cell,
+                neighbor_count,
+                expected,
+            } => write!(
+                f,
+                "Cell {:?} has {} neighbors, but the tile set expects {}",
+                cell, neighbor_count, expected
+            ),
         }
     }
 }
 
-impl std::error::Error for WfcError {}
+#[cfg(feature = "std")]
+impl std::error::Error for WfcError {
+    /// `Grid`变体返回内部的[`GridError`]，使`Box<dyn Error>`等错误链保留底层原因
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WfcError::Grid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
 
 // =============================================================================
 // 初始化特性 - 对应原C++的initialize虚函数
@@ -218,7 +731,7 @@ impl std::error::Error for WfcError {}
 /// 初始化特性，对应C++的initialize()虚函数
 pub trait WfcInitializer<EdgeData>
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug + Send + Sync,
+    EdgeData: Clone + PartialEq + core::fmt::Debug + Send + Sync,
 {
     /// 初始化WFC系统，对应C++的initialize()虚函数
     fn initialize(&mut self, manager: &mut WfcManager<EdgeData>) -> Result<(), WfcError>;
@@ -229,7 +742,7 @@ pub struct DefaultInitializer;
 
 impl<EdgeData> WfcInitializer<EdgeData> for DefaultInitializer
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug + Send + Sync,
+    EdgeData: Clone + PartialEq + core::fmt::Debug + Send + Sync,
 {
     fn initialize(&mut self, manager: &mut WfcManager<EdgeData>) -> Result<(), WfcError> {
         // 1. 构建瓷砖集
@@ -267,6 +780,192 @@ pub enum StepResult {
     Complete,
 }
 
+impl core::fmt::Display for StepResult {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let text = match self {
+            StepResult::Collapsed => "collapsed a cell",
+            StepResult::ConflictsResolved => "conflicts resolved",
+            StepResult::ConflictResolutionFailed => "conflict resolution failed",
+            StepResult::Complete => "complete",
+        };
+        f.write_str(text)
+    }
+}
+
+/// [`WfcManager::run_until`]和[`WfcManager::run_steps`]的运行结局
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// 所有单元格都已坍塌，冲突已解决
+    Completed,
+    /// 调用方中止了运行，系统停留在可查询、可继续运行的中间状态
+    Cancelled,
+    /// 用完了坍塌步数预算，仍有未坍塌的单元格
+    BudgetExhausted,
+    /// 所有单元格都已处理，但存在冲突，需要调用[`WfcManager::resolve_conflicts`]
+    NeedsConflictResolution,
+}
+
+/// 运行进度，由[`WfcManager::run_with_observer`]在每次坍塌和冲突修复后报告
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfcProgress {
+    /// 已坍塌的单元格数量
+    pub completed_count: usize,
+    /// 单元格总数（包括虚拟边界节点）
+    pub total_cells: usize,
+    /// 刚刚坍塌的单元格及其瓷砖，冲突修复后为`None`
+    pub collapsed: Option<(CellId, TileId)>,
+}
+
+/// 单元格状态统计，由[`WfcManager::statistics`]返回
+///
+/// 只统计真实单元格，虚拟边界节点不计入。
+#[derive(Debug, Clone, PartialEq)]
+pub struct WfcStatistics {
+    /// 真实单元格总数
+    pub total_cells: usize,
+    /// 已坍塌的单元格数量
+    pub collapsed: usize,
+    /// 未坍塌的单元格数量
+    pub uncollapsed: usize,
+    /// 冲突单元格数量
+    pub conflict: usize,
+    /// 已坍塌单元格所占比例，范围`[0, 1]`；没有单元格时为1.0
+    pub completion_ratio: f64,
+    /// 各瓷砖被已坍塌单元格选中的次数，未被使用的瓷砖不出现
+    pub tile_usage: BTreeMap<TileId, usize>,
+}
+
+// =============================================================================
+// 运行前预检查
+// =============================================================================
+
+/// 预检查发现的问题，由[`WfcManager::preflight`]返回
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreflightIssue {
+    /// 瓷砖集为空
+    EmptyTileSet,
+    /// 瓷砖的边数量与方向系统的方向数不一致
+    TileArityMismatch {
+        /// 瓷砖ID
+        tile: TileId,
+        /// 瓷砖的边数量
+        edge_count: usize,
+        /// 方向系统的方向数
+        expected: usize,
+    },
+    /// 单元格的邻居数量与方向系统的方向数不一致
+    CellArityMismatch {
+        /// 单元格ID
+        cell: CellId,
+        /// 实际邻居数量（含虚拟边界节点）
+        neighbor_count: usize,
+        /// 方向系统的方向数
+        expected: usize,
+    },
+    /// 瓷砖在某个方向上没有任何可相邻的瓷砖
+    MissingAdjacency {
+        /// 瓷砖ID
+        tile: TileId,
+        /// 邻居数组中的方向索引
+        direction_index: usize,
+    },
+    /// 单元格既没有出边也没有入边
+    IsolatedCell(CellId),
+    /// 沿某方向到达的邻居，沿相反方向无法回到原单元格
+    DirectionInconsistency {
+        /// 起始单元格
+        cell: CellId,
+        /// 方向名称
+        direction: &'static str,
+        /// 沿该方向找到的邻居
+        neighbor: CellId,
+    },
+}
+
+impl core::fmt::Display for PreflightIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PreflightIssue::EmptyTileSet => write!(f, "Tile set is empty"),
+            PreflightIssue::TileArityMismatch {
+                tile,
+                edge_count,
+                expected,
+            } => write!(
+                f,
+                "Tile {} has {} edges, but the direction system has {} directions",
+                tile, edge_count, expected
+            ),
+            PreflightIssue::CellArityMismatch {
+                cell,
+                neighbor_count,
+                expected,
+            } => write!(
+                f,
+                "Cell {:?} has {} neighbors, but the direction system has {} directions",
+                cell, neighbor_count, expected
+            ),
+            PreflightIssue::MissingAdjacency {
+                tile,
+                direction_index,
+            } => write!(
+                f,
+                "Tile {} has no compatible neighbor at direction index {}",
+                tile, direction_index
+            ),
+            PreflightIssue::IsolatedCell(cell) => {
+                write!(f, "Cell {:?} has no incoming or outgoing edges", cell)
+            }
+            PreflightIssue::DirectionInconsistency {
+                cell,
+                direction,
+                neighbor,
+            } => write!(
+                f,
+                "Cell {:?} reaches {:?} going {}, but the opposite direction does not lead back",
+                cell, neighbor, direction
+            ),
+        }
+    }
+}
+
+// =============================================================================
+// 冲突诊断
+// =============================================================================
+
+/// 单个冲突单元格的诊断信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellConflict {
+    /// 冲突单元格
+    pub cell: CellId,
+    /// 单元格剩余的可能瓷砖，冲突时为空
+    pub possibilities: Vec<TileId>,
+    /// 按邻居顺序排列的真实邻居及其当前可能瓷砖，虚拟边界节点不计入
+    pub neighbors: Vec<(CellId, Vec<TileId>)>,
+}
+
+/// 冲突诊断报告，由[`WfcManager::conflict_report`]返回，
+/// 也随[`WfcError::UnresolvableConflicts`]一起返回
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConflictReport {
+    /// 按单元格ID排序的冲突单元格
+    pub conflicts: Vec<CellConflict>,
+}
+
+impl ConflictReport {
+    /// 所有冲突单元格的ID
+    pub fn cells(&self) -> Vec<CellId> {
+        self.conflicts
+            .iter()
+            .map(|conflict| conflict.cell)
+            .collect()
+    }
+
+    /// 是否没有冲突
+    pub fn is_empty(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
 // =============================================================================
 // WFC管理器主结构
 // =============================================================================
@@ -279,7 +978,7 @@ pub enum StepResult {
 /// - 集成边对管理，确保WFC算法的完整性
 pub struct WfcManager<EdgeData>
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug + Send + Sync,
+    EdgeData: Clone + PartialEq + core::fmt::Debug + Send + Sync,
 {
     /// 网格系统，对应C++的grid_成员
     grid: GridSystem,
@@ -291,16 +990,51 @@ where
     completed_count: usize,
     /// 随机数生成器
     rng: StdRng,
+    /// 主随机种子，未配置时为随机生成的种子
+    seed: u64,
     /// 配置参数
     config: WfcConfig,
-    /// 熵值缓存，对应C++的entropyCache
-    #[allow(dead_code)]
-    entropy_cache: HashMap<Vec<TileId>, f64>,
+    /// 熵值缓存，对应C++的entropyCache，键为可能性位集
+    entropy_cache: HashMap<FixedBitSet, f64>,
+    /// 熵值缓存命中次数
+    entropy_cache_hits: usize,
+    /// 缓存中熵值所依据的瓷砖权重版本号，见[`TileSetVirtual::weight_generation`]
+    entropy_cache_generation: u64,
+    /// 未坍塌单元格的最小熵优先队列，过期条目在取出时跳过
+    entropy_heap: BinaryHeap<EntropyHeapEntry>,
+    /// 单元格数据被批量修改，优先队列需要在下次选择前重建
+    entropy_heap_dirty: bool,
+    /// 当前存活的快照占用的内存（字节）
+    snapshot_bytes_in_use: usize,
+    /// 快照内存占用的历史峰值（字节）
+    peak_snapshot_bytes: usize,
+    /// 累计约束传播步数（邻居可能性更新次数）
+    propagation_steps: usize,
+    /// 回溯策略的决策栈，栈顶为最近的决策
+    decision_stack: Vec<Decision>,
+    /// 单元格级别的瓷砖权重倍数，未设置的组合倍数为1.0
+    weight_multipliers: HashMap<CellId, HashMap<TileId, f64>>,
+    /// 弧一致性传播的支持计数，首次使用时建立
+    supports: Option<SupportCounts>,
+    /// 连续失败的冲突修复次数
+    failed_resolutions: usize,
+    /// 区域生成时限定的单元格集合，坍塌和传播都不会越过区域边界
+    region: Option<HashSet<CellId>>,
+    /// 按发生顺序记录的坍塌事件：(步序号, 单元格, 瓷砖)
+    collapse_events: Vec<(usize, CellId, TileId)>,
+    /// 下一次坍塌的步序号，回溯撤销坍塌时不回退
+    collapse_step: usize,
+    /// 坍塌时调整候选瓷砖权重的软约束
+    scoring: Option<ScoringFn>,
+    /// 评分函数使用的邻居可能性缓冲区，在坍塌之间复用
+    scoring_neighbors: Vec<Vec<TileId>>,
+    /// 径向观察策略缓存的`(种子单元格, 广度优先距离)`
+    radial_distances: Option<(CellId, HashMap<CellId, usize>)>,
 }
 
 impl<EdgeData> WfcManager<EdgeData>
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug + Send + Sync,
+    EdgeData: Clone + PartialEq + core::fmt::Debug + Send + Sync,
 {
     /// 创建新的WFC管理器
     pub fn new(
@@ -308,9 +1042,7 @@ where
         tile_set: Box<dyn TileSetVirtual<EdgeData>>,
     ) -> Result<Self, WfcError> {
         let config = WfcConfig::default();
-        let seed = config
-            .random_seed
-            .unwrap_or_else(|| rand::rng().random());
+        let seed = config.random_seed.unwrap_or_else(default_seed);
         let rng = StdRng::seed_from_u64(seed);
 
         Ok(Self {
@@ -319,8 +1051,26 @@ where
             wfc_data: HashMap::new(),
             completed_count: 0,
             rng,
+            seed,
             config,
             entropy_cache: HashMap::new(),
+            entropy_cache_hits: 0,
+            entropy_cache_generation: 0,
+            entropy_heap: BinaryHeap::new(),
+            entropy_heap_dirty: true,
+            snapshot_bytes_in_use: 0,
+            peak_snapshot_bytes: 0,
+            propagation_steps: 0,
+            decision_stack: Vec::new(),
+            weight_multipliers: HashMap::new(),
+            supports: None,
+            failed_resolutions: 0,
+            region: None,
+            collapse_events: Vec::new(),
+            collapse_step: 0,
+            scoring: None,
+            scoring_neighbors: Vec::new(),
+            radial_distances: None,
         })
     }
 
@@ -330,9 +1080,7 @@ where
         tile_set: Box<dyn TileSetVirtual<EdgeData>>,
         config: WfcConfig,
     ) -> Result<Self, WfcError> {
-        let seed = config
-            .random_seed
-            .unwrap_or_else(|| rand::rng().random());
+        let seed = config.random_seed.unwrap_or_else(default_seed);
         let rng = StdRng::seed_from_u64(seed);
 
         Ok(Self {
@@ -341,8 +1089,26 @@ where
             wfc_data: HashMap::new(),
             completed_count: 0,
             rng,
+            seed,
             config,
             entropy_cache: HashMap::new(),
+            entropy_cache_hits: 0,
+            entropy_cache_generation: 0,
+            entropy_heap: BinaryHeap::new(),
+            entropy_heap_dirty: true,
+            snapshot_bytes_in_use: 0,
+            peak_snapshot_bytes: 0,
+            propagation_steps: 0,
+            decision_stack: Vec::new(),
+            weight_multipliers: HashMap::new(),
+            supports: None,
+            failed_resolutions: 0,
+            region: None,
+            collapse_events: Vec::new(),
+            collapse_step: 0,
+            scoring: None,
+            scoring_neighbors: Vec::new(),
+            radial_distances: None,
         })
     }
 
@@ -350,667 +1116,4648 @@ where
     // 公共接口方法 - 对应原C++的public方法
     // ==========================================================================
 
+    /// 复制出一个使用新种子、尚未初始化的管理器，用于并发生成多张地图
+    ///
+    /// 副本包含网格、瓷砖集、配置（随机种子替换为`seed`）、单元格权重倍数和软约束评分函数，
+    /// 但不包含任何运行状态：使用前需要调用[`initialize_with`](Self::initialize_with)，
+    /// 预设和禁用的瓷砖也需要在副本上重新设置。瓷砖集满足`Send + Sync`，
+    /// 管理器可以在线程间共享引用，因此多个线程可以同时从同一个管理器复制后各自运行。
+    ///
+    /// 瓷砖集没有实现[`TileSetVirtual::clone_box`]时返回`Ok(None)`。
+    ///
+    /// # 错误
+    ///
+    /// 与[`with_config`](Self::with_config)相同。
+    pub fn clone_for_new_run(&self, seed: u64) -> Result<Option<Self>, WfcError> {
+        let Some(tile_set) = self.tile_set.clone_box() else {
+            return Ok(None);
+        };
+        let mut config = self.config.clone();
+        config.random_seed = Some(seed);
+
+        let mut manager = Self::with_config(self.grid.clone(), tile_set, config)?;
+        manager.weight_multipliers = self.weight_multipliers.clone();
+        manager.scoring = self.scoring.clone();
+        Ok(Some(manager))
+    }
+
     /// 使用初始化器初始化WFC系统，对应C++的initialize()虚函数调用
+    ///
+    /// 瓷砖集通过[`TileSetVirtual::expected_neighbor_count`]声明了邻居数量时，
+    /// 邻居数量不一致的真实单元格会导致[`WfcError::NeighborCountMismatch`]。
     pub fn initialize_with<I: WfcInitializer<EdgeData>>(
         &mut self,
         initializer: &mut I,
     ) -> Result<(), WfcError> {
-        initializer.initialize(self)
+        self.clear_decisions();
+        self.entropy_cache.clear();
+        // 支持计数按瓷砖数量建表，重新构建瓷砖集后需要重建
+        self.supports = None;
+        self.failed_resolutions = 0;
+        self.collapse_events.clear();
+        self.collapse_step = 0;
+        self.check_neighbor_counts()?;
+        initializer.initialize(self)?;
+        self.entropy_heap_dirty = true;
+        match self.enforce_global_constraints()? {
+            Some(constraint) => Err(WfcError::GlobalConstraintUnsatisfiable(constraint)),
+            None => Ok(()),
+        }
     }
 
     /// 完整运行WFC算法，对应C++的run()
+    ///
+    /// 使用[`ConflictStrategy::Backtrack`]时，冲突在每次坍塌后立即通过回溯处理，
+    /// 所有决策都无法消除冲突时返回[`WfcError::UnresolvableConflicts`]，
+    /// 其中的[`ConflictReport`]描述修复失败前的冲突单元格。
+    ///
+    /// 使用分层修复时，没有可坍塌的单元格但仍有冲突就进行修复；连续失败次数超过
+    /// [`WfcConfig::max_conflict_retries`]时同样返回[`WfcError::UnresolvableConflicts`]。
     pub fn run(&mut self) -> Result<(), WfcError> {
-        while !self.is_complete() {
-            self.collapse()?;
-        }
-
-        // 解决剩余冲突
-        if !self.resolve_conflicts()? {
-            return Err(WfcError::UnresolvableConflicts);
-        }
+        self.run_with_observer(|_| {})
+    }
 
+    /// 完整运行WFC算法，并在每次坍塌和冲突修复尝试后调用`observer`
+    ///
+    /// 可用于显示进度条或逐帧记录生成过程。
+    ///
+    /// # 示例
+    ///
+    /// ```rust,no_run
+    /// # use rlwfc::{WfcManager, WfcError};
+    /// # fn demo(manager: &mut WfcManager<i32>) -> Result<(), WfcError> {
+    /// manager.run_with_observer(|progress| {
+    ///     println!("{}/{}", progress.completed_count, progress.total_cells);
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run_with_observer<F: FnMut(&WfcProgress)>(
+        &mut self,
+        mut observer: F,
+    ) -> Result<(), WfcError> {
+        self.run_until(|progress| {
+            observer(progress);
+            true
+        })?;
         Ok(())
     }
 
-    /// 单步执行，对应C++的runStep()
-    pub fn run_step(&mut self) -> Result<StepResult, WfcError> {
-        if self.is_complete() {
-            if self.has_conflicts() {
-                if self.resolve_conflicts()? {
-                    Ok(StepResult::ConflictsResolved)
-                } else {
-                    Ok(StepResult::ConflictResolutionFailed)
+    /// 运行WFC算法，直到完成或`should_continue`返回`false`
+    ///
+    /// `should_continue`的调用时机与[`run_with_observer`](Self::run_with_observer)相同。
+    /// 中止后返回[`RunOutcome::Cancelled`]，已坍塌的单元格保持不变，
+    /// 之后可以继续查询状态，或再次调用`run`完成剩余部分。
+    ///
+    /// # 示例
+    ///
+    /// ```rust,no_run
+    /// # use rlwfc::{RunOutcome, WfcManager, WfcError};
+    /// # fn demo(manager: &mut WfcManager<i32>) -> Result<(), WfcError> {
+    /// let mut steps = 0;
+    /// let outcome = manager.run_until(|_| {
+    ///     steps += 1;
+    ///     steps < 500_000
+    /// })?;
+    /// if outcome == RunOutcome::Cancelled {
+    ///     println!("超出步数上限");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run_until<F: FnMut(&WfcProgress) -> bool>(
+        &mut self,
+        mut should_continue: F,
+    ) -> Result<RunOutcome, WfcError> {
+        loop {
+            if !self.is_complete() {
+                match self.collapse() {
+                    Ok(collapsed) => {
+                        if !should_continue(&self.progress(Some(collapsed))) {
+                            return Ok(RunOutcome::Cancelled);
+                        }
+                        continue;
+                    }
+                    // 剩余单元格都已冲突，先修复再继续
+                    Err(WfcError::NoUncollapsedCells) if self.has_conflicts() => {}
+                    Err(error) => return Err(error),
                 }
-            } else {
-                Ok(StepResult::Complete)
+            } else if !self.has_conflicts() {
+                break;
+            }
+
+            // 解决剩余冲突
+            self.clear_decisions();
+            self.resolve_conflicts_with_retries()?;
+            if !should_continue(&self.progress(None)) {
+                return Ok(RunOutcome::Cancelled);
             }
-        } else {
-            self.collapse()?;
-            Ok(StepResult::Collapsed)
         }
-    }
+        self.clear_decisions();
 
-    /// 预设单元格，对应C++的preCollapsed()
-    pub fn pre_collapse(&mut self, cell: CellId, tile: TileId) -> Result<(), WfcError> {
-        let cell_data = self
-            .wfc_data
-            .get_mut(&cell)
-            .ok_or(WfcError::CellNotFound(cell))?;
+        Ok(RunOutcome::Completed)
+    }
 
-        if cell_data.state != CellState::Uncollapsed {
-            return Err(WfcError::CellAlreadyCollapsed);
+    /// 最多执行`max`次坍塌
+    ///
+    /// 适合逐帧动画或增量生成：每次调用推进固定步数，返回后可以查询中间状态再继续。
+    /// 与[`run`](Self::run)不同，这里不会自动修复冲突，而是返回
+    /// [`RunOutcome::NeedsConflictResolution`]交由调用方处理。
+    ///
+    /// ```rust
+    /// use rlwfc::{RunOutcome, WfcError, WfcManager};
+    ///
+    /// # fn demo(manager: &mut WfcManager<i32>) -> Result<(), WfcError> {
+    /// while manager.run_steps(20)? == RunOutcome::BudgetExhausted {
+    ///     // 绘制当前帧
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run_steps(&mut self, max: usize) -> Result<RunOutcome, WfcError> {
+        for _ in 0..max {
+            if self.is_complete() {
+                break;
+            }
+            match self.collapse() {
+                Ok(_) => {}
+                Err(WfcError::NoUncollapsedCells) if self.has_conflicts() => {
+                    return Ok(RunOutcome::NeedsConflictResolution);
+                }
+                Err(error) => return Err(error),
+            }
         }
 
-        if !cell_data.possibilities.contains(&tile) {
-            return Err(WfcError::InvalidTileChoice);
+        if !self.is_complete() {
+            Ok(RunOutcome::BudgetExhausted)
+        } else if self.has_conflicts() {
+            Ok(RunOutcome::NeedsConflictResolution)
+        } else {
+            self.clear_decisions();
+            Ok(RunOutcome::Completed)
         }
+    }
 
-        self.set_tile_for_cell(cell, tile)?;
-        self.propagate_effects(cell)?;
+    /// 只在给定的单元格区域内重新生成
+    ///
+    /// 用于修补已生成地图中的局部区域：区域内的单元格先恢复为全部可能性
+    /// （已坍塌的单元格重新计为未完成），再按区域外邻居收紧区域边界，
+    /// 最后只在区域内坍塌。区域外的单元格视为固定约束，无论是否已坍塌都保持不变，
+    /// 约束传播在区域边界处停止。
+    ///
+    /// 区域内出现的冲突不会用分层修复处理（修复会改动区域外的邻居），
+    /// 需要自动解决时使用[`ConflictStrategy::Backtrack`]，否则返回错误，
+    /// 冲突单元格保留在区域内，可以再次调用本方法重试。
+    ///
+    /// # 错误
+    ///
+    /// * `CellNotFound` - 区域包含未初始化的单元格
+    /// * `UnresolvableConflicts` - 区域内仍有冲突单元格
+    pub fn run_region(&mut self, cells: &HashSet<CellId>) -> Result<(), WfcError> {
+        let mut region: Vec<CellId> = cells.iter().copied().collect();
+        region.sort_unstable();
+        if let Some(&missing) = region
+            .iter()
+            .find(|&cell_id| !self.wfc_data.contains_key(cell_id))
+        {
+            return Err(WfcError::CellNotFound(missing));
+        }
 
-        Ok(())
+        self.solve_region(cells, &region, None)
     }
 
-    /// 检查是否完成，对应C++的isComplete()
-    pub fn is_complete(&self) -> bool {
-        self.completed_count == self.grid.get_cells_count()
-    }
+    /// 强制单元格使用指定瓷砖，并只重新生成周围必要的最小范围
+    ///
+    /// 适用于交互式编辑：地图生成后修改一个单元格，邻近区域自动调整。
+    /// 先只重置目标单元格，无法与区域外的单元格相容时逐圈扩大区域
+    /// （每次加入当前区域的全部邻居），直到指定瓷砖和区域内其余单元格都能坍塌。
+    /// 区域外的单元格保持不变。每次尝试失败都会回滚到调用前的状态。
+    ///
+    /// # 错误
+    ///
+    /// * `CellNotFound` - 单元格不存在
+    /// * `InvalidTileChoice` - 瓷砖不存在
+    /// * 区域扩展到整个连通网格仍无法满足时，返回最后一次尝试的错误，状态保持不变
+    pub fn repin(&mut self, cell: CellId, tile: TileId) -> Result<(), WfcError> {
+        if !self.wfc_data.contains_key(&cell) {
+            return Err(WfcError::CellNotFound(cell));
+        }
+        if self.tile_set.get_tile(tile).is_none() {
+            return Err(WfcError::InvalidTileChoice { cell, tile });
+        }
 
-    /// 获取单元格状态，对应C++的getCellState()
-    pub fn get_cell_state(&self, cell_id: CellId) -> Result<CellState, WfcError> {
-        self.wfc_data
-            .get(&cell_id)
-            .map(|data| data.state)
-            .ok_or(WfcError::CellNotFound(cell_id))
+        let snapshot = self.create_snapshot();
+        let mut cells = HashSet::from([cell]);
+        loop {
+            let mut region: Vec<CellId> = cells.iter().copied().collect();
+            region.sort_unstable();
+            let error = match self.solve_region(&cells, &region, Some((cell, tile))) {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+            self.restore_snapshot(&snapshot)?;
+
+            // 向外扩展一圈，没有新单元格可加入时放弃
+            let mut grown = cells.clone();
+            for &cell_id in &region {
+                grown.extend(self.grid.get_neighbors(cell_id));
+                grown.extend(self.grid.get_incoming_neighbors(cell_id));
+            }
+            grown.retain(|cell_id| self.wfc_data.contains_key(cell_id));
+            if grown.len() == cells.len() {
+                return Err(error);
+            }
+            cells = grown;
+        }
     }
 
-    /// 获取已坍塌单元格的瓷砖，对应C++的getCollapsedCellData()
-    pub fn get_collapsed_cell_tile(&self, cell_id: CellId) -> Result<TileId, WfcError> {
+    /// 重置区域并在区域内重新生成，可选地先把一个单元格固定为指定瓷砖
+    fn solve_region(
+        &mut self,
+        cells: &HashSet<CellId>,
+        region: &[CellId],
+        pin: Option<(CellId, TileId)>,
+    ) -> Result<(), WfcError> {
+        // 旧的决策点记录的是区域重置前的状态
+        self.clear_decisions();
+        let all_tiles = self.tile_set.get_all_tile_ids();
+        for &cell_id in region {
+            let cell_data = &self.wfc_data[&cell_id];
+            if cell_data.state == CellState::Collapsed {
+                self.completed_count -= 1;
+            }
+            let possibilities = cell_data.possibility_set(all_tiles.iter().copied());
+            let entropy = self.reset_possibilities(cell_id, possibilities) + self.entropy_noise();
+            let cell_data = self.wfc_data.get_mut(&cell_id).unwrap();
+            cell_data.entropy = entropy;
+            cell_data.state = CellState::Uncollapsed;
+        }
+
+        self.region = Some(cells.clone());
+        self.entropy_heap_dirty = true;
+        let result = self.run_within_region(region, pin);
+        // 区域外的过期条目在选择时被丢弃，结束后重建优先队列
+        self.region = None;
+        self.entropy_heap_dirty = true;
+        self.clear_decisions();
+        result
+    }
+
+    /// 在已设置的区域内收紧边界并坍塌全部单元格
+    fn run_within_region(
+        &mut self,
+        region: &[CellId],
+        pin: Option<(CellId, TileId)>,
+    ) -> Result<(), WfcError> {
+        for &cell_id in region {
+            if self.wfc_data[&cell_id].state == CellState::Uncollapsed
+                && self.update_neighbor_possibilities(cell_id)?
+            {
+                self.propagate_effects(cell_id)?;
+            }
+        }
+
+        if let Some((cell, tile)) = pin {
+            let cell_data = &self.wfc_data[&cell];
+            if cell_data.state != CellState::Uncollapsed || !cell_data.is_possible(tile) {
+                return Err(WfcError::InvalidTileChoice { cell, tile });
+            }
+            self.set_tile_for_cell(cell, tile)?;
+            self.propagate_effects(cell)?;
+        }
+
+        loop {
+            match self.collapse() {
+                Ok(_) => {}
+                Err(WfcError::NoUncollapsedCells) => break,
+                Err(error) => return Err(error),
+            }
+        }
+
+        if region
+            .iter()
+            .any(|cell_id| self.wfc_data[cell_id].state == CellState::Conflict)
+        {
+            return Err(WfcError::UnresolvableConflicts(self.conflict_report()));
+        }
+        Ok(())
+    }
+
+    /// 单步执行，对应C++的runStep()
+    ///
+    /// 没有可坍塌的单元格但仍有冲突时进行一次修复。连续失败次数超过
+    /// [`WfcConfig::max_conflict_retries`]后返回[`WfcError::UnresolvableConflicts`]，
+    /// 循环调用本方法的调用方不会在无解的瓷砖集上无限重试。
+    pub fn run_step(&mut self) -> Result<StepResult, WfcError> {
+        if self.is_complete() {
+            if self.has_conflicts() {
+                self.resolve_step()
+            } else {
+                self.clear_decisions();
+                Ok(StepResult::Complete)
+            }
+        } else {
+            match self.collapse() {
+                Ok(_) => Ok(StepResult::Collapsed),
+                Err(WfcError::NoUncollapsedCells) if self.has_conflicts() => self.resolve_step(),
+                Err(error) => Err(error),
+            }
+        }
+    }
+
+    /// 单步执行中的一次冲突修复
+    fn resolve_step(&mut self) -> Result<StepResult, WfcError> {
+        if self.resolve_conflicts_with_retries()? {
+            Ok(StepResult::ConflictsResolved)
+        } else {
+            Ok(StepResult::ConflictResolutionFailed)
+        }
+    }
+
+    /// 预设单元格，对应C++的preCollapsed()
+    pub fn pre_collapse(&mut self, cell: CellId, tile: TileId) -> Result<(), WfcError> {
         let cell_data = self
             .wfc_data
-            .get(&cell_id)
-            .ok_or(WfcError::CellNotFound(cell_id))?;
+            .get_mut(&cell)
+            .ok_or(WfcError::CellNotFound(cell))?;
 
-        if cell_data.state == CellState::Collapsed && cell_data.possibilities.len() == 1 {
-            Ok(cell_data.possibilities[0])
-        } else {
-            Err(WfcError::InconsistentState)
+        if cell_data.state != CellState::Uncollapsed {
+            return Err(WfcError::CellAlreadyCollapsed);
+        }
+
+        if !cell_data.is_possible(tile) {
+            return Err(WfcError::InvalidTileChoice { cell, tile });
         }
+
+        self.set_tile_for_cell(cell, tile)?;
+        self.propagate_effects(cell)?;
+
+        Ok(())
     }
 
-    /// 获取网格系统引用，对应C++的getGrid()
-    pub fn get_grid(&self) -> &GridSystem {
-        &self.grid
+    /// 一次预设多个单元格，所有赋值完成后只进行一轮约束传播
+    ///
+    /// 适合批量设置边界等区域约束。先校验全部赋值再修改状态，
+    /// 任意一项无效时不做任何修改。
+    ///
+    /// # 错误
+    ///
+    /// * `CellNotFound` - 单元格不存在
+    /// * `CellAlreadyCollapsed` - 单元格已坍塌、处于冲突状态或在列表中重复出现
+    /// * `InvalidTileChoice` - 瓷砖不在单元格当前的可能性中
+    pub fn pre_collapse_many(&mut self, assignments: &[(CellId, TileId)]) -> Result<(), WfcError> {
+        let mut seen = HashSet::new();
+        for &(cell, tile) in assignments {
+            let cell_data = self
+                .wfc_data
+                .get(&cell)
+                .ok_or(WfcError::CellNotFound(cell))?;
+            if cell_data.state != CellState::Uncollapsed || !seen.insert(cell) {
+                return Err(WfcError::CellAlreadyCollapsed);
+            }
+            if !cell_data.is_possible(tile) {
+                return Err(WfcError::InvalidTileChoice { cell, tile });
+            }
+        }
+
+        for &(cell, tile) in assignments {
+            self.set_tile_for_cell(cell, tile)?;
+        }
+        let cells: Vec<CellId> = assignments.iter().map(|&(cell, _)| cell).collect();
+        #[cfg(feature = "parallel")]
+        return self.propagate_from_parallel(&cells);
+        #[cfg(not(feature = "parallel"))]
+        self.propagate_from(&cells)
     }
 
-    /// 获取所有瓷砖ID
-    pub fn get_all_tile_ids(&self) -> Vec<TileId> {
-        (0..self.tile_set.get_tile_count()).collect()
+    /// 禁止单元格使用某个瓷砖，等价于`ban_tiles(cell, &[tile])`
+    pub fn ban_tile(&mut self, cell: CellId, tile: TileId) -> Result<(), WfcError> {
+        self.ban_tiles(cell, &[tile])
     }
 
-    /// 获取瓷砖
-    pub fn get_tile(&self, tile_id: TileId) -> Option<&Tile<EdgeData>> {
-        if tile_id < self.tile_set.get_tile_count() {
-            self.tile_set.get_tile(tile_id)
+    /// 从单元格的可能性中移除指定瓷砖并传播约束
+    ///
+    /// 用于在运行前限制特定位置的瓷砖（例如地图边界不允许出现水面），
+    /// 而不必直接坍塌单元格。已不在可能性中的瓷砖会被忽略；
+    /// 可能性被全部移除时单元格进入冲突状态。
+    ///
+    /// # 错误
+    ///
+    /// * `CellNotFound` - 单元格不存在
+    /// * `CellAlreadyCollapsed` - 单元格已坍塌或处于冲突状态
+    /// * `TileNotFound` - 瓷砖ID无效
+    pub fn ban_tiles(&mut self, cell: CellId, tiles: &[TileId]) -> Result<(), WfcError> {
+        let cell_data = self
+            .wfc_data
+            .get(&cell)
+            .ok_or(WfcError::CellNotFound(cell))?;
+        if cell_data.state != CellState::Uncollapsed {
+            return Err(WfcError::CellAlreadyCollapsed);
+        }
+        if let Some(&tile_id) = tiles
+            .iter()
+            .find(|&&tile_id| self.tile_set.get_tile(tile_id).is_none())
+        {
+            return Err(WfcError::TileNotFound(tile_id));
+        }
+
+        let mut possibilities = cell_data.possibilities.clone();
+        for &tile_id in tiles {
+            if tile_id < possibilities.len() {
+                possibilities.remove(tile_id);
+            }
+        }
+        if possibilities == cell_data.possibilities {
+            return Ok(());
+        }
+
+        let conflict = possibilities.is_clear();
+        let entropy = self.narrow_possibilities(cell, possibilities) + self.entropy_noise();
+        let cell_data = self.wfc_data.get_mut(&cell).unwrap();
+        cell_data.entropy = entropy;
+        if conflict {
+            cell_data.state = CellState::Conflict;
+            return Ok(());
+        }
+
+        self.push_entropy_entry(cell);
+        self.propagate_effects(cell)
+    }
+
+    /// 修改瓷砖权重，已初始化的单元格立即按新权重更新
+    ///
+    /// 通过[`TileSetVirtual::tile_set_mut`]修改权重后，丢弃按旧权重缓存的熵值，
+    /// 并从头计算所有单元格的权重和与熵值，之后的熵值和瓷砖选择都使用新权重。
+    ///
+    /// # 错误
+    ///
+    /// * `TileSetNotMutable` - 瓷砖集不支持修改
+    /// * `TileNotFound` - 瓷砖不存在或已被删除
+    pub fn set_tile_weight(&mut self, tile: TileId, weight: i32) -> Result<(), WfcError> {
+        self.tile_set
+            .tile_set_mut()
+            .ok_or(WfcError::TileSetNotMutable)?
+            .set_weight(tile, weight)
+            .map_err(|_| WfcError::TileNotFound(tile))?;
+        self.sync_weight_generation()
+    }
+
+    /// 设置单元格上某个瓷砖的权重倍数
+    ///
+    /// 用于让瓷砖在特定区域更常见（例如靠近地图底部的水面）。倍数同时作用于该单元格的
+    /// 瓷砖选择概率和熵值，未设置时为1.0。已初始化的未坍塌单元格会立即更新熵值，
+    /// 已坍塌的单元格不受影响。
+    ///
+    /// # 错误
+    ///
+    /// * `CellNotFound` - 单元格不存在
+    /// * `TileNotFound` - 瓷砖ID无效
+    /// * `InvalidWeightMultiplier` - 倍数为负数或不是有限值
+    pub fn set_cell_weight_multiplier(
+        &mut self,
+        cell: CellId,
+        tile: TileId,
+        factor: f64,
+    ) -> Result<(), WfcError> {
+        if !self.grid.contains_cell(cell) {
+            return Err(WfcError::CellNotFound(cell));
+        }
+        if self.tile_set.get_tile(tile).is_none() {
+            return Err(WfcError::TileNotFound(tile));
+        }
+        if !factor.is_finite() || factor < 0.0 {
+            return Err(WfcError::InvalidWeightMultiplier(factor));
+        }
+
+        let multipliers = self.weight_multipliers.entry(cell).or_default();
+        if factor == 1.0 {
+            multipliers.remove(&tile);
+            if multipliers.is_empty() {
+                self.weight_multipliers.remove(&cell);
+            }
         } else {
-            None
+            multipliers.insert(tile, factor);
+        }
+
+        let Some(cell_data) = self.wfc_data.get(&cell) else {
+            return Ok(());
+        };
+        if cell_data.state == CellState::Uncollapsed {
+            // 权重已改变，权重和需要从头计算
+            let possibilities = cell_data.possibilities.clone();
+            let entropy = self.reset_possibilities(cell, possibilities) + self.entropy_noise();
+            self.wfc_data.get_mut(&cell).unwrap().entropy = entropy;
+            self.push_entropy_entry(cell);
         }
+        Ok(())
     }
 
-    // ==========================================================================
-    // 核心算法实现 - 对应原C++的private方法
-    // ==========================================================================
+    /// 设置软约束评分函数
+    ///
+    /// 硬约束仍由[`TileSetVirtual::judge_possibility`]决定；评分函数只在坍塌时
+    /// 按邻居的当前可能性把候选瓷砖的权重乘以返回的倍数，用于表达"草地附近更可能是草地"
+    /// 这类偏好。评分不影响熵值，也不会排除任何瓷砖，除非所有候选的倍数都为0，
+    /// 此时选择ID最小的候选。每次坍塌会为每个候选瓷砖调用一次评分函数。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{AdjacencyTileSet, Direction4Full, GridSystem, WfcManager};
+    ///
+    /// let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+    /// let grass = tile_set.add_tile(vec![0; 4], 1);
+    /// tile_set.add_tile(vec![0; 4], 1);
+    /// let mut manager = WfcManager::new(GridSystem::new(), Box::new(tile_set)).unwrap();
+    ///
+    /// // 已确定为草地的邻居越多，草地的权重越高
+    /// manager.set_scoring(move |tile, neighbors| {
+    ///     let grass_neighbors = neighbors.iter().filter(|(_, tiles)| *tiles == [grass]).count();
+    ///     if tile == grass { 1.0 + grass_neighbors as f64 } else { 1.0 }
+    /// });
+    /// ```
+    pub fn set_scoring(
+        &mut self,
+        scoring: impl Fn(TileId, &[(usize, &[TileId])]) -> f64 + Send + Sync + 'static,
+    ) {
+        self.scoring = Some(Arc::new(scoring));
+    }
 
-    /// 主坍塌算法，对应C++的collapse()
-    fn collapse(&mut self) -> Result<(), WfcError> {
-        // 1. 找到最小熵单元
-        let min_entropy_cell = self.find_min_entropy_cell()?;
+    /// 移除软约束评分函数
+    pub fn clear_scoring(&mut self) {
+        self.scoring = None;
+    }
 
-        // 2. 从概率分布中选择瓷砖
-        let chosen_tile = self.choose_tile_from_probabilities(min_entropy_cell)?;
+    /// 使用预先计算的可能性热启动
+    ///
+    /// 用于迭代编辑：传入上一次运行的各单元格可能性（通常只修改了少数单元格），
+    /// 系统只需从未确定的单元格重新传播，而不必从头求解。
+    ///
+    /// - 单一可能性的单元格视为已坍塌，空列表视为冲突，其余为未坍塌
+    /// - 未出现在`domains`中的单元格保持原状
+    /// - 已坍塌单元格必须与邻居的可能性兼容，否则返回`InconsistentState`并保持系统状态不变
+    ///
+    /// # 错误
+    ///
+    /// * `CellNotFound` - `domains`中包含未初始化的单元格
+    /// * `TileNotFound` - `domains`中包含无效的瓷砖ID
+    /// * `InconsistentState` - 热启动数据自相矛盾
+    pub fn warm_start(&mut self, domains: HashMap<CellId, Vec<TileId>>) -> Result<(), WfcError> {
+        for (&cell_id, domain) in &domains {
+            if !self.wfc_data.contains_key(&cell_id) {
+                return Err(WfcError::CellNotFound(cell_id));
+            }
+            if let Some(&tile_id) = domain
+                .iter()
+                .find(|&&tile_id| self.tile_set.get_tile(tile_id).is_none())
+            {
+                return Err(WfcError::TileNotFound(tile_id));
+            }
+        }
 
-        // 3. 设置瓷砖并更新状态
-        self.set_tile_for_cell(min_entropy_cell, chosen_tile)?;
+        let snapshot = self.create_snapshot();
+        let mut open_cells = Vec::new();
+        let mut collapsed_cells = Vec::new();
+
+        for (cell_id, domain) in domains {
+            let domain = self.wfc_data[&cell_id].possibility_set(domain);
+            let state = match domain.count_ones(..) {
+                0 => CellState::Conflict,
+                1 => CellState::Collapsed,
+                _ => CellState::Uncollapsed,
+            };
+            match state {
+                CellState::Uncollapsed => open_cells.push(cell_id),
+                CellState::Collapsed => collapsed_cells.push(cell_id),
+                CellState::Conflict => {}
+            }
 
-        // 4. 传播约束效果
-        self.propagate_effects(min_entropy_cell)?;
+            let entropy = self.reset_possibilities(cell_id, domain);
+            let cell_data = self.wfc_data.get_mut(&cell_id).unwrap();
+            cell_data.entropy = entropy;
+            cell_data.state = state;
+        }
+        self.entropy_heap_dirty = true;
+
+        self.completed_count = self
+            .wfc_data
+            .values()
+            .filter(|data| data.state == CellState::Collapsed)
+            .count();
+
+        // 校验已坍塌单元格与真实邻居一致，虚拟边界节点不参与约束
+        let consistent = self
+            .wfc_data
+            .iter()
+            .filter(|(&cell_id, data)| {
+                data.state == CellState::Collapsed && !self.grid.is_virtual_node(cell_id)
+            })
+            .all(|(&cell_id, data)| self.is_consistent_with_real_neighbors(cell_id, data));
+        if !consistent {
+            self.restore_snapshot(&snapshot)?;
+            return Err(WfcError::InconsistentState);
+        }
+
+        // 新坍塌的单元格约束未出现在domains中的邻居
+        self.propagate_from(&collapsed_cells)?;
+
+        // 未确定的单元格先按邻居收紧，再继续传播
+        for cell_id in open_cells {
+            if self.update_neighbor_possibilities(cell_id)? {
+                self.propagate_effects(cell_id)?;
+            }
+        }
 
         Ok(())
     }
 
-    /// 寻找最小熵单元格，对应C++的reCalcMinEntropyCell()
-    fn find_min_entropy_cell(&self) -> Result<CellId, WfcError> {
+    /// 运行前预检查，一次性汇总常见的配置错误
+    ///
+    /// 依次检查：
+    ///
+    /// 1. 瓷砖集是否为空
+    /// 2. 瓷砖边数量、单元格邻居数量是否与方向系统`D`一致
+    /// 3. 每个瓷砖在每个方向上是否至少有一个可相邻的瓷砖
+    /// 4. 是否存在孤立单元格
+    /// 5. 方向查询是否可逆（沿某方向到达邻居后能沿相反方向返回）
+    ///
+    /// 虚拟边界节点不参与单元格相关的检查。边数量不一致的瓷砖会跳过邻接检查，
+    /// 避免调用`judge_possibility`时越界。
+    ///
+    /// # 示例
+    ///
+    /// ```rust,no_run
+    /// # use rlwfc::{WfcManager, Direction4Full};
+    /// # fn check(manager: &WfcManager<i32>) {
+    /// if let Err(issues) = manager.preflight::<Direction4Full>() {
+    ///     for issue in issues {
+    ///         eprintln!("{}", issue);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn preflight<D: DirectionTrait>(&self) -> Result<(), Vec<PreflightIssue>> {
+        let mut issues = Vec::new();
+        let expected = D::all_directions().len();
+        let tile_ids = self.tile_set.get_all_tile_ids();
+
+        if tile_ids.is_empty() {
+            issues.push(PreflightIssue::EmptyTileSet);
+        }
+
+        // 瓷砖边数量
+        let mut well_formed = Vec::new();
+        for &tile_id in &tile_ids {
+            let edge_count = self
+                .tile_set
+                .get_tile(tile_id)
+                .map_or(0, |tile| tile.edge_count());
+            if edge_count == expected {
+                well_formed.push(tile_id);
+            } else {
+                issues.push(PreflightIssue::TileArityMismatch {
+                    tile: tile_id,
+                    edge_count,
+                    expected,
+                });
+            }
+        }
+
+        // 邻接完整性：每个方向至少有一个可相邻的瓷砖
+        for &tile_id in &well_formed {
+            for direction_index in 0..expected {
+                let has_partner = well_formed.iter().any(|&other| {
+                    let mut neighbor_possibilities = vec![vec![]; expected];
+                    neighbor_possibilities[direction_index] = vec![other];
+                    self.tile_set
+                        .judge_possibility(&neighbor_possibilities, tile_id)
+                });
+                if !has_partner {
+                    issues.push(PreflightIssue::MissingAdjacency {
+                        tile: tile_id,
+                        direction_index,
+                    });
+                }
+            }
+        }
+
+        // 单元格邻居数量与孤立单元格
+        let has_incoming: HashSet<CellId> = self
+            .grid
+            .get_all_cells()
+            .flat_map(|cell_id| self.grid.get_neighbors(cell_id))
+            .collect();
+        let real_cells: Vec<CellId> = self
+            .grid
+            .get_all_cells()
+            .filter(|&cell_id| !self.grid.is_virtual_node(cell_id))
+            .collect();
+
+        for &cell_id in &real_cells {
+            let neighbor_count = self.grid.get_neighbors(cell_id).len();
+            if neighbor_count == 0 && !has_incoming.contains(&cell_id) {
+                issues.push(PreflightIssue::IsolatedCell(cell_id));
+            } else if neighbor_count != expected {
+                issues.push(PreflightIssue::CellArityMismatch {
+                    cell: cell_id,
+                    neighbor_count,
+                    expected,
+                });
+            }
+        }
+
+        // 方向一致性
+        for &cell_id in &real_cells {
+            for direction in D::all_directions() {
+                let Some(opposite) = direction.opposite() else {
+                    continue;
+                };
+                let Some(neighbor) = self.grid.get_neighbor_by_direction(cell_id, direction) else {
+                    continue;
+                };
+                if self.grid.is_virtual_node(neighbor) {
+                    continue;
+                }
+                if self.grid.get_neighbor_by_direction(neighbor, opposite) != Some(cell_id) {
+                    issues.push(PreflightIssue::DirectionInconsistency {
+                        cell: cell_id,
+                        direction: direction.name(),
+                        neighbor,
+                    });
+                }
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// 检查是否完成，对应C++的isComplete()
+    pub fn is_complete(&self) -> bool {
+        self.completed_count == self.grid.get_cells_count()
+    }
+
+    /// 获取单元格状态，对应C++的getCellState()
+    pub fn get_cell_state(&self, cell_id: CellId) -> Result<CellState, WfcError> {
         self.wfc_data
-            .iter()
-            .filter(|(_, data)| data.state == CellState::Uncollapsed)
-            .min_by(|(_, a), (_, b)| {
-                a.entropy
-                    .partial_cmp(&b.entropy)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
-            .map(|(&cell_id, _)| cell_id)
-            .ok_or(WfcError::NoUncollapsedCells)
+            .get(&cell_id)
+            .map(|data| data.state)
+            .ok_or(WfcError::CellNotFound(cell_id))
     }
 
-    /// 从概率分布选择瓷砖，对应C++的chooseTileFromProbabilities()
-    fn choose_tile_from_probabilities(&mut self, cell_id: CellId) -> Result<TileId, WfcError> {
+    /// 获取已坍塌单元格的瓷砖，对应C++的getCollapsedCellData()
+    pub fn get_collapsed_cell_tile(&self, cell_id: CellId) -> Result<TileId, WfcError> {
         let cell_data = self
             .wfc_data
             .get(&cell_id)
             .ok_or(WfcError::CellNotFound(cell_id))?;
 
-        if cell_data.possibilities.is_empty() {
-            return Err(WfcError::InvalidTileChoice);
+        if cell_data.state == CellState::Collapsed && cell_data.possibility_count() == 1 {
+            cell_data
+                .possibilities
+                .minimum()
+                .ok_or(WfcError::InconsistentState)
+        } else {
+            Err(WfcError::InconsistentState)
         }
+    }
 
-        // 计算总权重，对应C++的weightSum计算
-        let mut total_weight = 0i32;
-        for &tile_id in &cell_data.possibilities {
-            if let Some(tile) = self.tile_set.get_tile(tile_id) {
-                total_weight += tile.weight;
-            }
+    /// 获取单元格当前仍可能的瓷砖，按瓷砖ID升序排列
+    ///
+    /// 可能性以位集存储，因此返回新分配的列表而不是切片。
+    pub fn get_possibilities(&self, cell_id: CellId) -> Result<Vec<TileId>, WfcError> {
+        self.wfc_data
+            .get(&cell_id)
+            .map(CellWfcData::possibilities)
+            .ok_or(WfcError::CellNotFound(cell_id))
+    }
+
+    /// 获取单元格当前的熵值，已坍塌的单元格为0
+    pub fn get_entropy(&self, cell_id: CellId) -> Result<f64, WfcError> {
+        self.wfc_data
+            .get(&cell_id)
+            .map(|data| data.entropy)
+            .ok_or(WfcError::CellNotFound(cell_id))
+    }
+
+    /// 获取所有未坍塌单元格及其熵值，按熵值升序排列，熵值相同时按`CellId`排序
+    ///
+    /// 即求解器选择下一个坍塌单元格时看到的"波"，可用于可视化或实现外部的观察策略。
+    /// 熵值包含配置的噪声；边界的虚拟节点同样参与坍塌，也会出现在结果中，
+    /// 可以用[`GridSystem::is_virtual_node`]过滤。
+    pub fn uncollapsed_by_entropy(&self) -> Vec<(CellId, f64)> {
+        let mut cells: Vec<(CellId, f64)> = self
+            .wfc_data
+            .iter()
+            .filter(|(_, data)| data.state == CellState::Uncollapsed)
+            .map(|(&cell_id, data)| (cell_id, data.entropy))
+            .collect();
+        cells.sort_unstable_by(|(id_a, a), (id_b, b)| a.total_cmp(b).then_with(|| id_a.cmp(id_b)));
+        cells
+    }
+
+    /// 获取所有已坍塌单元格选定的瓷砖
+    ///
+    /// 未坍塌和冲突的单元格不出现在结果中。需要确认结果完整时使用
+    /// [`try_collapsed_tiles`](Self::try_collapsed_tiles)。
+    /// 结果按`CellId`排序，遍历顺序在多次运行之间保持一致。
+    pub fn collapsed_tiles(&self) -> BTreeMap<CellId, TileId> {
+        self.wfc_data
+            .iter()
+            .filter(|(_, data)| data.state == CellState::Collapsed)
+            .filter_map(|(&cell_id, data)| Some((cell_id, data.possibilities.minimum()?)))
+            .collect()
+    }
+
+    /// 获取所有单元格选定的瓷砖，要求每个单元格都已坍塌
+    ///
+    /// # 错误
+    ///
+    /// * `UnresolvableConflicts` - 存在冲突单元格
+    /// * `InconsistentState` - 存在尚未坍塌的单元格
+    pub fn try_collapsed_tiles(&self) -> Result<BTreeMap<CellId, TileId>, WfcError> {
+        if self.has_conflicts() {
+            return Err(WfcError::UnresolvableConflicts(self.conflict_report()));
         }
 
-        if total_weight == 0 {
-            return Ok(cell_data.possibilities[0]); // 如果没有权重，返回第一个
+        let tiles = self.collapsed_tiles();
+        if tiles.len() != self.wfc_data.len() {
+            return Err(WfcError::InconsistentState);
         }
+        Ok(tiles)
+    }
 
-        // 使用预计算的随机数，完全对应C++的逻辑
-        // C++: randNum %= weightSum;
-        let rand_num = cell_data.rand_num % total_weight;
-        
-        // C++: 累计权重直到 weightSum >= randNum
-        let mut weight_sum = 0i32;
-        for &tile_id in &cell_data.possibilities {
-            if let Some(tile) = self.tile_set.get_tile(tile_id) {
-                weight_sum += tile.weight;
-                if weight_sum > rand_num {  // C++: weightSum >= randNum，但我们用>避免边界问题
-                    return Ok(tile_id);
+    /// 生成当前冲突单元格的诊断报告
+    ///
+    /// 报告列出每个冲突单元格剩余的可能瓷砖，以及约束它的真实邻居的当前可能瓷砖，
+    /// 便于定位哪些邻居组合排除了所有瓷砖。没有冲突时返回空报告。
+    pub fn conflict_report(&self) -> ConflictReport {
+        let conflicts = self
+            .collect_conflict_cells()
+            .into_iter()
+            .map(|cell| {
+                let neighbors = self
+                    .grid
+                    .get_neighbors(cell)
+                    .into_iter()
+                    .filter(|&neighbor| !self.grid.is_virtual_node(neighbor))
+                    .filter_map(|neighbor| {
+                        let data = self.wfc_data.get(&neighbor)?;
+                        Some((neighbor, data.possibilities()))
+                    })
+                    .collect();
+                CellConflict {
+                    cell,
+                    possibilities: self.wfc_data[&cell].possibilities(),
+                    neighbors,
                 }
-            }
+            })
+            .collect();
+
+        ConflictReport { conflicts }
+    }
+
+    /// 统计当前各状态的单元格数量和瓷砖使用次数，用于运行后的分析
+    ///
+    /// 虚拟边界节点不计入，已坍塌的单元格按选定的瓷砖计入
+    /// [`tile_usage`](WfcStatistics::tile_usage)。
+    pub fn statistics(&self) -> WfcStatistics {
+        let mut statistics = WfcStatistics {
+            total_cells: 0,
+            collapsed: 0,
+            uncollapsed: 0,
+            conflict: 0,
+            completion_ratio: 1.0,
+            tile_usage: BTreeMap::new(),
+        };
+
+        for (&cell_id, data) in &self.wfc_data {
+            if self.grid.is_virtual_node(cell_id) {
+                continue;
+            }
+            statistics.total_cells += 1;
+            match data.state {
+                CellState::Uncollapsed => statistics.uncollapsed += 1,
+                CellState::Conflict => statistics.conflict += 1,
+                CellState::Collapsed => {
+                    statistics.collapsed += 1;
+                    if let Some(tile_id) = data.possibilities.minimum() {
+                        *statistics.tile_usage.entry(tile_id).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        if statistics.total_cells > 0 {
+            statistics.completion_ratio =
+                statistics.collapsed as f64 / statistics.total_cells as f64;
+        }
+        statistics
+    }
+
+    /// 统计单元格的真实邻居中各状态的数量
+    ///
+    /// 返回`(已坍塌, 未坍塌, 冲突)`，虚拟节点不计入。可用于诊断，
+    /// 或实现优先坍塌受约束最多的单元格之类的自定义观察策略。
+    /// 单元格不存在时返回全零。
+    pub fn neighbor_state_counts(&self, cell: CellId) -> (usize, usize, usize) {
+        let mut counts = (0, 0, 0);
+        let neighbors = self.grid.get_neighbors(cell).into_iter();
+        for neighbor in neighbors.filter(|&neighbor| !self.grid.is_virtual_node(neighbor)) {
+            match self.wfc_data.get(&neighbor).map(|data| data.state) {
+                Some(CellState::Collapsed) => counts.0 += 1,
+                Some(CellState::Uncollapsed) => counts.1 += 1,
+                Some(CellState::Conflict) => counts.2 += 1,
+                None => {}
+            }
+        }
+        counts
+    }
+
+    /// 获取单元格在运行过程中被排除的瓷砖
+    ///
+    /// 结果为全部瓷砖与该单元格当前可能性列表的差集，按瓷砖ID升序排列。
+    /// 被排除的瓷砖与当前可能性合起来恰好是完整的瓷砖集。
+    pub fn eliminated_tiles(&self, cell: CellId) -> Result<Vec<TileId>, WfcError> {
+        let cell_data = self
+            .wfc_data
+            .get(&cell)
+            .ok_or(WfcError::CellNotFound(cell))?;
+
+        Ok(self
+            .tile_set
+            .get_all_tile_ids()
+            .into_iter()
+            .filter(|&tile_id| !cell_data.is_possible(tile_id))
+            .collect())
+    }
+
+    /// 获取冲突修复期间快照内存占用的峰值（字节）
+    ///
+    /// 可用于为[`WfcConfig::max_snapshot_memory`]选择合适的上限。
+    pub fn peak_snapshot_memory(&self) -> usize {
+        self.peak_snapshot_bytes
+    }
+
+    /// 获取累计的约束传播步数
+    ///
+    /// 每次检查并更新一个邻居的可能性计为一步，可用于比较不同启动方式的开销。
+    pub fn propagation_steps(&self) -> usize {
+        self.propagation_steps
+    }
+
+    /// 按发生顺序返回坍塌事件`(步序号, 单元格, 瓷砖)`，用于导出生成过程的动画
+    ///
+    /// 观察、预设以及冲突修复中的每次坍塌都会记录，步序号严格递增。回溯撤销的坍塌
+    /// 会从历史中移除（步序号不回退，因此可能出现间隔），重放全部事件即可得到当前结果。
+    /// [`warm_start`](Self::warm_start)直接写入的状态不产生事件。
+    /// 重新调用[`initialize_with`](Self::initialize_with)时清空。
+    pub fn collapse_events(&self) -> &[(usize, CellId, TileId)] {
+        &self.collapse_events
+    }
+
+    /// 重新设置随机种子
+    ///
+    /// 使用新的种子重建内部随机数生成器，空间哈希并列规则也随之改变。
+    /// 每个单元格的预计算随机数在`initialize_with`时从该生成器取得，
+    /// 因此需要在初始化之前调用才能完全复现以该种子创建的管理器。
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.config.random_seed = Some(seed);
+        self.rng = StdRng::seed_from_u64(seed);
+        self.entropy_heap_dirty = true;
+    }
+
+    /// 保存当前状态作为检查点
+    ///
+    /// 适用于交互式编辑：在尝试性的`pre_collapse`之前保存，
+    /// 对结果不满意时通过[`restore`](Self::restore)回滚。
+    pub fn checkpoint(&self) -> SystemSnapshot {
+        self.create_snapshot()
+    }
+
+    /// 恢复到检查点保存的状态
+    ///
+    /// 快照中的单元格集合必须与当前管理器的单元格完全一致，
+    /// 否则返回[`WfcError::InconsistentState`]且不修改当前状态。
+    /// 恢复后回溯策略的决策栈被清空。
+    pub fn restore(&mut self, snapshot: SystemSnapshot) -> Result<(), WfcError> {
+        let same_cells = snapshot.data.len() == self.wfc_data.len()
+            && snapshot.data.keys().all(|&cell_id| {
+                self.wfc_data.contains_key(&cell_id) && self.grid.contains_cell(cell_id)
+            });
+        if !same_cells {
+            return Err(WfcError::InconsistentState);
+        }
+
+        self.clear_decisions();
+        self.restore_snapshot(&snapshot)
+    }
+
+    /// 获取网格系统引用，对应C++的getGrid()
+    pub fn get_grid(&self) -> &GridSystem {
+        &self.grid
+    }
+
+    /// 消耗管理器，取回网格系统和瓷砖集
+    ///
+    /// 用于以不同配置再次运行而无需重新构建网格。单元格的WFC状态随管理器一起丢弃，
+    /// 新的管理器需要重新调用[`initialize_with`](Self::initialize_with)。
+    pub fn into_parts(self) -> (GridSystem, Box<dyn TileSetVirtual<EdgeData>>) {
+        (self.grid, self.tile_set)
+    }
+
+    /// 获取所有瓷砖ID
+    pub fn get_all_tile_ids(&self) -> Vec<TileId> {
+        self.tile_set.get_all_tile_ids()
+    }
+
+    /// 获取瓷砖
+    pub fn get_tile(&self, tile_id: TileId) -> Option<&Tile<EdgeData>> {
+        self.tile_set.get_tile(tile_id)
+    }
+
+    // ==========================================================================
+    // 核心算法实现 - 对应原C++的private方法
+    // ==========================================================================
+
+    /// 主坍塌算法，对应C++的collapse()
+    ///
+    /// 返回最终生效的坍塌单元格及其瓷砖（回溯后为最近的决策）。
+    fn collapse(&mut self) -> Result<(CellId, TileId), WfcError> {
+        // 1. 找到最小熵单元
+        let min_entropy_cell = self.find_min_entropy_cell()?;
+
+        // 2. 从概率分布中选择瓷砖
+        let chosen_tile = self.choose_tile_from_probabilities(min_entropy_cell)?;
+
+        // 回溯策略下在修改状态之前记录决策点
+        let backtracking = self.config.conflict_strategy == ConflictStrategy::Backtrack;
+        if backtracking {
+            self.push_decision(min_entropy_cell, chosen_tile)?;
+        }
+
+        // 3. 设置瓷砖并更新状态
+        self.set_tile_for_cell(min_entropy_cell, chosen_tile)?;
+
+        // 4. 传播约束效果
+        self.propagate_effects(min_entropy_cell)?;
+
+        // 5. 执行全局数量约束
+        let unmet = self.enforce_global_constraints()?;
+
+        // 6. 回溯策略下立即消除传播产生的冲突
+        if backtracking && (unmet.is_some() || self.has_conflicts()) {
+            // 回溯失败后状态已恢复，需要先记录诊断报告
+            let report = self.conflict_report();
+            if !self.backtrack()? {
+                return Err(match unmet {
+                    Some(constraint) => WfcError::GlobalConstraintUnsatisfiable(constraint),
+                    None => WfcError::UnresolvableConflicts(report),
+                });
+            }
+            if let Some(decision) = self.decision_stack.last() {
+                return Ok((decision.cell, *decision.tried.last().unwrap()));
+            }
+        }
+        if let Some(constraint) = unmet {
+            return Err(WfcError::GlobalConstraintUnsatisfiable(constraint));
+        }
+
+        Ok((min_entropy_cell, chosen_tile))
+    }
+
+    /// 当前运行进度
+    fn progress(&self, collapsed: Option<(CellId, TileId)>) -> WfcProgress {
+        WfcProgress {
+            completed_count: self.completed_count,
+            total_cells: self.grid.get_cells_count(),
+            collapsed,
+        }
+    }
+
+    /// 按[`WfcConfig::observation_heuristic`]寻找下一个坍塌的单元格，
+    /// 对应C++的reCalcMinEntropyCell()
+    fn find_min_entropy_cell(&mut self) -> Result<CellId, WfcError> {
+        match self.config.observation_heuristic {
+            ObservationHeuristic::MinEntropy => self.find_min_entropy_cell_heap(),
+            ObservationHeuristic::Scanline => self
+                .wfc_data
+                .iter()
+                .filter(|(&cell_id, data)| {
+                    data.state == CellState::Uncollapsed && self.in_region(cell_id)
+                })
+                .map(|(&cell_id, _)| cell_id)
+                .min()
+                .ok_or(WfcError::NoUncollapsedCells),
+            ObservationHeuristic::MostConstrained => self
+                .wfc_data
+                .iter()
+                .filter(|(&cell_id, data)| {
+                    data.state == CellState::Uncollapsed && self.in_region(cell_id)
+                })
+                .min_by_key(|(&cell_id, data)| {
+                    let (collapsed, _, _) = self.neighbor_state_counts(cell_id);
+                    (
+                        data.possibility_count(),
+                        Reverse(collapsed),
+                        self.tie_break_key(cell_id),
+                    )
+                })
+                .map(|(&cell_id, _)| cell_id)
+                .ok_or(WfcError::NoUncollapsedCells),
+            ObservationHeuristic::RadialFrom(seed) => self.find_nearest_cell(seed),
+        }
+    }
+
+    /// 径向观察策略：选择距种子单元格最近的未坍塌单元格
+    fn find_nearest_cell(&mut self, seed: CellId) -> Result<CellId, WfcError> {
+        if !matches!(&self.radial_distances, Some((cached, _)) if *cached == seed) {
+            self.radial_distances = Some((seed, self.grid.bfs_distances(seed)));
+        }
+        let distances = &self.radial_distances.as_ref().unwrap().1;
+
+        self.wfc_data
+            .iter()
+            .filter(|(&cell_id, data)| {
+                data.state == CellState::Uncollapsed && self.in_region(cell_id)
+            })
+            .map(|(&cell_id, data)| {
+                let distance = distances.get(&cell_id).copied().unwrap_or(usize::MAX);
+                (distance, data.entropy, self.tie_break_key(cell_id), cell_id)
+            })
+            .min_by(|a, b| {
+                a.0.cmp(&b.0)
+                    .then_with(|| a.1.total_cmp(&b.1))
+                    .then_with(|| a.2.cmp(&b.2))
+            })
+            .map(|(_, _, _, cell_id)| cell_id)
+            .ok_or(WfcError::NoUncollapsedCells)
+    }
+
+    /// 寻找最小熵单元格
+    ///
+    /// 熵值相同时按[`WfcConfig::tie_break`]选择，默认的[`TieBreak::LowestId`]
+    /// 优先选择`CellId`索引最小的单元格，使结果不受`HashMap`遍历顺序影响。
+    ///
+    /// 使用惰性失效的优先队列：熵值变化时压入新条目，取出时跳过状态或熵值
+    /// 已经改变的过期条目，避免每次坍塌都线性扫描全部单元格。
+    fn find_min_entropy_cell_heap(&mut self) -> Result<CellId, WfcError> {
+        if self.entropy_heap_dirty {
+            self.rebuild_entropy_heap();
+        }
+
+        while let Some(Reverse((entropy, _, cell_id))) = self.entropy_heap.peek() {
+            let current = self.in_region(*cell_id)
+                && self.wfc_data.get(cell_id).is_some_and(|data| {
+                    data.state == CellState::Uncollapsed
+                        && data.entropy.to_bits() == entropy.0.to_bits()
+                });
+            if current {
+                return Ok(*cell_id);
+            }
+            self.entropy_heap.pop();
+        }
+
+        Err(WfcError::NoUncollapsedCells)
+    }
+
+    /// 单元格是否位于当前生成区域内，未限定区域时总是返回`true`
+    fn in_region(&self, cell_id: CellId) -> bool {
+        match &self.region {
+            Some(region) => region.contains(&cell_id),
+            None => true,
+        }
+    }
+
+    /// 将单元格的当前熵值压入优先队列，已坍塌或冲突的单元格忽略
+    fn push_entropy_entry(&mut self, cell_id: CellId) {
+        if let Some(data) = self.wfc_data.get(&cell_id) {
+            if data.state == CellState::Uncollapsed {
+                let key = self.tie_break_key(cell_id);
+                let entry = (OrderedEntropy(data.entropy), key, cell_id);
+                self.entropy_heap.push(Reverse(entry));
+            }
+        }
+    }
+
+    /// 根据全部未坍塌单元格重建优先队列
+    fn rebuild_entropy_heap(&mut self) {
+        let entries: Vec<EntropyHeapEntry> = self
+            .wfc_data
+            .iter()
+            .filter(|(_, data)| data.state == CellState::Uncollapsed)
+            .map(|(&cell_id, data)| {
+                let key = self.tie_break_key(cell_id);
+                Reverse((OrderedEntropy(data.entropy), key, cell_id))
+            })
+            .collect();
+        self.entropy_heap = BinaryHeap::from(entries);
+        self.entropy_heap_dirty = false;
+    }
+
+    /// 线性扫描寻找最小熵单元格，作为优先队列的参照实现
+    #[cfg(test)]
+    fn find_min_entropy_cell_linear(&self) -> Result<CellId, WfcError> {
+        self.wfc_data
+            .iter()
+            .filter(|(_, data)| data.state == CellState::Uncollapsed)
+            .min_by(|(&id_a, a), (&id_b, b)| {
+                a.entropy
+                    .partial_cmp(&b.entropy)
+                    .unwrap_or(core::cmp::Ordering::Equal)
+                    .then_with(|| self.tie_break_key(id_a).cmp(&self.tie_break_key(id_b)))
+            })
+            .map(|(&cell_id, _)| cell_id)
+            .ok_or(WfcError::NoUncollapsedCells)
+    }
+
+    /// 计算并列单元格的排序键，键值较小者优先
+    fn tie_break_key(&self, cell_id: CellId) -> u64 {
+        match self.config.tie_break {
+            TieBreak::LowestId => cell_id.index() as u64,
+            TieBreak::Unordered => 0,
+            TieBreak::SpatialHash => match self.grid.get_cell_coords(cell_id) {
+                Some(coords) => spatial_hash(coords, self.seed),
+                None => spatial_hash(&[cell_id.index() as i32], self.seed),
+            },
+        }
+    }
+
+    /// 从概率分布选择瓷砖，对应C++的chooseTileFromProbabilities()
+    ///
+    /// 可能性为空的单元格应处于冲突状态而不会被选中，遇到时返回`InconsistentState`。
+    /// 开启[`WfcConfig::look_ahead`]时只在不会使邻居冲突的瓷砖中选择。
+    fn choose_tile_from_probabilities(&mut self, cell_id: CellId) -> Result<TileId, WfcError> {
+        let cell_data = self
+            .wfc_data
+            .get(&cell_id)
+            .ok_or(WfcError::CellNotFound(cell_id))?;
+
+        let viable = self
+            .config
+            .look_ahead
+            .then(|| self.viable_tiles(cell_id, &cell_data.possibilities))
+            .filter(|tiles| !tiles.is_clear());
+        let possibilities = viable.as_ref().unwrap_or(&cell_data.possibilities);
+
+        let Some(first_tile) = possibilities.minimum() else {
+            return Err(WfcError::InconsistentState);
+        };
+
+        // 设置了权重倍数或软约束的单元格按浮点权重抽样
+        let multipliers = self.weight_multipliers.get(&cell_id);
+        if multipliers.is_some() || self.scoring.is_some() {
+            // 每次坍塌只收集一次邻居可能性，写入复用的缓冲区
+            let mut neighbor_possibilities: Vec<(usize, &[TileId])> = Vec::new();
+            if self.scoring.is_some() {
+                let neighbors = self.grid.get_neighbors(cell_id);
+                self.scoring_neighbors
+                    .resize_with(neighbors.len(), Vec::new);
+                for (buffer, neighbor) in self.scoring_neighbors.iter_mut().zip(&neighbors) {
+                    buffer.clear();
+                    match self.wfc_data.get(neighbor) {
+                        Some(neighbor_data) => buffer.extend(neighbor_data.possibilities.ones()),
+                        None => buffer.extend(self.tile_set.get_all_tile_ids()),
+                    }
+                }
+                neighbor_possibilities.extend(
+                    self.scoring_neighbors
+                        .iter()
+                        .map(Vec::as_slice)
+                        .enumerate()
+                        .filter(|&(index, _)| !self.grid.is_virtual_node(neighbors[index])),
+                );
+            }
+            let weights: Vec<(TileId, f64)> = possibilities
+                .ones()
+                .filter_map(|tile_id| {
+                    let tile = self.tile_set.get_tile(tile_id)?;
+                    let mut weight = tile.weight as f64 * weight_multiplier(multipliers, tile_id);
+                    if let Some(scoring) = &self.scoring {
+                        let score = scoring(tile_id, &neighbor_possibilities);
+                        weight *= if score.is_finite() {
+                            score.max(0.0)
+                        } else {
+                            0.0
+                        };
+                    }
+                    Some((tile_id, weight))
+                })
+                .collect();
+            let total_weight: f64 = weights.iter().map(|&(_, weight)| weight).sum();
+            if total_weight <= 0.0 {
+                return Ok(first_tile);
+            }
+
+            let unit = if self.config.live_random {
+                self.rng.random::<f64>()
+            } else {
+                cell_data.rand_num as f64 / (i32::MAX as f64 + 1.0)
+            };
+            let target = unit * total_weight;
+            let mut weight_sum = 0.0;
+            for &(tile_id, weight) in &weights {
+                weight_sum += weight;
+                if weight_sum > target {
+                    return Ok(tile_id);
+                }
+            }
+            return Ok(weights.last().map_or(first_tile, |&(tile_id, _)| tile_id));
+        }
+
+        // 计算总权重，对应C++的weightSum计算
+        let mut total_weight = 0i32;
+        for tile_id in possibilities.ones() {
+            if let Some(tile) = self.tile_set.get_tile(tile_id) {
+                total_weight += tile.weight;
+            }
+        }
+
+        if total_weight == 0 {
+            return Ok(first_tile); // 如果没有权重，返回第一个
+        }
+
+        // 默认使用预计算的随机数，完全对应C++的逻辑
+        // C++: randNum %= weightSum;
+        let rand_num = if self.config.live_random {
+            self.rng.random_range(0..total_weight)
+        } else {
+            cell_data.rand_num % total_weight
+        };
+        
+        // C++: 累计权重直到 weightSum >= randNum
+        let mut weight_sum = 0i32;
+        for tile_id in possibilities.ones() {
+            if let Some(tile) = self.tile_set.get_tile(tile_id) {
+                weight_sum += tile.weight;
+                if weight_sum > rand_num {  // C++: weightSum >= randNum，但我们用>避免边界问题
+                    return Ok(tile_id);
+                }
+            }
+        }
+
+        // 保险措施，理论上不应该到达这里
+        Ok(possibilities.maximum().unwrap_or(first_tile))
+    }
+
+    /// 设置单元格瓷砖，对应C++的setTileForCell()
+    fn set_tile_for_cell(&mut self, cell_id: CellId, tile_id: TileId) -> Result<(), WfcError> {
+        let (weight_sum, weight_log_weight_sum) =
+            self.weight_sums(cell_id, core::iter::once(tile_id));
+        let cell_data = self
+            .wfc_data
+            .get_mut(&cell_id)
+            .ok_or(WfcError::CellNotFound(cell_id))?;
+
+        // 设置选定的瓷砖为唯一的可能性
+        cell_data.possibilities.clear();
+        cell_data.possibilities.grow_and_insert(tile_id);
+        cell_data.weight_sum = weight_sum;
+        cell_data.weight_log_weight_sum = weight_log_weight_sum;
+        cell_data.entropy = 0.0;
+        cell_data.state = CellState::Collapsed;
+
+        self.completed_count += 1;
+        self.collapse_events
+            .push((self.collapse_step, cell_id, tile_id));
+        self.collapse_step += 1;
+
+        Ok(())
+    }
+
+    /// 约束传播算法，对应C++的propagateEffects()
+    ///
+    /// 利用无向连接（边对）进行双向约束传播，确保所有邻居的约束一致性
+    fn propagate_effects(&mut self, start_cell: CellId) -> Result<(), WfcError> {
+        self.propagate_from(&[start_cell])
+    }
+
+    /// 从多个起始单元格同时进行一轮约束传播
+    fn propagate_from(&mut self, start_cells: &[CellId]) -> Result<(), WfcError> {
+        if self.is_complete() {
+            return Ok(());
+        }
+
+        let mut propagation_queue: VecDeque<CellId> = start_cells.iter().copied().collect();
+        let mut processed_cells: HashSet<CellId> = start_cells.iter().copied().collect();
+
+        while let Some(current_cell) = propagation_queue.pop_front() {
+            // 获取所有邻居
+            let neighbors = self.grid.get_neighbors(current_cell);
+
+            for neighbor in neighbors {
+                if processed_cells.contains(&neighbor) {
+                    continue;
+                }
+
+                let neighbor_data = self
+                    .wfc_data
+                    .get(&neighbor)
+                    .ok_or(WfcError::CellNotFound(neighbor))?;
+                if neighbor_data.state != CellState::Uncollapsed {
+                    continue;
+                }
+
+                // 更新邻居可能性
+                let constraint_updated = self.update_neighbor_possibilities(neighbor)?;
+
+                if constraint_updated {
+                    propagation_queue.push_back(neighbor);
+                    processed_cells.insert(neighbor);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 从多个起始单元格并行传播约束
+    ///
+    /// 起始单元格按相邻关系分成若干分区，各分区在只读视图上并行传播。
+    /// 只有各分区读写的单元格互不重叠时才合并结果，此时与顺序传播完全相同；
+    /// 否则丢弃并行结果，退回[`propagate_from`](Self::propagate_from)。
+    #[cfg(feature = "parallel")]
+    fn propagate_from_parallel(&mut self, start_cells: &[CellId]) -> Result<(), WfcError> {
+        if !self.try_propagate_partitions(start_cells)? {
+            self.propagate_from(start_cells)?;
+        }
+        Ok(())
+    }
+
+    /// 尝试并行传播各分区并合并结果，返回是否已合并
+    #[cfg(feature = "parallel")]
+    fn try_propagate_partitions(&mut self, start_cells: &[CellId]) -> Result<bool, WfcError> {
+        // 熵值噪声按更新顺序消耗随机数，并行时无法保持相同的顺序
+        if self.is_complete() || self.config.entropy_noise > 0.0 {
+            return Ok(false);
+        }
+
+        let partitions = self.partition_start_cells(start_cells);
+        if partitions.len() < 2 {
+            return Ok(false);
+        }
+
+        let results: Vec<PartitionPropagation> = partitions
+            .par_iter()
+            .map(|cells| self.propagate_partition(cells))
+            .collect::<Result<_, _>>()?;
+
+        // 分区之间读写重叠时，传播顺序会影响结果
+        for (index, first) in results.iter().enumerate() {
+            for second in &results[index + 1..] {
+                if !first.writes.is_disjoint(&second.writes)
+                    || !first.writes.is_disjoint(&second.reads)
+                    || !second.writes.is_disjoint(&first.reads)
+                {
+                    return Ok(false);
+                }
+            }
+        }
+
+        for result in results {
+            self.propagation_steps += result.steps;
+            for (cell, possibilities) in result.updates {
+                let entropy = self.narrow_possibilities(cell, possibilities);
+                let cell_data = self.wfc_data.get_mut(&cell).unwrap();
+                cell_data.entropy = entropy;
+                if cell_data.possibilities.is_clear() {
+                    cell_data.state = CellState::Conflict;
+                }
+                self.push_entropy_entry(cell);
+            }
+        }
+        Ok(true)
+    }
+
+    /// 按相邻关系把起始单元格分组，保持各组首次出现的顺序
+    #[cfg(feature = "parallel")]
+    fn partition_start_cells(&self, start_cells: &[CellId]) -> Vec<Vec<CellId>> {
+        let index: HashMap<CellId, usize> = start_cells
+            .iter()
+            .enumerate()
+            .map(|(position, &cell)| (cell, position))
+            .collect();
+        let mut union = UnionFind::new(start_cells.len());
+        for (position, &cell) in start_cells.iter().enumerate() {
+            for neighbor in self.grid.get_neighbors(cell) {
+                if let Some(&other) = index.get(&neighbor) {
+                    union.union(position, other);
+                }
+            }
+        }
+
+        let mut groups: Vec<Vec<CellId>> = Vec::new();
+        let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+        for (position, &cell) in start_cells.iter().enumerate() {
+            let group = *group_of_root
+                .entry(union.find(position))
+                .or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+            groups[group].push(cell);
+        }
+        groups
+    }
+
+    /// 在只读视图上按[`propagate_from`](Self::propagate_from)的顺序传播一个分区
+    ///
+    /// 新的可能性记录在局部覆盖层中，同时记录读写过的单元格供合并前检查。
+    #[cfg(feature = "parallel")]
+    fn propagate_partition(
+        &self,
+        start_cells: &[CellId],
+    ) -> Result<PartitionPropagation, WfcError> {
+        let mut result = PartitionPropagation {
+            writes: start_cells.iter().copied().collect(),
+            ..PartitionPropagation::default()
+        };
+        let mut overlay: HashMap<CellId, FixedBitSet> = HashMap::new();
+        let mut propagation_queue: VecDeque<CellId> = start_cells.iter().copied().collect();
+        let mut processed_cells: HashSet<CellId> = start_cells.iter().copied().collect();
+
+        while let Some(current_cell) = propagation_queue.pop_front() {
+            for neighbor in self.grid.get_neighbors(current_cell) {
+                if processed_cells.contains(&neighbor) {
+                    continue;
+                }
+
+                // 覆盖层中的单元格都已处理，这里读到的总是原始状态
+                result.reads.insert(neighbor);
+                let neighbor_data = self
+                    .wfc_data
+                    .get(&neighbor)
+                    .ok_or(WfcError::CellNotFound(neighbor))?;
+                if neighbor_data.state != CellState::Uncollapsed {
+                    continue;
+                }
+                result.steps += 1;
+
+                let neighbor_possibilities: Vec<Vec<TileId>> = self
+                    .grid
+                    .get_neighbors(neighbor)
+                    .into_iter()
+                    .map(|cell| {
+                        result.reads.insert(cell);
+                        match (overlay.get(&cell), self.wfc_data.get(&cell)) {
+                            (Some(possibilities), _) => possibilities.ones().collect(),
+                            (None, Some(cell_data)) => cell_data.possibilities(),
+                            (None, None) => self.tile_set.get_all_tile_ids(),
+                        }
+                    })
+                    .collect();
+
+                let edge_labels = self.neighbor_edge_labels(neighbor);
+                let mut compatible_tiles = neighbor_data.possibilities.clone();
+                for tile_id in neighbor_data.possibilities.ones() {
+                    if !self.judge(&edge_labels, &neighbor_possibilities, tile_id) {
+                        compatible_tiles.remove(tile_id);
+                    }
+                }
+
+                if compatible_tiles.count_ones(..) != neighbor_data.possibility_count() {
+                    overlay.insert(neighbor, compatible_tiles.clone());
+                    result.updates.push((neighbor, compatible_tiles));
+                    result.writes.insert(neighbor);
+                    propagation_queue.push_back(neighbor);
+                    processed_cells.insert(neighbor);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// 更新邻居可能性，基于约束传播
+    fn update_neighbor_possibilities(&mut self, neighbor: CellId) -> Result<bool, WfcError> {
+        // 区域生成时，区域外的单元格作为固定约束不被修改
+        if !self.in_region(neighbor) {
+            return Ok(false);
+        }
+        self.propagation_steps += 1;
+
+        // 先获取邻居数据的克隆，避免可变借用冲突
+        let neighbor_data = self
+            .wfc_data
+            .get(&neighbor)
+            .ok_or(WfcError::CellNotFound(neighbor))?
+            .clone();
+
+        if neighbor_data.state != CellState::Uncollapsed {
+            return Ok(false); // 已坍塌或冲突的单元格不需要更新
+        }
+
+        // 过滤兼容的瓷砖，按边标签判断的瓷砖集无法使用按位置建立的支持计数
+        let compatible_tiles = match self.config.propagation {
+            PropagationMode::ArcConsistency if !self.tile_set.uses_edge_labels() => {
+                self.filter_supported_tiles(neighbor)?
+            }
+            _ => self.filter_compatible_tiles(neighbor)?,
+        };
+
+        // 检查是否产生了约束变化
+        let old_count = neighbor_data.possibility_count();
+        let new_count = compatible_tiles.count_ones(..);
+
+        if new_count != old_count {
+            // 更新邻居数据，熵值只按被移除的瓷砖增量更新
+            let new_entropy =
+                self.narrow_possibilities(neighbor, compatible_tiles) + self.entropy_noise();
+            let neighbor_data_mut = self.wfc_data.get_mut(&neighbor).unwrap();
+            neighbor_data_mut.entropy = new_entropy;
+
+            // 检查冲突状态
+            if neighbor_data_mut.possibilities.is_clear() {
+                neighbor_data_mut.state = CellState::Conflict;
+            }
+            self.push_entropy_entry(neighbor);
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// 过滤兼容的瓷砖
+    ///
+    /// 邻居可能性只收集一次，所有候选瓷砖共用；结果是当前可能性位集的子集。
+    fn filter_compatible_tiles(&self, cell_id: CellId) -> Result<FixedBitSet, WfcError> {
+        let cell_data = self
+            .wfc_data
+            .get(&cell_id)
+            .ok_or(WfcError::CellNotFound(cell_id))?;
+        let neighbor_possibilities = self.collect_neighbor_possibilities(cell_id);
+        let edge_labels = self.neighbor_edge_labels(cell_id);
+        let mut compatible_tiles = cell_data.possibilities.clone();
+
+        for tile_id in cell_data.possibilities.ones() {
+            if !self.judge(&edge_labels, &neighbor_possibilities, tile_id) {
+                compatible_tiles.remove(tile_id);
+            }
+        }
+
+        Ok(compatible_tiles)
+    }
+
+    /// 按支持计数过滤兼容的瓷砖
+    ///
+    /// 只有在某个方向上支持计数为零的瓷砖才调用`judge_possibility`确认，
+    /// 其余瓷砖在每个方向都有兼容的邻居瓷砖，必然保留。
+    fn filter_supported_tiles(&mut self, cell_id: CellId) -> Result<FixedBitSet, WfcError> {
+        if self.supports.is_none() {
+            self.supports = Some(self.build_support_counts());
+        }
+        for neighbor in self.grid.get_neighbors(cell_id) {
+            self.sync_support_counts(neighbor);
+        }
+
+        let cell_data = self
+            .wfc_data
+            .get(&cell_id)
+            .ok_or(WfcError::CellNotFound(cell_id))?;
+        let counts = &self.supports.as_ref().unwrap().counts[&cell_id];
+        let unsupported: Vec<TileId> = cell_data
+            .possibilities
+            .ones()
+            .filter(|&tile_id| counts.iter().any(|direction| direction[tile_id] == 0))
+            .collect();
+
+        let mut compatible_tiles = cell_data.possibilities.clone();
+        if !unsupported.is_empty() {
+            let neighbor_possibilities = self.collect_neighbor_possibilities(cell_id);
+            let edge_labels = self.neighbor_edge_labels(cell_id);
+            for tile_id in unsupported {
+                if !self.judge(&edge_labels, &neighbor_possibilities, tile_id) {
+                    compatible_tiles.remove(tile_id);
+                }
+            }
+        }
+
+        Ok(compatible_tiles)
+    }
+
+    /// 根据当前可能性建立全部单元格的支持计数
+    fn build_support_counts(&self) -> SupportCounts {
+        let all_tiles = self.tile_set.get_all_tile_ids();
+        let capacity = all_tiles.iter().max().map_or(0, |&max| max + 1);
+        let all_set: FixedBitSet = {
+            let mut set = FixedBitSet::with_capacity(capacity);
+            set.extend(all_tiles.iter().copied());
+            set
+        };
+
+        let mut supports = SupportCounts::default();
+        for cell in self.grid.get_all_cells() {
+            let possibilities = match self.wfc_data.get(&cell) {
+                Some(data) => data.possibilities.clone(),
+                None => all_set.clone(),
+            };
+            supports.seen.insert(cell, possibilities);
+        }
+
+        for cell in self.grid.get_all_cells() {
+            let neighbors = self.grid.get_neighbors(cell);
+            let degree = neighbors.len();
+            let mut counts = Vec::with_capacity(degree);
+            for (direction, &neighbor) in neighbors.iter().enumerate() {
+                let table = supports
+                    .tables
+                    .entry((degree, direction))
+                    .or_insert_with(|| {
+                        self.compatibility_table(&all_tiles, capacity, degree, direction)
+                    });
+                let mut direction_counts = vec![0u32; capacity];
+                let seen = supports.seen.get(&neighbor).unwrap_or(&all_set);
+                for neighbor_tile in seen.ones() {
+                    for tile_id in table[neighbor_tile].ones() {
+                        direction_counts[tile_id] += 1;
+                    }
+                }
+                counts.push(direction_counts);
+                supports
+                    .dependents
+                    .entry(neighbor)
+                    .or_default()
+                    .push((cell, direction));
+            }
+            supports.counts.insert(cell, counts);
+        }
+
+        supports
+    }
+
+    /// 用只约束一个方向的探测调用计算两两兼容表
+    ///
+    /// 其他方向填入全部瓷砖，`table[b]`为该方向邻居是`b`时仍被接受的瓷砖集合。
+    fn compatibility_table(
+        &self,
+        all_tiles: &[TileId],
+        capacity: usize,
+        degree: usize,
+        direction: usize,
+    ) -> Vec<FixedBitSet> {
+        let mut probe = vec![all_tiles.to_vec(); degree];
+        let mut table = vec![FixedBitSet::with_capacity(capacity); capacity];
+        for &neighbor_tile in all_tiles {
+            probe[direction] = vec![neighbor_tile];
+            for &tile_id in all_tiles {
+                if self.tile_set.judge_possibility(&probe, tile_id) {
+                    table[neighbor_tile].insert(tile_id);
+                }
+            }
+        }
+        table
+    }
+
+    /// 把单元格可能性自上次同步以来的变化应用到依赖它的支持计数上
+    fn sync_support_counts(&mut self, cell_id: CellId) {
+        let (Some(supports), Some(data)) = (self.supports.as_mut(), self.wfc_data.get(&cell_id))
+        else {
+            return;
+        };
+        let Some(seen) = supports.seen.get_mut(&cell_id) else {
+            return;
+        };
+        if *seen == data.possibilities {
+            return;
+        }
+
+        let removed: Vec<TileId> = seen.difference(&data.possibilities).collect();
+        let added: Vec<TileId> = data.possibilities.difference(seen).collect();
+        seen.clone_from(&data.possibilities);
+
+        for &(dependent, direction) in supports.dependents.get(&cell_id).into_iter().flatten() {
+            let counts = supports.counts.get_mut(&dependent).unwrap();
+            let table = &supports.tables[&(counts.len(), direction)];
+            let direction_counts = &mut counts[direction];
+            for &neighbor_tile in &removed {
+                for tile_id in table[neighbor_tile].ones() {
+                    direction_counts[tile_id] -= 1;
+                }
+            }
+            for &neighbor_tile in &added {
+                for tile_id in table[neighbor_tile].ones() {
+                    direction_counts[tile_id] += 1;
+                }
+            }
+        }
+    }
+
+    /// 检查瓷砖兼容性，对应C++的tileIsCompatible()
+    fn tile_is_compatible(&self, tile_id: TileId, cell_id: CellId) -> Result<bool, WfcError> {
+        let neighbor_possibilities = self.collect_neighbor_possibilities(cell_id);
+        let edge_labels = self.neighbor_edge_labels(cell_id);
+
+        Ok(self.judge(&edge_labels, &neighbor_possibilities, tile_id))
+    }
+
+    /// 按邻居顺序收集边标签，瓷砖集不使用边标签时返回空列表
+    fn neighbor_edge_labels(&self, cell_id: CellId) -> Vec<Option<&str>> {
+        if self.tile_set.uses_edge_labels() {
+            self.grid.get_neighbor_edge_types(cell_id)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// 判断瓷砖在邻居约束下是否可行，按瓷砖集的需要选择是否传入边标签
+    fn judge(
+        &self,
+        edge_labels: &[Option<&str>],
+        neighbor_possibilities: &[Vec<TileId>],
+        tile_id: TileId,
+    ) -> bool {
+        if self.tile_set.uses_edge_labels() {
+            self.tile_set
+                .judge_possibility_labeled(edge_labels, neighbor_possibilities, tile_id)
+        } else {
+            self.tile_set
+                .judge_possibility(neighbor_possibilities, tile_id)
+        }
+    }
+
+    /// 筛选放置后不会使任何未坍塌邻居失去全部可能性的瓷砖，用于前瞻坍塌
+    fn viable_tiles(&self, cell_id: CellId, possibilities: &FixedBitSet) -> FixedBitSet {
+        let mut viable = possibilities.clone();
+        for tile_id in possibilities.ones() {
+            if !self.placement_keeps_neighbors_viable(cell_id, tile_id) {
+                viable.remove(tile_id);
+            }
+        }
+        viable
+    }
+
+    /// 假设单元格坍塌为`tile_id`，检查每个会被传播更新的邻居是否仍有可行的瓷砖
+    fn placement_keeps_neighbors_viable(&self, cell_id: CellId, tile_id: TileId) -> bool {
+        for neighbor in self.grid.get_neighbors(cell_id) {
+            let Some(neighbor_data) = self.wfc_data.get(&neighbor) else {
+                continue;
+            };
+            if neighbor_data.state != CellState::Uncollapsed || !self.in_region(neighbor) {
+                continue;
+            }
+
+            let mut neighbor_possibilities = self.collect_neighbor_possibilities(neighbor);
+            for (slot, other) in self.grid.get_neighbors(neighbor).into_iter().enumerate() {
+                if other == cell_id {
+                    neighbor_possibilities[slot] = vec![tile_id];
+                }
+            }
+            let edge_labels = self.neighbor_edge_labels(neighbor);
+            let satisfiable = neighbor_data
+                .possibilities
+                .ones()
+                .any(|candidate| self.judge(&edge_labels, &neighbor_possibilities, candidate));
+            if !satisfiable {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 按邻居顺序收集可能性列表，未初始化的邻居视为允许全部瓷砖
+    fn collect_neighbor_possibilities(&self, cell_id: CellId) -> Vec<Vec<TileId>> {
+        self.grid
+            .get_neighbors(cell_id)
+            .into_iter()
+            .map(|neighbor| match self.wfc_data.get(&neighbor) {
+                Some(neighbor_data) => neighbor_data.possibilities(),
+                None => self.tile_set.get_all_tile_ids(),
+            })
+            .collect()
+    }
+
+    /// 检查已坍塌单元格的瓷砖是否与真实邻居的可能性兼容
+    fn is_consistent_with_real_neighbors(&self, cell_id: CellId, data: &CellWfcData) -> bool {
+        let neighbor_possibilities: Vec<Vec<TileId>> = self
+            .grid
+            .get_neighbors(cell_id)
+            .into_iter()
+            .map(|neighbor| match self.wfc_data.get(&neighbor) {
+                Some(neighbor_data) if !self.grid.is_virtual_node(neighbor) => {
+                    neighbor_data.possibilities()
+                }
+                _ => vec![],
+            })
+            .collect();
+
+        let edge_labels = self.neighbor_edge_labels(cell_id);
+        data.possibilities
+            .minimum()
+            .is_some_and(|tile_id| self.judge(&edge_labels, &neighbor_possibilities, tile_id))
+    }
+
+    /// 计算香农熵，对应C++的calculateEntropy()
+    ///
+    /// 这是从头计算的路径，用于初始化等需要重置权重和的场景；传播中的熵值更新
+    /// 见[`narrow_possibilities`](Self::narrow_possibilities)。
+    /// 结果按可能性位集缓存，相同的可能性集合总是得到完全相同的熵值。
+    /// 缓存条目超过[`ENTROPY_CACHE_CAPACITY`]时整体清空，避免无限增长；
+    /// 瓷砖集的权重版本号变化时同样清空，不会读到按旧权重计算的熵值。
+    fn calculate_entropy(&mut self, possibilities: &FixedBitSet) -> f64 {
+        if possibilities.count_ones(..) <= 1 {
+            return 0.0;
+        }
+
+        let generation = self.tile_set.weight_generation();
+        if generation != self.entropy_cache_generation {
+            self.entropy_cache.clear();
+            self.entropy_cache_generation = generation;
+        }
+
+        if let Some(&entropy) = self.entropy_cache.get(possibilities) {
+            self.entropy_cache_hits += 1;
+            return entropy;
+        }
+
+        let entropy = self.compute_entropy(possibilities);
+        if self.entropy_cache.len() >= ENTROPY_CACHE_CAPACITY {
+            self.entropy_cache.clear();
+        }
+        self.entropy_cache.insert(possibilities.clone(), entropy);
+        entropy
+    }
+
+    /// 瓷砖权重版本号变化时丢弃熵值缓存，并按新权重重新计算所有单元格的权重和
+    fn sync_weight_generation(&mut self) -> Result<(), WfcError> {
+        let generation = self.tile_set.weight_generation();
+        if generation == self.entropy_cache_generation {
+            return Ok(());
+        }
+        self.entropy_cache.clear();
+        self.entropy_cache_generation = generation;
+        self.update_all_entropies()
+    }
+
+    /// 计算单元格的熵值，设置了权重倍数的单元格不经缓存单独计算
+    fn cell_entropy(&mut self, cell_id: CellId, possibilities: &FixedBitSet) -> f64 {
+        match self.weight_multipliers.get(&cell_id) {
+            Some(multipliers) => self.weighted_entropy(possibilities, Some(multipliers)),
+            None => self.calculate_entropy(possibilities),
+        }
+    }
+
+    /// 计算给定瓷砖的有效权重和与`weight * log2(weight)`之和
+    fn weight_sums(&self, cell_id: CellId, tiles: impl Iterator<Item = TileId>) -> (f64, f64) {
+        let multipliers = self.weight_multipliers.get(&cell_id);
+        tiles
+            .filter_map(|tile_id| {
+                let tile = self.tile_set.get_tile(tile_id)?;
+                Some(tile.weight as f64 * weight_multiplier(multipliers, tile_id))
+            })
+            .filter(|&weight| weight > 0.0)
+            .fold((0.0, 0.0), |(sum, log_sum), weight| {
+                (sum + weight, log_sum + weight * log2(weight))
+            })
+    }
+
+    /// 把单元格的可能性缩小为`possibilities`，返回增量更新后的熵值（不含噪声）
+    ///
+    /// `possibilities`必须是当前可能性的子集。只需从权重和中减去被移除瓷砖的贡献，
+    /// 代价与移除的瓷砖数成正比，不必遍历剩余瓷砖。
+    fn narrow_possibilities(&mut self, cell_id: CellId, possibilities: FixedBitSet) -> f64 {
+        let (removed_sum, removed_log_sum) = self.weight_sums(
+            cell_id,
+            self.wfc_data[&cell_id]
+                .possibilities
+                .difference(&possibilities),
+        );
+        let cell_data = self.wfc_data.get_mut(&cell_id).unwrap();
+        cell_data.weight_sum -= removed_sum;
+        cell_data.weight_log_weight_sum -= removed_log_sum;
+        cell_data.possibilities = possibilities;
+        cell_data.incremental_entropy()
+    }
+
+    /// 把单元格的可能性替换为任意集合，从头计算权重和，返回熵值（不含噪声）
+    ///
+    /// 用于初始化、热启动等可能性可能增加的场景，熵值经缓存精确计算。
+    fn reset_possibilities(&mut self, cell_id: CellId, possibilities: FixedBitSet) -> f64 {
+        let (weight_sum, weight_log_weight_sum) = self.weight_sums(cell_id, possibilities.ones());
+        let entropy = self.cell_entropy(cell_id, &possibilities);
+        let cell_data = self.wfc_data.get_mut(&cell_id).unwrap();
+        cell_data.weight_sum = weight_sum;
+        cell_data.weight_log_weight_sum = weight_log_weight_sum;
+        cell_data.possibilities = possibilities;
+        entropy
+    }
+
+    /// 生成熵值噪声，未启用时不消耗随机数
+    fn entropy_noise(&mut self) -> f64 {
+        if self.config.entropy_noise > 0.0 {
+            self.rng.random::<f64>() * self.config.entropy_noise
+        } else {
+            0.0
+        }
+    }
+
+    /// 不经缓存直接计算香农熵
+    fn compute_entropy(&self, possibilities: &FixedBitSet) -> f64 {
+        self.weighted_entropy(possibilities, None)
+    }
+
+    /// 按瓷砖权重（乘以可选的单元格权重倍数）计算香农熵
+    fn weighted_entropy(
+        &self,
+        possibilities: &FixedBitSet,
+        multipliers: Option<&HashMap<TileId, f64>>,
+    ) -> f64 {
+        let count = possibilities.count_ones(..);
+        if count <= 1 {
+            return 0.0;
+        }
+
+        let weights: Vec<f64> = possibilities
+            .ones()
+            .filter_map(|tile_id| {
+                let tile = self.tile_set.get_tile(tile_id)?;
+                Some(tile.weight as f64 * weight_multiplier(multipliers, tile_id))
+            })
+            .collect();
+
+        // 计算总权重
+        let total_weight: f64 = weights.iter().sum();
+
+        if total_weight == 0.0 {
+            return log2(count as f64);
+        }
+
+        // 计算香农熵
+        weights
+            .iter()
+            .map(|&weight| weight / total_weight)
+            .filter(|&prob| prob > 0.0)
+            .map(|prob| -prob * log2(prob))
+            .sum()
+    }
+
+    /// 更新所有单元格的熵值
+    fn update_all_entropies(&mut self) -> Result<(), WfcError> {
+        // 按CellId排序，保证熵值噪声的分配不受HashMap遍历顺序影响
+        let mut cell_ids: Vec<CellId> = self.wfc_data.keys().copied().collect();
+        cell_ids.sort_unstable();
+
+        for cell_id in cell_ids {
+            let possibilities = self.wfc_data[&cell_id].possibilities.clone();
+            let entropy = self.reset_possibilities(cell_id, possibilities) + self.entropy_noise();
+            self.wfc_data.get_mut(&cell_id).unwrap().entropy = entropy;
+        }
+        self.entropy_heap_dirty = true;
+
+        Ok(())
+    }
+
+    // ==========================================================================
+    // 分层冲突修复系统 - 对应原C++的冲突处理方法
+    // ==========================================================================
+
+    /// 解决所有冲突，使用统一的分层修复方法，对应C++的resolveConflicts()
+    pub fn resolve_conflicts(&mut self) -> Result<bool, WfcError> {
+        let conflict_cells = self.collect_conflict_cells();
+
+        if conflict_cells.is_empty() {
+            return Ok(true);
+        }
+
+        // 使用分层回溯解决所有冲突
+        if !self.layered_backtrack_resolution(conflict_cells)? {
+            return Ok(false);
+        }
+
+        // 修复中坍塌的单元格可能使瓷砖达到上限
+        match self.enforce_global_constraints()? {
+            Some(constraint) => Err(WfcError::GlobalConstraintUnsatisfiable(constraint)),
+            None => Ok(true),
+        }
+    }
+
+    /// 修复冲突并记录连续失败次数
+    ///
+    /// 失败次数超过[`WfcConfig::max_conflict_retries`]时返回
+    /// [`WfcError::UnresolvableConflicts`]，其中的报告描述最后一次修复前的冲突。
+    fn resolve_conflicts_with_retries(&mut self) -> Result<bool, WfcError> {
+        // 修复过程会改写冲突区域，需要先记录诊断报告
+        let report = self.conflict_report();
+        if self.resolve_conflicts()? {
+            self.failed_resolutions = 0;
+            return Ok(true);
+        }
+
+        self.failed_resolutions += 1;
+        if self.failed_resolutions > self.config.max_conflict_retries {
+            self.failed_resolutions = 0;
+            return Err(WfcError::UnresolvableConflicts(report));
+        }
+        Ok(false)
+    }
+
+    /// 收集所有冲突单元格，按`CellId`索引升序排列
+    ///
+    /// 结果作为分层修复的初始层，排序保证修复顺序不受`HashMap`遍历顺序影响。
+    fn collect_conflict_cells(&self) -> Vec<CellId> {
+        let mut cells: Vec<CellId> = self
+            .wfc_data
+            .iter()
+            .filter(|(_, data)| data.state == CellState::Conflict)
+            .map(|(&cell_id, _)| cell_id)
+            .collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    /// 分层回溯解决，对应C++的resolveConflictsCell()
+    ///
+    /// 这是WFC系统的核心冲突修复机制，通过分层回溯来解决冲突。
+    /// 不同于传统WFC的过程性回溯，这里的回溯是专门为解决冲突层而设计的。
+    fn layered_backtrack_resolution(
+        &mut self,
+        conflict_cells: Vec<CellId>,
+    ) -> Result<bool, WfcError> {
+        let mut layers = vec![conflict_cells];
+        self.resolve_conflicts_recursive(&mut layers, 0)
+    }
+
+    /// 递归冲突解决 - 分层修复的核心算法
+    ///
+    /// 这个方法实现了分层冲突修复的递归逻辑，通过逐层扩展和回溯来解决冲突。
+    /// 注意：这里的"回溯"是为冲突修复设计的，不同于传统WFC算法的过程性回溯。
+    fn resolve_conflicts_recursive(
+        &mut self,
+        layers: &mut Vec<Vec<CellId>>,
+        depth: usize,
+    ) -> Result<bool, WfcError> {
+        if depth >= self.config.max_recursion_depth {
+            return Ok(false);
+        }
+
+        // 从外层到内层解决冲突
+        for layer_idx in (0..layers.len()).rev() {
+            for cell_idx in 0..layers[layer_idx].len() {
+                let cell = layers[layer_idx][cell_idx];
+                self.recover_cell_possibilities(cell, layers)?;
+            }
+        }
+
+        // 尝试获取解决方案
+        let all_cells: Vec<CellId> = layers.iter().flatten().copied().collect();
+        if self.backtrack_solution(&all_cells, 0)? {
+            return Ok(true);
+        }
+
+        // 如果失败，扩展到下一层
+        if depth < self.config.max_recursion_depth - 1 {
+            let next_layer = self.build_next_layer(&layers[depth])?;
+            if !next_layer.is_empty() {
+                layers.push(next_layer);
+                return self.resolve_conflicts_recursive(layers, depth + 1);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// 恢复单元格可能性，用于分层修复
+    fn recover_cell_possibilities(
+        &mut self,
+        cell_id: CellId,
+        layers: &[Vec<CellId>],
+    ) -> Result<(), WfcError> {
+        let (cx, _) = find_in_2d_vector(layers, &cell_id).unwrap_or((layers.len(), 0));
+        let neighbors = self.grid.get_neighbors(cell_id);
+        let mut neighbor_possibilities = Vec::new();
+
+        for neighbor in neighbors {
+            let (nx, _) = find_in_2d_vector(layers, &neighbor).unwrap_or((layers.len(), 0));
+            if nx >= cx {
+                if let Some(neighbor_data) = self.wfc_data.get(&neighbor) {
+                    neighbor_possibilities.push(neighbor_data.possibilities());
+                } else {
+                    neighbor_possibilities.push(vec![]);
+                }
+            } else {
+                neighbor_possibilities.push(vec![]);
+            }
+        }
+
+        // 根据邻居约束恢复可能性
+        let cell_data = self
+            .wfc_data
+            .get(&cell_id)
+            .ok_or(WfcError::CellNotFound(cell_id))?;
+        let edge_labels = self.neighbor_edge_labels(cell_id);
+        let compatible_tiles = self
+            .tile_set
+            .get_all_tile_ids()
+            .into_iter()
+            .filter(|&tile_id| !self.exceeds_global_max(cell_id, tile_id))
+            .filter(|&tile_id| self.judge(&edge_labels, &neighbor_possibilities, tile_id));
+        let new_possibilities = cell_data.possibility_set(compatible_tiles);
+
+        // 确定新状态
+        let new_state = if new_possibilities.is_clear() {
+            CellState::Conflict
+        } else {
+            CellState::Uncollapsed
+        };
+
+        // 恢复的可能性可能多于当前集合，熵值从头计算
+        let new_entropy = self.reset_possibilities(cell_id, new_possibilities);
+        let cell_data = self
+            .wfc_data
+            .get_mut(&cell_id)
+            .ok_or(WfcError::CellNotFound(cell_id))?;
+        cell_data.entropy = new_entropy;
+        cell_data.state = new_state;
+        self.push_entropy_entry(cell_id);
+
+        Ok(())
+    }
+
+    /// 构建下一层
+    fn build_next_layer(&self, current_layer: &[CellId]) -> Result<Vec<CellId>, WfcError> {
+        let mut next_layer = Vec::new();
+
+        for &cell in current_layer {
+            let neighbors = self.grid.get_neighbors(cell);
+            for neighbor in neighbors {
+                if let Some(neighbor_data) = self.wfc_data.get(&neighbor) {
+                    if neighbor_data.state == CellState::Collapsed
+                        && !next_layer.contains(&neighbor)
+                    {
+                        next_layer.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        Ok(next_layer)
+    }
+
+    /// 回溯求解算法，对应C++的retrospectiveGetSolution()
+    ///
+    /// 这是分层修复过程中使用的局部回溯算法，用于在冲突修复时寻找可行的瓷砖组合。
+    /// 注意：这不是传统WFC的全局回溯，而是针对特定冲突层的局部求解。
+    fn backtrack_solution(&mut self, cells: &[CellId], index: usize) -> Result<bool, WfcError> {
+        if index >= cells.len() {
+            return Ok(true);
+        }
+
+        let cell_id = cells[index];
+        let cell_data = self
+            .wfc_data
+            .get(&cell_id)
+            .ok_or(WfcError::CellNotFound(cell_id))?;
+
+        let Some(first_possibility) = cell_data
+            .possibilities
+            .ones()
+            .find(|&tile_id| !self.exceeds_global_max(cell_id, tile_id))
+        else {
+            return Ok(false);
+        };
+
+        // 最后一个单元直接选择第一种可能性
+        if index == cells.len() - 1 {
+            self.set_tile_for_cell(cell_id, first_possibility)?;
+            return Ok(true);
+        }
+
+        let possibilities = cell_data.possibilities();
+
+        // 保存当前状态，快照只在本层尝试期间存活
+        let snapshot = self.create_snapshot();
+        let snapshot_bytes = snapshot.estimated_bytes();
+        self.reserve_snapshot_memory(snapshot_bytes)?;
+
+        let result = self.try_possibilities(cells, index, &possibilities, &snapshot);
+
+        drop(snapshot);
+        self.snapshot_bytes_in_use -= snapshot_bytes;
+        result
+    }
+
+    /// 依次尝试单元格的每种可能性，失败时恢复到快照
+    fn try_possibilities(
+        &mut self,
+        cells: &[CellId],
+        index: usize,
+        possibilities: &[TileId],
+        snapshot: &SystemSnapshot,
+    ) -> Result<bool, WfcError> {
+        let cell_id = cells[index];
+
+        for &possibility in possibilities {
+            if !self.exceeds_global_max(cell_id, possibility)
+                && self.tile_is_compatible(possibility, cell_id)?
+            {
+                // 设置瓷砖
+                self.set_tile_for_cell(cell_id, possibility)?;
+
+                // 递归处理下一个单元
+                if self.backtrack_solution(cells, index + 1)? {
+                    return Ok(true);
+                }
+
+                // 恢复状态
+                self.restore_snapshot(snapshot)?;
+            }
+        }
+
+        Ok(false)
+    }
+
+    // ==========================================================================
+    // 回溯冲突策略
+    // ==========================================================================
+
+    /// 保存坍塌前的状态作为决策点
+    fn push_decision(&mut self, cell: CellId, tile: TileId) -> Result<(), WfcError> {
+        let snapshot = self.create_snapshot();
+        let snapshot_bytes = snapshot.estimated_bytes();
+        self.reserve_snapshot_memory(snapshot_bytes)?;
+
+        self.decision_stack.push(Decision {
+            cell,
+            tried: vec![tile],
+            snapshot,
+            snapshot_bytes,
+        });
+        Ok(())
+    }
+
+    /// 回退到最近仍有未尝试瓷砖的决策点，换用其他瓷砖重新坍塌
+    ///
+    /// 返回`false`表示所有决策点都已穷尽，问题无解。
+    fn backtrack(&mut self) -> Result<bool, WfcError> {
+        while let Some(mut decision) = self.decision_stack.pop() {
+            self.restore_snapshot(&decision.snapshot)?;
+
+            let mut remaining = self.wfc_data[&decision.cell].possibilities.clone();
+            for &tile in &decision.tried {
+                remaining.remove(tile);
+            }
+            if remaining.is_clear() {
+                // 该决策点已穷尽，继续回退到更早的决策
+                self.snapshot_bytes_in_use -= decision.snapshot_bytes;
+                continue;
+            }
+
+            // 在剩余瓷砖中按权重重新选择
+            let entropy = self.narrow_possibilities(decision.cell, remaining);
+            self.wfc_data.get_mut(&decision.cell).unwrap().entropy = entropy;
+
+            let cell = decision.cell;
+            let tile = self.choose_tile_from_probabilities(cell)?;
+            decision.tried.push(tile);
+            self.decision_stack.push(decision);
+
+            self.set_tile_for_cell(cell, tile)?;
+            self.propagate_effects(cell)?;
+
+            let unmet = self.enforce_global_constraints()?;
+            if unmet.is_none() && !self.has_conflicts() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// 清空决策栈并释放快照内存
+    fn clear_decisions(&mut self) {
+        for decision in self.decision_stack.drain(..) {
+            self.snapshot_bytes_in_use -= decision.snapshot_bytes;
+        }
+    }
+
+    /// 登记新快照的内存占用，超出配置上限时返回错误
+    fn reserve_snapshot_memory(&mut self, bytes: usize) -> Result<(), WfcError> {
+        let required = self.snapshot_bytes_in_use + bytes;
+        if let Some(limit) = self.config.max_snapshot_memory {
+            if required > limit {
+                return Err(WfcError::SnapshotMemoryExceeded { limit, required });
+            }
+        }
+
+        self.snapshot_bytes_in_use = required;
+        self.peak_snapshot_bytes = self.peak_snapshot_bytes.max(required);
+        Ok(())
+    }
+
+    /// 创建系统快照，对应C++的getSystem()
+    fn create_snapshot(&self) -> SystemSnapshot {
+        SystemSnapshot {
+            data: self.wfc_data.clone(),
+            completed_count: self.completed_count,
+            collapse_event_count: self.collapse_events.len(),
+        }
+    }
+
+    /// 恢复系统快照，对应C++的setSystem()
+    ///
+    /// 借用快照并复用现有存储，避免每次恢复都额外克隆一份快照。
+    fn restore_snapshot(&mut self, snapshot: &SystemSnapshot) -> Result<(), WfcError> {
+        self.wfc_data.clone_from(&snapshot.data);
+        self.completed_count = snapshot.completed_count;
+        // 快照之后的坍塌已被撤销，不再属于生成历史
+        self.collapse_events.truncate(snapshot.collapse_event_count);
+        self.entropy_heap_dirty = true;
+        Ok(())
+    }
+
+    /// 检查真实单元格的邻居数量是否等于瓷砖集要求的数量
+    fn check_neighbor_counts(&self) -> Result<(), WfcError> {
+        let Some(expected) = self.tile_set.expected_neighbor_count() else {
+            return Ok(());
+        };
+        let mismatch = self
+            .grid
+            .get_all_cells()
+            .filter(|&cell_id| !self.grid.is_virtual_node(cell_id))
+            .map(|cell_id| (cell_id, self.grid.get_neighbors(cell_id).len()))
+            .filter(|&(_, neighbor_count)| neighbor_count != expected)
+            .min_by_key(|&(cell_id, _)| cell_id);
+        match mismatch {
+            Some((cell, neighbor_count)) => Err(WfcError::NeighborCountMismatch {
+                cell,
+                neighbor_count,
+                expected,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // ==========================================================================
+    // 全局数量约束
+    // ==========================================================================
+
+    /// 执行[`WfcConfig::global_constraints`]
+    ///
+    /// 达到上限的瓷砖从区域内所有未坍塌的非虚拟单元格中移除并传播，
+    /// 返回第一个下限已无法满足的约束。
+    fn enforce_global_constraints(&mut self) -> Result<Option<GlobalConstraint>, WfcError> {
+        if self.config.global_constraints.is_empty() {
+            return Ok(None);
+        }
+
+        let constraints = self.config.global_constraints.clone();
+        for constraint in &constraints {
+            let Some(max) = constraint.max else {
+                continue;
+            };
+            if self.tile_usage(constraint.tile).0 < max {
+                continue;
+            }
+
+            let mut cells: Vec<CellId> = self
+                .wfc_data
+                .iter()
+                .filter(|(&cell_id, data)| {
+                    data.state == CellState::Uncollapsed
+                        && data.is_possible(constraint.tile)
+                        && !self.grid.is_virtual_node(cell_id)
+                        && self.in_region(cell_id)
+                })
+                .map(|(&cell_id, _)| cell_id)
+                .collect();
+            cells.sort_unstable();
+            for cell_id in cells {
+                // 前面的移除可能已经通过传播使单元格冲突
+                if self.wfc_data[&cell_id].state == CellState::Uncollapsed {
+                    self.ban_tiles(cell_id, &[constraint.tile])?;
+                }
+            }
+        }
+
+        Ok(constraints.into_iter().find(|constraint| {
+            let (placed, reachable) = self.tile_usage(constraint.tile);
+            constraint.min.is_some_and(|min| placed + reachable < min)
+        }))
+    }
+
+    /// 在单元格上放置瓷砖是否会超过全局数量上限，虚拟边界节点不计数
+    fn exceeds_global_max(&self, cell_id: CellId, tile_id: TileId) -> bool {
+        !self.grid.is_virtual_node(cell_id)
+            && self.config.global_constraints.iter().any(|constraint| {
+                constraint.tile == tile_id
+                    && constraint
+                        .max
+                        .is_some_and(|max| self.tile_usage(tile_id).0 >= max)
+            })
+    }
+
+    /// 统计瓷砖在非虚拟单元格上的`(已坍塌数, 仍可能放置的未坍塌数)`
+    fn tile_usage(&self, tile_id: TileId) -> (usize, usize) {
+        let mut placed = 0;
+        let mut reachable = 0;
+        for (&cell_id, data) in &self.wfc_data {
+            if self.grid.is_virtual_node(cell_id) || !data.is_possible(tile_id) {
+                continue;
+            }
+            match data.state {
+                CellState::Collapsed => placed += 1,
+                CellState::Uncollapsed => reachable += 1,
+                CellState::Conflict => {}
+            }
+        }
+        (placed, reachable)
+    }
+
+    /// 检查是否有冲突
+    fn has_conflicts(&self) -> bool {
+        self.wfc_data
+            .values()
+            .any(|data| data.state == CellState::Conflict)
+    }
+}
+
+/// 查询瓷砖的权重倍数，未设置时为1.0
+fn weight_multiplier(multipliers: Option<&HashMap<TileId, f64>>, tile_id: TileId) -> f64 {
+    multipliers
+        .and_then(|multipliers| multipliers.get(&tile_id))
+        .copied()
+        .unwrap_or(1.0)
+}
+
+/// 坐标与种子的哈希，使用SplitMix64混合，结果不依赖平台和标准库版本
+fn spatial_hash(coords: &[i32], seed: u64) -> u64 {
+    fn mix(mut z: u64) -> u64 {
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    coords.iter().fold(mix(seed), |hash, &coord| {
+        mix(hash.wrapping_add(0x9e37_79b9_7f4a_7c15) ^ coord as u32 as u64)
+    })
+}
+
+// =============================================================================
+// 测试模块
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile_set::{AdjacencyTileSet, TileSet};
+
+    // 测试用的简单瓷砖集
+    struct TestTileSet {
+        tiles: TileSet<&'static str>,
+    }
+
+    impl TestTileSet {
+        pub fn new() -> Self {
+            let mut tiles = TileSet::new();
+            tiles.add_tile(vec!["A", "B", "C", "D"], 10);
+            tiles.add_tile(vec!["B", "A", "D", "C"], 15);
+            Self { tiles }
+        }
+    }
+
+    impl TileSetVirtual<&'static str> for TestTileSet {
+        fn build_tile_set(&mut self) -> Result<(), GridError> {
+            // 已在new中构建
+            Ok(())
+        }
+
+        fn judge_possibility(
+            &self,
+            _neighbor_possibilities: &[Vec<TileId>],
+            _candidate: TileId,
+        ) -> bool {
+            // 简单返回true用于测试
+            true
+        }
+
+        fn get_tile(&self, tile_id: TileId) -> Option<&Tile<&'static str>> {
+            self.tiles.get_tile(tile_id)
+        }
+
+        fn get_tile_count(&self) -> usize {
+            self.tiles.get_tile_count()
+        }
+
+        fn get_all_tile_ids(&self) -> Vec<TileId> {
+            self.tiles.get_all_tile_ids()
+        }
+    }
+
+    // 边匹配瓷砖集，边数据顺序为 [北, 西, 南, 东]
+    struct MatchTileSet {
+        tiles: TileSet<i32>,
+    }
+
+    impl MatchTileSet {
+        fn new() -> Self {
+            let mut tiles = TileSet::new();
+            tiles.add_tile(vec![0, 0, 0, 0], 1);
+            tiles.add_tile(vec![1, 0, 1, 0], 1);
+            tiles.add_tile(vec![0, 1, 0, 1], 1);
+            tiles.add_tile(vec![1, 1, 1, 1], 1);
+            Self { tiles }
+        }
+    }
+
+    impl TileSetVirtual<i32> for MatchTileSet {
+        fn build_tile_set(&mut self) -> Result<(), GridError> {
+            Ok(())
+        }
+
+        fn judge_possibility(
+            &self,
+            neighbor_possibilities: &[Vec<TileId>],
+            candidate: TileId,
+        ) -> bool {
+            let Some(tile) = self.tiles.get_tile(candidate) else {
+                return false;
+            };
+            neighbor_possibilities
+                .iter()
+                .enumerate()
+                .all(|(direction, neighbor_tiles)| {
+                    neighbor_tiles.is_empty()
+                        || neighbor_tiles.iter().any(|&neighbor_id| {
+                            self.tiles.get_tile(neighbor_id).is_some_and(|neighbor| {
+                                neighbor.edges[(direction + 2) % 4] == tile.edges[direction]
+                            })
+                        })
+                })
+        }
+
+        fn get_tile(&self, tile_id: TileId) -> Option<&Tile<i32>> {
+            self.tiles.get_tile(tile_id)
+        }
+
+        fn get_tile_count(&self) -> usize {
+            self.tiles.get_tile_count()
+        }
+
+        fn get_all_tile_ids(&self) -> Vec<TileId> {
+            self.tiles.get_all_tile_ids()
+        }
+    }
+
+    // 按东、南、西、北顺序建边的正交网格，边界使用虚拟节点
+    fn build_orthogonal_grid(width: usize, height: usize) -> (GridSystem, Vec<Vec<CellId>>) {
+        let mut grid = GridSystem::new();
+        let cells: Vec<Vec<CellId>> = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| grid.add_cell(Cell::with_id((y * width + x) as u32)))
+                    .collect()
+            })
+            .collect();
+
+        for y in 0..height {
+            for x in 0..width {
+                let current = cells[y][x];
+                grid.create_edge(current, (x + 1 < width).then(|| cells[y][x + 1]))
+                    .unwrap();
+                grid.create_edge(current, (y + 1 < height).then(|| cells[y + 1][x]))
+                    .unwrap();
+                grid.create_edge(current, (x > 0).then(|| cells[y][x - 1]))
+                    .unwrap();
+                grid.create_edge(current, (y > 0).then(|| cells[y - 1][x]))
+                    .unwrap();
+            }
+        }
+
+        (grid, cells)
+    }
+
+    fn match_manager(width: usize, height: usize) -> (WfcManager<i32>, Vec<Vec<CellId>>) {
+        match_manager_with_config(width, height, WfcConfig::default())
+    }
+
+    fn match_manager_with_config(
+        width: usize,
+        height: usize,
+        config: WfcConfig,
+    ) -> (WfcManager<i32>, Vec<Vec<CellId>>) {
+        let (grid, cells) = build_orthogonal_grid(width, height);
+        let mut manager =
+            WfcManager::with_config(grid, Box::new(MatchTileSet::new()), config).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        (manager, cells)
+    }
+
+    // 1x3的一行：左端为十字瓷砖、右端为空白瓷砖，中间单元格无解
+    fn conflicted_row(config: WfcConfig) -> WfcManager<i32> {
+        let (mut manager, cells) = match_manager_with_config(3, 1, config);
+        manager.set_tile_for_cell(cells[0][0], 3).unwrap();
+        manager.set_tile_for_cell(cells[0][2], 0).unwrap();
+
+        let middle = manager.wfc_data.get_mut(&cells[0][1]).unwrap();
+        middle.possibilities.clear();
+        middle.state = CellState::Conflict;
+        manager
+    }
+
+    #[test]
+    fn test_wfc_manager_creation() {
+        let grid = GridSystem::new();
+        let tile_set = Box::new(TestTileSet::new()) as Box<dyn TileSetVirtual<&'static str>>;
+
+        let manager = WfcManager::new(grid, tile_set).unwrap();
+        assert_eq!(manager.completed_count, 0);
+        assert!(manager.is_complete()); // 空网格自动完成
+    }
+
+    #[test]
+    fn test_state_display() {
+        assert_eq!(format!("{}", CellState::Uncollapsed), "uncollapsed");
+        assert_eq!(format!("{}", CellState::Collapsed), "collapsed");
+        assert_eq!(format!("{}", CellState::Conflict), "conflict");
+
+        assert_eq!(StepResult::Collapsed.to_string(), "collapsed a cell");
+        assert_eq!(
+            StepResult::ConflictsResolved.to_string(),
+            "conflicts resolved"
+        );
+        assert_eq!(
+            StepResult::ConflictResolutionFailed.to_string(),
+            "conflict resolution failed"
+        );
+        assert_eq!(StepResult::Complete.to_string(), "complete");
+    }
+
+    #[test]
+    fn test_wfc_states() {
+        assert_eq!(CellState::Uncollapsed, CellState::Uncollapsed);
+        assert_ne!(CellState::Uncollapsed, CellState::Collapsed);
+
+        let data = CellWfcData::new(12345, vec![0, 1]);
+        assert_eq!(data.state, CellState::Uncollapsed);
+        assert_eq!(data.rand_seed, 12345);
+        assert_eq!(data.possibility_count(), 2);
+    }
+
+    #[test]
+    fn test_collapsed_tiles() {
+        let (mut manager, cells) = conflict_free_manager(4, 3);
+        assert!(manager.collapsed_tiles().is_empty());
+        assert_eq!(
+            manager.try_collapsed_tiles(),
+            Err(WfcError::InconsistentState)
+        );
+
+        manager.pre_collapse(cells[0][0], 1).unwrap();
+        assert_eq!(
+            manager.collapsed_tiles(),
+            BTreeMap::from([(cells[0][0], 1)])
+        );
+
+        manager.run().unwrap();
+        let tiles = manager.try_collapsed_tiles().unwrap();
+        assert_eq!(tiles, manager.collapsed_tiles());
+        assert_eq!(tiles.len(), manager.get_grid().get_cells_count());
+        for (&cell, &tile) in &tiles {
+            assert_eq!(manager.get_collapsed_cell_tile(cell), Ok(tile));
         }
 
-        // 保险措施，理论上不应该到达这里
-        Ok(*cell_data.possibilities.last().unwrap())
+        let conflicted = conflicted_row(WfcConfig::default());
+        assert_eq!(
+            conflicted.try_collapsed_tiles(),
+            Err(WfcError::UnresolvableConflicts(
+                conflicted.conflict_report()
+            ))
+        );
+        assert_eq!(conflicted.collapsed_tiles().len(), 2);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let (mut manager, cells) = conflict_free_manager(4, 3);
+        let statistics = manager.statistics();
+        assert_eq!(statistics.total_cells, 12);
+        assert_eq!(statistics.uncollapsed, 12);
+        assert_eq!(statistics.completion_ratio, 0.0);
+        assert!(statistics.tile_usage.is_empty());
+
+        manager.pre_collapse(cells[0][0], 1).unwrap();
+        assert_eq!(manager.statistics().collapsed, 1);
+        assert_eq!(manager.statistics().tile_usage, BTreeMap::from([(1, 1)]));
+
+        // 完全坍塌后，瓷砖使用次数之和等于单元格总数
+        manager.run().unwrap();
+        let statistics = manager.statistics();
+        assert_eq!(statistics.collapsed, statistics.total_cells);
+        assert_eq!(statistics.uncollapsed + statistics.conflict, 0);
+        assert_eq!(statistics.completion_ratio, 1.0);
+        assert_eq!(
+            statistics.tile_usage.values().sum::<usize>(),
+            statistics.total_cells
+        );
     }
 
-    /// 设置单元格瓷砖，对应C++的setTileForCell()
-    fn set_tile_for_cell(&mut self, cell_id: CellId, tile_id: TileId) -> Result<(), WfcError> {
-        let cell_data = self
-            .wfc_data
-            .get_mut(&cell_id)
-            .ok_or(WfcError::CellNotFound(cell_id))?;
+    #[test]
+    fn test_collapse_events() {
+        let (mut manager, _) = conflict_free_manager(5, 4);
+        assert!(manager.collapse_events().is_empty());
+        manager.run().unwrap();
+
+        // 每个单元格恰好坍塌一次，事件与最终结果一致
+        let events = manager.collapse_events();
+        assert_eq!(events.len(), manager.get_grid().get_cells_count());
+        assert!(events.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        let cells: HashSet<CellId> = events.iter().map(|&(_, cell, _)| cell).collect();
+        assert_eq!(cells.len(), events.len());
+        for &(_, cell, tile) in events {
+            assert_eq!(manager.get_collapsed_cell_tile(cell), Ok(tile));
+        }
 
-        // 设置选定的瓷砖为唯一的可能性
-        cell_data.possibilities.clear();
-        cell_data.possibilities.push(tile_id);
-        cell_data.entropy = 0.0;
-        cell_data.state = CellState::Collapsed;
+        // 恢复检查点时撤销之后的事件，重新初始化时清空
+        let (mut manager, cells) = conflict_free_manager(3, 3);
+        manager.pre_collapse(cells[0][0], 0).unwrap();
+        let checkpoint = manager.checkpoint();
+        manager.pre_collapse(cells[1][1], 1).unwrap();
+        assert_eq!(manager.collapse_events().len(), 2);
+        manager.restore(checkpoint).unwrap();
+        assert_eq!(manager.collapse_events(), &[(0, cells[0][0], 0)]);
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        assert!(manager.collapse_events().is_empty());
+    }
 
-        self.completed_count += 1;
+    #[test]
+    fn test_look_ahead_avoids_conflicts() {
+        // 边数据顺序：[北, 西, 南, 东]。岩壁瓷砖的东边没有任何瓷砖能匹配，
+        // 且空白瓷砖权重为0，默认路径总是选中岩壁瓷砖
+        let manager_with = |look_ahead: bool| {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            tile_set.add_tile(vec![0, 0, 0, 0], 0);
+            tile_set.add_tile(vec![0, 0, 0, 2], 1);
+            let (grid, _) = build_orthogonal_grid(3, 3);
+            let config = WfcConfig::builder().seed(3).look_ahead(look_ahead).build();
+            let mut manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            manager
+        };
 
-        Ok(())
+        // 第一次坍塌就使东侧邻居冲突
+        let mut manager = manager_with(false);
+        manager.run_steps(1).unwrap();
+        assert!(manager.statistics().conflict > 0);
+
+        // 前瞻只在东侧没有真实邻居时放置岩壁瓷砖，整个过程没有冲突
+        let mut manager = manager_with(true);
+        assert_eq!(manager.run_steps(1000).unwrap(), RunOutcome::Completed);
+        assert_eq!(manager.statistics().conflict, 0);
+        assert!(manager.is_complete());
     }
 
-    /// 约束传播算法，对应C++的propagateEffects()
-    ///
-    /// 利用无向连接（边对）进行双向约束传播，确保所有邻居的约束一致性
-    fn propagate_effects(&mut self, start_cell: CellId) -> Result<(), WfcError> {
-        if self.is_complete() {
-            return Ok(());
+    #[test]
+    fn test_global_tile_count_constraints() {
+        let manager_with = |config: WfcConfig| {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            tile_set.add_tile(vec![0, 0, 0, 0], 1);
+            tile_set.add_tile(vec![0, 0, 0, 0], 5);
+            let (grid, _) = build_orthogonal_grid(4, 4);
+            WfcManager::with_config(grid, Box::new(tile_set), config).unwrap()
+        };
+
+        // 权重更高的瓷砖最多出现一次
+        for strategy in [ConflictStrategy::LayeredRepair, ConflictStrategy::Backtrack] {
+            let config = WfcConfig::builder()
+                .seed(11)
+                .conflict_strategy(strategy)
+                .global_constraint(GlobalConstraint::at_most(1, 1))
+                .build();
+            let mut manager = manager_with(config);
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            manager.run().unwrap();
+
+            let usage = manager.statistics().tile_usage;
+            assert_eq!(usage.get(&1), Some(&1));
+            assert_eq!(usage.get(&0), Some(&15));
         }
 
-        let mut propagation_queue = VecDeque::new();
-        let mut processed_cells = HashSet::new();
+        // 分层修复从邻居恢复可能性时不会放回已达上限的瓷砖
+        let config = WfcConfig::builder()
+            .global_constraint(GlobalConstraint::at_most(0, 1))
+            .build();
+        let (mut manager, cells) = match_manager_with_config(3, 1, config);
+        manager.set_tile_for_cell(cells[0][0], 0).unwrap();
+        assert_eq!(manager.enforce_global_constraints(), Ok(None));
+        assert!(!manager.wfc_data[&cells[0][1]].is_possible(0));
+        let last = manager.wfc_data.get_mut(&cells[0][2]).unwrap();
+        last.possibilities.clear();
+        last.state = CellState::Conflict;
+
+        assert_eq!(manager.resolve_conflicts(), Ok(true));
+        manager.run().unwrap();
+        assert_eq!(manager.statistics().tile_usage.get(&0), Some(&1));
+        assert_ne!(manager.get_collapsed_cell_tile(cells[0][2]), Ok(0));
+
+        // 下限超过单元格数量时无法满足
+        let config = WfcConfig::builder()
+            .global_constraint(GlobalConstraint::at_least(0, 17))
+            .build();
+        let mut manager = manager_with(config);
+        assert_eq!(
+            manager.initialize_with(&mut DefaultInitializer),
+            Err(WfcError::GlobalConstraintUnsatisfiable(
+                GlobalConstraint::at_least(0, 17)
+            ))
+        );
+    }
 
-        propagation_queue.push_back(start_cell);
-        processed_cells.insert(start_cell);
+    #[test]
+    fn test_uncollapsed_by_entropy() {
+        let (mut manager, _) = match_manager(3, 3);
+        manager.run_steps(3).unwrap();
 
-        while let Some(current_cell) = propagation_queue.pop_front() {
-            // 获取所有邻居
-            let neighbors = self.grid.get_neighbors(current_cell);
+        let wave = manager.uncollapsed_by_entropy();
+        let uncollapsed = manager
+            .wfc_data
+            .values()
+            .filter(|data| data.state == CellState::Uncollapsed)
+            .count();
+        assert_eq!(wave.len(), uncollapsed);
+        assert!(wave
+            .iter()
+            .all(|&(cell, _)| manager.get_cell_state(cell).unwrap() == CellState::Uncollapsed));
+        assert!(wave
+            .windows(2)
+            .all(|pair| (pair[0].1, pair[0].0) <= (pair[1].1, pair[1].0)));
 
-            for neighbor in neighbors {
-                if processed_cells.contains(&neighbor) {
-                    continue;
-                }
+        // 传播使已坍塌单元格的邻居熵值降低，排在最前
+        assert!(wave.first().unwrap().1 < wave.last().unwrap().1);
+    }
 
-                let neighbor_data = self
-                    .wfc_data
-                    .get(&neighbor)
-                    .ok_or(WfcError::CellNotFound(neighbor))?;
-                if neighbor_data.state != CellState::Uncollapsed {
-                    continue;
-                }
+    #[test]
+    #[cfg(not(feature = "std"))]
+    fn test_no_std_core() {
+        // 哈希表来自hashbrown，对数由libm计算
+        let _: hashbrown::HashSet<CellId> = HashSet::new();
+        assert_eq!(log2(8.0), 3.0);
+
+        // 没有系统熵源，未配置种子时使用固定种子，结果可以复现
+        let run = || {
+            let (mut manager, _) = match_manager(4, 4);
+            assert_eq!(manager.seed, 0);
+            manager.run().unwrap();
+            manager.collapsed_tiles()
+        };
+        assert_eq!(run(), run());
+    }
 
-                // 更新邻居可能性
-                let constraint_updated = self.update_neighbor_possibilities(neighbor)?;
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_error_source_chain() {
+        use std::error::Error;
+
+        let error: Box<dyn Error> = Box::new(WfcError::Grid(GridError::SelfLoop));
+        let source = error.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<GridError>(),
+            Some(&GridError::SelfLoop)
+        );
+
+        assert!(WfcError::NoUncollapsedCells.source().is_none());
+    }
 
-                if constraint_updated {
-                    propagation_queue.push_back(neighbor);
-                    processed_cells.insert(neighbor);
+    #[test]
+    fn test_scoring_biases_choice() {
+        // 瓷砖0和1的边完全相同，权重分别为1和2
+        let (mut manager, cells) = conflict_free_manager(3, 1);
+        manager.config.live_random = true;
+        manager.pre_collapse(cells[0][0], 0).unwrap();
+
+        let count_zero = |manager: &mut WfcManager<i32>, cell: CellId| {
+            (0..400)
+                .filter(|_| manager.choose_tile_from_probabilities(cell).unwrap() == 0)
+                .count()
+        };
+        assert!(count_zero(&mut manager, cells[0][1]) < 180);
+
+        // 西边邻居已确定为瓷砖0时，瓷砖0的权重提高到9倍
+        let prefer_after = |direction: Direction4Full| {
+            move |tile: TileId, neighbors: &[(usize, &[TileId])]| {
+                // 1行网格只有东西两个真实邻居，虚拟边界节点不出现
+                assert!(neighbors.len() <= 2);
+                let matched = neighbors.iter().any(|&(index, tiles)| {
+                    Direction4Full::from_neighbor_index(index) == Some(direction) && tiles == [0]
+                });
+                if tile == 0 && matched {
+                    9.0
+                } else {
+                    1.0
                 }
             }
-        }
+        };
+        manager.set_scoring(prefer_after(Direction4Full::West));
+        assert!(count_zero(&mut manager, cells[0][1]) > 280);
+        // 不相邻的单元格不受影响
+        assert!(count_zero(&mut manager, cells[0][2]) < 180);
+
+        // 条件方向上的邻居不满足时同样不受影响
+        manager.set_scoring(prefer_after(Direction4Full::East));
+        assert!(count_zero(&mut manager, cells[0][1]) < 180);
+    }
 
-        Ok(())
+    #[test]
+    fn test_radial_observation() {
+        let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+        tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        tile_set.add_tile(vec![0, 0, 0, 0], 2);
+        let (grid, cells) = build_orthogonal_grid(5, 5);
+        let center = cells[2][2];
+        let distances = grid.bfs_distances(center);
+        let config = WfcConfig::builder()
+            .seed(5)
+            .observation_heuristic(ObservationHeuristic::RadialFrom(center))
+            .build();
+        let mut manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        manager.pre_collapse(center, 0).unwrap();
+        manager.run_steps(12).unwrap();
+
+        // 种子之后先坍塌距离为1的4个单元格，再坍塌距离为2的8个单元格
+        let order: Vec<usize> = manager.collapse_events()[1..]
+            .iter()
+            .map(|&(_, cell, _)| distances[&cell])
+            .collect();
+        assert_eq!(order, [vec![1; 4], vec![2; 8]].concat());
     }
 
-    /// 更新邻居可能性，基于约束传播
-    fn update_neighbor_possibilities(&mut self, neighbor: CellId) -> Result<bool, WfcError> {
-        // 先获取邻居数据的克隆，避免可变借用冲突
-        let neighbor_data = self
-            .wfc_data
-            .get(&neighbor)
-            .ok_or(WfcError::CellNotFound(neighbor))?
-            .clone();
+    #[test]
+    fn test_clone_for_new_run() {
+        let (mut manager, cells) = conflict_free_manager(3, 3);
+        manager
+            .set_cell_weight_multiplier(cells[0][0], 1, 0.0)
+            .unwrap();
+
+        // 副本带有新种子和权重倍数，但需要重新初始化
+        let mut run = manager.clone_for_new_run(11).unwrap().unwrap();
+        assert_eq!(run.seed, 11);
+        assert!(run.wfc_data.is_empty());
+        run.initialize_with(&mut DefaultInitializer).unwrap();
+        run.run().unwrap();
+        assert_eq!(run.get_collapsed_cell_tile(cells[0][0]), Ok(0));
+
+        // 瓷砖集不支持复制
+        let (manager, _) = match_manager(2, 2);
+        assert!(manager.clone_for_new_run(11).unwrap().is_none());
+    }
 
-        if neighbor_data.state != CellState::Uncollapsed {
-            return Ok(false); // 已坍塌或冲突的单元格不需要更新
+    #[test]
+    fn test_clone_for_new_run_across_threads() {
+        let (manager, _) = conflict_free_manager(5, 5);
+        let results: Vec<BTreeMap<CellId, TileId>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4u64)
+                .map(|seed| {
+                    let manager = &manager;
+                    scope.spawn(move || {
+                        let mut run = manager.clone_for_new_run(seed).unwrap().unwrap();
+                        run.initialize_with(&mut DefaultInitializer).unwrap();
+                        run.run().unwrap();
+                        run.try_collapsed_tiles().unwrap()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        assert_eq!(results.len(), 4);
+        for tiles in &results {
+            assert_eq!(tiles.len(), manager.wfc_data.len());
         }
+        // 各副本使用不同的种子独立运行
+        assert!(results.windows(2).any(|pair| pair[0] != pair[1]));
+    }
 
-        // 过滤兼容的瓷砖
-        let compatible_tiles = self.filter_compatible_tiles(neighbor)?;
-
-        // 检查是否产生了约束变化
-        let old_count = neighbor_data.possibilities.len();
-        let new_count = compatible_tiles.len();
-
-        if new_count != old_count {
-            // 计算新的熵值
-            let new_entropy = self.calculate_entropy(&compatible_tiles);
-
-            // 更新邻居数据
-            let neighbor_data_mut = self.wfc_data.get_mut(&neighbor).unwrap();
-            neighbor_data_mut.possibilities = compatible_tiles;
-            neighbor_data_mut.entropy = new_entropy;
-
-            // 检查冲突状态
-            if neighbor_data_mut.possibilities.is_empty() {
-                neighbor_data_mut.state = CellState::Conflict;
+    #[test]
+    fn test_conflict_cells_are_ordered() {
+        let forced_conflicts = || {
+            let (mut manager, cells) = conflict_free_manager(5, 5);
+            let all_tiles = manager.get_all_tile_ids();
+            for (x, y) in [(4, 4), (0, 2), (3, 1), (1, 0), (2, 3)] {
+                manager.ban_tiles(cells[y][x], &all_tiles).unwrap();
             }
+            let mut expected: Vec<CellId> = [(1, 0), (3, 1), (0, 2), (2, 3), (4, 4)]
+                .into_iter()
+                .map(|(x, y)| cells[y][x])
+                .collect();
+            expected.sort_unstable();
+            (manager.collect_conflict_cells(), expected)
+        };
 
-            Ok(true)
-        } else {
-            Ok(false)
+        // 每次运行使用新的HashMap，结果仍按索引排序且完全相同
+        let (first, expected) = forced_conflicts();
+        assert_eq!(first, expected);
+        for _ in 0..5 {
+            assert_eq!(forced_conflicts().0, first);
         }
     }
 
-    /// 过滤兼容的瓷砖
-    fn filter_compatible_tiles(&self, cell_id: CellId) -> Result<Vec<TileId>, WfcError> {
-        let cell_data = self
-            .wfc_data
-            .get(&cell_id)
-            .ok_or(WfcError::CellNotFound(cell_id))?;
-        let mut compatible_tiles = Vec::new();
+    #[test]
+    fn test_get_possibilities() {
+        let (mut manager, cells) = match_manager(3, 3);
+        let cell = cells[1][1];
+        assert_eq!(
+            manager.get_possibilities(cell).unwrap(),
+            manager.get_all_tile_ids()
+        );
+        assert!(manager.get_entropy(cell).unwrap() > 0.0);
+
+        manager.pre_collapse(cell, 3).unwrap();
+        assert_eq!(manager.get_possibilities(cell).unwrap(), vec![3]);
+        assert_eq!(manager.get_entropy(cell).unwrap(), 0.0);
+
+        let missing = CellId::new(1000);
+        assert_eq!(
+            manager.get_possibilities(missing),
+            Err(WfcError::CellNotFound(missing))
+        );
+        assert_eq!(
+            manager.get_entropy(missing),
+            Err(WfcError::CellNotFound(missing))
+        );
+    }
 
-        for &tile_id in &cell_data.possibilities {
-            if self.tile_is_compatible(tile_id, cell_id)? {
-                compatible_tiles.push(tile_id);
+    #[test]
+    fn test_conflict_report() {
+        let (manager, cells) = conflict_free_manager(3, 1);
+        assert!(manager.conflict_report().is_empty());
+
+        // 左侧东边为1、右侧西边为0，中间单元格没有任何瓷砖能同时满足
+        let conflicted = conflicted_row(WfcConfig::default());
+        let report = conflicted.conflict_report();
+        let (left, middle, right) = (cells[0][0], cells[0][1], cells[0][2]);
+        assert_eq!(report.cells(), vec![middle]);
+        let conflict = &report.conflicts[0];
+        assert!(conflict.possibilities.is_empty());
+        let mut neighbors = conflict.neighbors.clone();
+        neighbors.sort();
+        assert_eq!(neighbors, vec![(left, vec![3]), (right, vec![0])]);
+
+        // 左侧只剩瓷砖3后传播使右侧只能为2或3，再排除这些瓷砖使右侧冲突；
+        // 回溯耗尽所有决策后，错误中携带冲突报告
+        let config = WfcConfig::builder()
+            .conflict_strategy(ConflictStrategy::Backtrack)
+            .build();
+        let (mut manager, cells) = match_manager_with_config(3, 1, config);
+        manager.ban_tiles(cells[0][0], &[0, 1, 2]).unwrap();
+        manager.ban_tiles(cells[0][2], &[1, 2, 3]).unwrap();
+        match manager.run() {
+            Err(WfcError::UnresolvableConflicts(report)) => {
+                assert_eq!(report.cells(), vec![cells[0][2]]);
+                assert_eq!(
+                    report.conflicts[0].neighbors,
+                    vec![(cells[0][1], vec![2, 3])]
+                );
             }
+            other => panic!("unexpected result: {:?}", other),
         }
+    }
 
-        Ok(compatible_tiles)
+    #[test]
+    fn test_scanline_observation() {
+        let collapse_order = || {
+            let config = WfcConfig::builder()
+                .seed(7)
+                .observation_heuristic(ObservationHeuristic::Scanline)
+                .build();
+            let (mut manager, cells) = match_manager_with_config(4, 3, config);
+            let mut order = Vec::new();
+            manager
+                .run_with_observer(|progress| {
+                    order.extend(progress.collapsed.map(|(cell, _)| cell))
+                })
+                .unwrap();
+            (order, cells)
+        };
+
+        let (order, cells) = collapse_order();
+        assert_eq!(&order[..2], &[cells[0][0], cells[0][1]]);
+        assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(collapse_order().0, order);
     }
 
-    /// 检查瓷砖兼容性，对应C++的tileIsCompatible()
-    fn tile_is_compatible(&self, tile_id: TileId, cell_id: CellId) -> Result<bool, WfcError> {
-        let neighbors = self.grid.get_neighbors(cell_id);
-        let mut neighbor_possibilities = Vec::new();
+    #[test]
+    fn test_most_constrained_observation() {
+        let config = WfcConfig::builder()
+            .observation_heuristic(ObservationHeuristic::MostConstrained)
+            .build();
+        let (mut manager, cells) = match_manager_with_config(3, 3, config);
+        assert_eq!(manager.find_min_entropy_cell().unwrap(), cells[0][0]);
+
+        // 直接收缩可能性，不触发传播
+        let restrict = |manager: &mut WfcManager<i32>, cell: CellId| {
+            let data = manager.wfc_data.get_mut(&cell).unwrap();
+            data.possibilities.set(0, false);
+            data.possibilities.set(1, false);
+        };
 
-        for neighbor in neighbors {
-            if let Some(neighbor_data) = self.wfc_data.get(&neighbor) {
-                neighbor_possibilities.push(neighbor_data.possibilities.clone());
-            } else {
-                neighbor_possibilities.push(self.tile_set.get_all_tile_ids());
+        // 可能性最少的单元格优先
+        restrict(&mut manager, cells[2][2]);
+        assert_eq!(manager.find_min_entropy_cell().unwrap(), cells[2][2]);
+
+        // 可能性数量相同时，已坍塌邻居较多者优先
+        restrict(&mut manager, cells[1][1]);
+        manager.set_tile_for_cell(cells[0][1], 3).unwrap();
+        assert_eq!(manager.find_min_entropy_cell().unwrap(), cells[1][1]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_propagation_matches_sequential() {
+        // 每种边组合各一个瓷砖，坍塌只约束相邻一圈单元格，传播范围保持局部
+        let manager = || {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            for bits in 0..16 {
+                tile_set.add_tile((0..4).map(|edge| (bits >> edge) & 1).collect(), 1);
+            }
+            let (grid, cells) = build_orthogonal_grid(20, 20);
+            let mut manager = WfcManager::new(grid, Box::new(tile_set)).unwrap();
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            (manager, cells)
+        };
+        let propagate = |seeds: &[(usize, usize, TileId)]| {
+            let (mut sequential, cells) = manager();
+            let (mut parallel, _) = manager();
+            let starts: Vec<CellId> = seeds.iter().map(|&(x, y, _)| cells[y][x]).collect();
+            for (&cell, &(_, _, tile)) in starts.iter().zip(seeds) {
+                sequential.set_tile_for_cell(cell, tile).unwrap();
+                parallel.set_tile_for_cell(cell, tile).unwrap();
+            }
+            sequential.propagate_from(&starts).unwrap();
+            let merged = parallel.try_propagate_partitions(&starts).unwrap();
+            if !merged {
+                parallel.propagate_from_parallel(&starts).unwrap();
             }
-        }
 
-        Ok(self
-            .tile_set
-            .judge_possibility(&neighbor_possibilities, tile_id))
+            assert_eq!(parallel.propagation_steps, sequential.propagation_steps);
+            for cell in sequential.get_grid().get_all_cells() {
+                let (expected, actual) = (&sequential.wfc_data[&cell], &parallel.wfc_data[&cell]);
+                assert_eq!(actual.state, expected.state);
+                assert_eq!(actual.possibilities, expected.possibilities);
+                assert_eq!(actual.entropy.to_bits(), expected.entropy.to_bits());
+            }
+            merged
+        };
+
+        // 两个相距很远的区域并行传播
+        assert!(propagate(&[(1, 1, 0), (2, 1, 0), (17, 17, 15)]));
+        // 两个区域的传播范围相接时退回顺序传播，结果同样一致
+        assert!(!propagate(&[(5, 5, 0), (7, 5, 15)]));
     }
 
-    /// 计算香农熵，对应C++的calculateEntropy()
-    fn calculate_entropy(&self, possibilities: &[TileId]) -> f64 {
-        if possibilities.is_empty() {
-            return 0.0;
+    #[test]
+    fn test_neighbor_state_counts() {
+        let (mut manager, cells) = match_manager(3, 3);
+        let center = cells[1][1];
+        assert_eq!(manager.neighbor_state_counts(center), (0, 4, 0));
+
+        // 北、西两侧坍塌，东侧冲突，南侧仍未坍塌
+        manager.set_tile_for_cell(cells[0][1], 0).unwrap();
+        manager.set_tile_for_cell(cells[1][0], 0).unwrap();
+        manager.wfc_data.get_mut(&cells[1][2]).unwrap().state = CellState::Conflict;
+        assert_eq!(manager.neighbor_state_counts(center), (2, 1, 1));
+
+        // 角落单元格的边界方向指向虚拟节点，不计入
+        assert_eq!(manager.neighbor_state_counts(cells[0][0]), (2, 0, 0));
+    }
+
+    #[test]
+    fn test_pre_collapse_invalid_tile_error() {
+        let (mut manager, cells) = match_manager(3, 3);
+        let center = cells[1][1];
+        manager.ban_tile(center, 2).unwrap();
+
+        let error = manager.pre_collapse(center, 2).unwrap_err();
+        assert_eq!(
+            error,
+            WfcError::InvalidTileChoice {
+                cell: center,
+                tile: 2
+            }
+        );
+        assert!(error.to_string().contains("Invalid tile choice 2"));
+        assert_eq!(
+            manager.pre_collapse(center, 99),
+            Err(WfcError::InvalidTileChoice {
+                cell: center,
+                tile: 99
+            })
+        );
+        assert_eq!(
+            WfcError::TileNotFound(7).to_string(),
+            "Tile 7 not found in tile set"
+        );
+    }
+
+    #[test]
+    fn test_pre_collapse_many() {
+        let corners = |cells: &[Vec<CellId>]| {
+            vec![
+                (cells[0][0], 0),
+                (cells[0][4], 0),
+                (cells[4][0], 0),
+                (cells[4][4], 0),
+            ]
+        };
+
+        // 逐个预设作为参照
+        let (mut sequential, cells) = match_manager(5, 5);
+        for (cell, tile) in corners(&cells) {
+            sequential.pre_collapse(cell, tile).unwrap();
         }
 
-        if possibilities.len() == 1 {
-            return 0.0;
+        let (mut manager, cells) = match_manager(5, 5);
+        manager.pre_collapse_many(&corners(&cells)).unwrap();
+        for (cell, tile) in corners(&cells) {
+            assert_eq!(manager.get_collapsed_cell_tile(cell), Ok(tile));
+        }
+        assert_eq!(manager.completed_count, 4);
+        assert!(manager.propagation_steps() <= sequential.propagation_steps());
+
+        // 任意一项无效时不做任何修改
+        let (mut manager, cells) = match_manager(5, 5);
+        let steps = manager.propagation_steps();
+        let mut assignments = corners(&cells);
+        assignments.push((cells[2][2], 99));
+        assert_eq!(
+            manager.pre_collapse_many(&assignments),
+            Err(WfcError::InvalidTileChoice {
+                cell: cells[2][2],
+                tile: 99
+            })
+        );
+        assignments[4] = (cells[0][0], 1);
+        assert_eq!(
+            manager.pre_collapse_many(&assignments),
+            Err(WfcError::CellAlreadyCollapsed)
+        );
+        assert_eq!(manager.completed_count, 0);
+        assert_eq!(manager.propagation_steps(), steps);
+        for cell in cells.iter().flatten() {
+            assert_eq!(manager.wfc_data[cell].possibility_count(), 4);
         }
+    }
 
-        // 计算总权重
-        let total_weight: f64 = possibilities
-            .iter()
-            .filter_map(|&tile_id| self.tile_set.get_tile(tile_id))
-            .map(|tile| tile.weight as f64)
-            .sum();
+    #[test]
+    fn test_ban_tiles() {
+        for seed in 0..8 {
+            let config = WfcConfig::builder().seed(seed).build();
+            let (mut manager, cells) = match_manager_with_config(4, 4, config);
+            let cell = cells[2][1];
+
+            manager.ban_tiles(cell, &[0, 1]).unwrap();
+            manager.ban_tile(cell, 3).unwrap();
+            assert_eq!(manager.wfc_data[&cell].possibilities(), vec![2]);
+            assert_eq!(manager.get_cell_state(cell), Ok(CellState::Uncollapsed));
+            assert_eq!(manager.eliminated_tiles(cell).unwrap(), vec![0, 1, 3]);
+
+            // 只剩一种可能性的单元格熵最小，下一次坍塌必然选中它和剩余的瓷砖
+            assert_eq!(manager.collapse().unwrap(), (cell, 2));
+        }
 
-        if total_weight == 0.0 {
-            return (possibilities.len() as f64).log2();
+        let (mut manager, cells) = match_manager(3, 3);
+        let cell = cells[1][1];
+        assert_eq!(manager.ban_tile(cell, 99), Err(WfcError::TileNotFound(99)));
+        manager.ban_tiles(cell, &[0, 1, 2, 3]).unwrap();
+        assert_eq!(manager.get_cell_state(cell), Ok(CellState::Conflict));
+        assert_eq!(
+            manager.ban_tile(cell, 0),
+            Err(WfcError::CellAlreadyCollapsed)
+        );
+
+        manager.pre_collapse(cells[0][0], 0).unwrap();
+        assert_eq!(
+            manager.ban_tile(cells[0][0], 1),
+            Err(WfcError::CellAlreadyCollapsed)
+        );
+    }
+
+    #[test]
+    fn test_eliminated_tiles() {
+        let (mut manager, cells) = match_manager(3, 3);
+        let center = cells[1][1];
+        manager.pre_collapse(center, 0).unwrap();
+
+        let all_tiles = manager.get_all_tile_ids();
+        for cell in cells.iter().flatten().copied() {
+            let mut union = manager.eliminated_tiles(cell).unwrap();
+            union.extend(manager.wfc_data[&cell].possibilities());
+            union.sort_unstable();
+            assert_eq!(union, all_tiles);
         }
 
-        // 计算香农熵
-        possibilities
-            .iter()
-            .filter_map(|&tile_id| self.tile_set.get_tile(tile_id))
-            .map(|tile| tile.weight as f64 / total_weight)
-            .filter(|&prob| prob > 0.0)
-            .map(|prob| -prob * prob.log2())
-            .sum()
+        // 中心为空白瓷砖，北邻居的南边必须为0，排除了1和3
+        assert_eq!(manager.eliminated_tiles(cells[0][1]).unwrap(), vec![1, 3]);
+        assert_eq!(manager.eliminated_tiles(center).unwrap(), vec![1, 2, 3]);
     }
 
-    /// 更新所有单元格的熵值
-    fn update_all_entropies(&mut self) -> Result<(), WfcError> {
-        let cell_ids: Vec<CellId> = self.wfc_data.keys().copied().collect();
+    #[test]
+    fn test_snapshot_memory_limit() {
+        let mut unbounded = conflicted_row(WfcConfig::default());
+        assert!(unbounded.resolve_conflicts().unwrap());
+        let peak = unbounded.peak_snapshot_memory();
+        assert!(peak > 0);
+        // 修复结束后不再持有任何快照
+        assert_eq!(unbounded.snapshot_bytes_in_use, 0);
+
+        let mut bounded = conflicted_row(WfcConfig {
+            max_snapshot_memory: Some(peak),
+            ..WfcConfig::default()
+        });
+        assert!(bounded.resolve_conflicts().unwrap());
+        assert!(bounded.peak_snapshot_memory() <= peak);
+
+        let mut starved = conflicted_row(WfcConfig {
+            max_snapshot_memory: Some(peak - 1),
+            ..WfcConfig::default()
+        });
+        assert!(matches!(
+            starved.resolve_conflicts(),
+            Err(WfcError::SnapshotMemoryExceeded { limit, required })
+                if limit == peak - 1 && required > limit
+        ));
+        assert!(starved.peak_snapshot_memory() < peak);
+    }
 
-        for cell_id in cell_ids {
-            let possibilities = self.wfc_data[&cell_id].possibilities.clone();
-            let entropy = self.calculate_entropy(&possibilities);
-            self.wfc_data.get_mut(&cell_id).unwrap().entropy = entropy;
+    #[test]
+    fn test_spatial_hash_tie_break() {
+        let first_pick = |seed: u64| {
+            let config = WfcConfig {
+                random_seed: Some(seed),
+                tie_break: TieBreak::SpatialHash,
+                ..WfcConfig::default()
+            };
+            let (mut manager, cells) = match_manager_with_config(4, 4, config);
+            for (y, row) in cells.iter().enumerate() {
+                for (x, &cell) in row.iter().enumerate() {
+                    manager
+                        .grid
+                        .set_cell_coords(cell, vec![x as i32, y as i32])
+                        .unwrap();
+                }
+            }
+            (manager.find_min_entropy_cell().unwrap(), cells[0][0])
+        };
+
+        // 相同种子下选择可复现
+        for seed in 0..8 {
+            assert_eq!(first_pick(seed).0, first_pick(seed).0);
         }
 
-        Ok(())
+        // 不同种子下首个选择并不总是最小ID的单元格
+        assert!((0..8).any(|seed| {
+            let (picked, lowest) = first_pick(seed);
+            picked != lowest
+        }));
     }
 
-    // ==========================================================================
-    // 分层冲突修复系统 - 对应原C++的冲突处理方法
-    // ==========================================================================
+    #[test]
+    fn test_seeded_runs_are_reproducible() {
+        let outcome = |manager: &mut WfcManager<i32>, cells: &[Vec<CellId>]| {
+            let result = manager.run();
+            let tiles: Vec<Vec<TileId>> = cells
+                .iter()
+                .flatten()
+                .map(|cell| manager.wfc_data[cell].possibilities())
+                .collect();
+            (result, tiles)
+        };
 
-    /// 解决所有冲突，使用统一的分层修复方法，对应C++的resolveConflicts()
-    pub fn resolve_conflicts(&mut self) -> Result<bool, WfcError> {
-        let conflict_cells = self.collect_conflict_cells();
+        let config = WfcConfig::builder()
+            .seed(2024)
+            .tie_break(TieBreak::SpatialHash)
+            .build();
+        let (mut first, cells) = match_manager_with_config(5, 5, config.clone());
+        let (mut second, _) = match_manager_with_config(5, 5, config);
+        let expected = outcome(&mut first, &cells);
+        assert_eq!(outcome(&mut second, &cells), expected);
+
+        // set_seed之后与直接使用该种子创建的管理器结果相同
+        let other = WfcConfig::builder()
+            .seed(1)
+            .tie_break(TieBreak::SpatialHash)
+            .build();
+        let (grid, _) = build_orthogonal_grid(5, 5);
+        let mut reseeded =
+            WfcManager::with_config(grid, Box::new(MatchTileSet::new()), other).unwrap();
+        reseeded.set_seed(2024);
+        reseeded.initialize_with(&mut DefaultInitializer).unwrap();
+        assert_eq!(reseeded.seed, 2024);
+        assert_eq!(outcome(&mut reseeded, &cells), expected);
+    }
 
-        if conflict_cells.is_empty() {
-            return Ok(true);
+    #[test]
+    fn test_lowest_id_tie_break() {
+        // 初始状态下所有单元格熵值相同，首个选择总是索引最小的单元格
+        for _ in 0..8 {
+            let (mut manager, cells) = match_manager(4, 4);
+            assert_eq!(manager.config.tie_break, TieBreak::LowestId);
+            assert_eq!(manager.find_min_entropy_cell().unwrap(), cells[0][0]);
         }
 
-        // 使用分层回溯解决所有冲突
-        self.layered_backtrack_resolution(conflict_cells)
+        // 默认配置下相同种子的完整运行结果一致
+        let run = || {
+            let (mut manager, cells) =
+                match_manager_with_config(5, 5, WfcConfig::builder().seed(11).build());
+            let result = manager.run();
+            let tiles: Vec<Vec<TileId>> = cells
+                .iter()
+                .flatten()
+                .map(|cell| manager.wfc_data[cell].possibilities())
+                .collect();
+            (result, tiles)
+        };
+        let expected = run();
+        for _ in 0..4 {
+            assert_eq!(run(), expected);
+        }
     }
 
-    /// 收集所有冲突单元格
-    fn collect_conflict_cells(&self) -> Vec<CellId> {
-        self.wfc_data
-            .iter()
-            .filter(|(_, data)| data.state == CellState::Conflict)
-            .map(|(&cell_id, _)| cell_id)
-            .collect()
+    #[test]
+    fn test_entropy_heap_matches_linear_scan() {
+        for tie_break in [TieBreak::LowestId, TieBreak::SpatialHash] {
+            for seed in 0..6 {
+                let config = WfcConfig::builder().seed(seed).tie_break(tie_break).build();
+                let (mut manager, _) = match_manager_with_config(6, 6, config);
+
+                // 每次坍塌前优先队列与线性扫描选出同一个单元格
+                while let Ok(expected) = manager.find_min_entropy_cell_linear() {
+                    assert_eq!(manager.find_min_entropy_cell().unwrap(), expected);
+                    manager.collapse().unwrap();
+                }
+                assert!(matches!(
+                    manager.find_min_entropy_cell(),
+                    Err(WfcError::NoUncollapsedCells)
+                ));
+
+                // 分层修复会批量恢复单元格，之后两者仍然一致
+                if manager.has_conflicts() {
+                    let _ = manager.resolve_conflicts();
+                    if let Ok(expected) = manager.find_min_entropy_cell_linear() {
+                        assert_eq!(manager.find_min_entropy_cell().unwrap(), expected);
+                    }
+                }
+            }
+        }
     }
 
-    /// 分层回溯解决，对应C++的resolveConflictsCell()
-    ///
-    /// 这是WFC系统的核心冲突修复机制，通过分层回溯来解决冲突。
-    /// 不同于传统WFC的过程性回溯，这里的回溯是专门为解决冲突层而设计的。
-    fn layered_backtrack_resolution(
-        &mut self,
-        conflict_cells: Vec<CellId>,
-    ) -> Result<bool, WfcError> {
-        let mut layers = vec![conflict_cells];
-        self.resolve_conflicts_recursive(&mut layers, 0)
+    #[test]
+    fn test_backtrack_strategy() {
+        // 只有拐角瓷砖的管道瓷砖集，贪心坍塌很容易走进死胡同
+        let corner_tiles = || {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            tile_set
+                .tiles_mut()
+                .add_tile_with_rotations(vec![1, 1, 0, 0], 1);
+            Box::new(tile_set)
+        };
+        let run = |seed: u64, strategy: ConflictStrategy| {
+            let config = WfcConfig::builder()
+                .seed(seed)
+                .tie_break(TieBreak::SpatialHash)
+                .conflict_strategy(strategy)
+                .build();
+            let (grid, cells) = build_orthogonal_grid(5, 5);
+            let mut manager = WfcManager::with_config(grid, corner_tiles(), config).unwrap();
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            let result = manager.run();
+            (manager, cells, result)
+        };
+
+        let mut layered_failures = 0;
+        for seed in 0..32 {
+            if run(seed, ConflictStrategy::LayeredRepair).2.is_err() {
+                layered_failures += 1;
+            }
+
+            // 回溯策略在所有种子下都得到一致的完整结果
+            let (manager, cells, result) = run(seed, ConflictStrategy::Backtrack);
+            assert_eq!(result, Ok(()), "seed {}", seed);
+            for &cell in cells.iter().flatten() {
+                let data = &manager.wfc_data[&cell];
+                assert_eq!(data.state, CellState::Collapsed);
+                assert!(manager.is_consistent_with_real_neighbors(cell, data));
+            }
+            assert!(manager.decision_stack.is_empty());
+            assert_eq!(manager.snapshot_bytes_in_use, 0);
+        }
+        assert!(layered_failures > 0);
     }
 
-    /// 递归冲突解决 - 分层修复的核心算法
-    ///
-    /// 这个方法实现了分层冲突修复的递归逻辑，通过逐层扩展和回溯来解决冲突。
-    /// 注意：这里的"回溯"是为冲突修复设计的，不同于传统WFC算法的过程性回溯。
-    fn resolve_conflicts_recursive(
-        &mut self,
-        layers: &mut Vec<Vec<CellId>>,
-        depth: usize,
-    ) -> Result<bool, WfcError> {
-        if depth >= self.config.max_recursion_depth {
-            return Ok(false);
+    #[test]
+    fn test_bitset_matches_vec_results() {
+        // 基准数据由可能性使用Vec<TileId>保存时的实现生成
+        let pipes = [
+            vec![
+                0, 0, 1, 1, 0, 5, 10, 6, 9, 3, 5, 3, 4, 8, 3, 0, 9, 6, 0, 5, 10, 2, 8, 8, 6, 1, 1,
+                0, 5, 10, 7, 1, 4, 2, 8, 3,
+            ],
+            vec![
+                10, 8, 7, 0, 0, 9, 9, 6, 1, 0, 0, 4, 9, 8, 3, 5, 2, 6, 3, 0, 0, 1, 0, 1, 0, 5, 2,
+                8, 6, 4, 5, 8, 6, 5, 3, 0,
+            ],
+            vec![
+                3, 0, 0, 9, 6, 5, 2, 6, 5, 8, 3, 1, 5, 8, 3, 5, 2, 8, 4, 2, 6, 9, 6, 0, 6, 5, 8, 8,
+                3, 5, 8, 8, 6, 5, 10, 3,
+            ],
+        ];
+        let corners = [
+            (
+                vec![
+                    2, 3, 2, 0, 2, 0, 1, 0, 2, 0, 3, 2, 3, 1, 3, 0, 1, 0, 2, 0, 2, 3, 2, 0, 2,
+                ],
+                163,
+            ),
+            (
+                vec![
+                    0, 1, 0, 2, 3, 2, 3, 2, 0, 1, 0, 1, 0, 2, 3, 3, 2, 3, 1, 0, 0, 1, 0, 2, 3,
+                ],
+                170,
+            ),
+            (
+                vec![
+                    0, 2, 0, 2, 3, 2, 0, 2, 0, 1, 1, 3, 1, 3, 2, 2, 0, 2, 0, 1, 0, 2, 0, 2, 3,
+                ],
+                164,
+            ),
+        ];
+        type PipeTiles = AdjacencyTileSet<i32, Direction4Full>;
+        let run = |tile_set: PipeTiles, size: usize, config: WfcConfig| {
+            let (grid, cells) = build_orthogonal_grid(size, size);
+            let mut manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            manager.run().unwrap();
+            let tiles: Vec<TileId> = cells
+                .iter()
+                .flatten()
+                .map(|&cell| manager.get_collapsed_cell_tile(cell).unwrap())
+                .collect();
+            (tiles, manager.propagation_steps())
+        };
+
+        for (seed, expected) in pipes.iter().enumerate() {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            tile_set.add_tile(vec![0, 0, 0, 0], 3);
+            tile_set
+                .tiles_mut()
+                .add_tile_with_rotations(vec![1, 0, 1, 0], 2);
+            tile_set
+                .tiles_mut()
+                .add_tile_with_rotations(vec![1, 1, 0, 0], 2);
+            tile_set
+                .tiles_mut()
+                .add_tile_with_rotations(vec![1, 1, 1, 0], 1);
+            let config = WfcConfig::builder()
+                .seed(seed as u64)
+                .conflict_strategy(ConflictStrategy::Backtrack)
+                .build();
+            let (tiles, _) = run(tile_set, 6, config);
+            assert_eq!(&tiles, expected, "seed {}", seed);
         }
 
-        // 从外层到内层解决冲突
-        for layer_idx in (0..layers.len()).rev() {
-            for &cell in &layers[layer_idx].clone() {
-                self.recover_cell_possibilities(cell, layers)?;
-            }
+        // 只有拐角瓷砖时会触发回溯，传播步数也与原实现相同
+        for (seed, (expected, steps)) in corners.iter().enumerate() {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            tile_set
+                .tiles_mut()
+                .add_tile_with_rotations(vec![1, 1, 0, 0], 1);
+            let config = WfcConfig::builder()
+                .seed(seed as u64)
+                .tie_break(TieBreak::SpatialHash)
+                .conflict_strategy(ConflictStrategy::Backtrack)
+                .build();
+            assert_eq!(
+                run(tile_set, 5, config),
+                (expected.clone(), *steps),
+                "seed {}",
+                seed
+            );
         }
+    }
 
-        // 尝试获取解决方案
-        let all_cells: Vec<CellId> = layers.iter().flatten().copied().collect();
-        if self.backtrack_solution(&all_cells, 0)? {
-            return Ok(true);
+    #[test]
+    fn test_live_random_matches_weights() {
+        let weighted_run = |seed: u64| {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            for weight in [1, 3, 6] {
+                tile_set.add_tile(vec![0, 0, 0, 0], weight);
+            }
+            let config = WfcConfig::builder().seed(seed).live_random(true).build();
+            let (grid, _) = build_orthogonal_grid(50, 40);
+            let mut manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            manager.run().unwrap();
+            manager.collapsed_tiles()
+        };
+
+        let tiles = weighted_run(11);
+        assert_eq!(tiles, weighted_run(11));
+
+        // 所有瓷砖彼此兼容，每个单元格独立按1:3:6的权重抽样
+        let mut counts = [0usize; 3];
+        for &tile in tiles.values() {
+            counts[tile] += 1;
+        }
+        let total = counts.iter().sum::<usize>() as f64;
+        for (count, expected) in counts.into_iter().zip([0.1, 0.3, 0.6]) {
+            let frequency = count as f64 / total;
+            assert!(
+                (frequency - expected).abs() < 0.03,
+                "frequency {} deviates from weight share {}",
+                frequency,
+                expected
+            );
         }
+    }
 
-        // 如果失败，扩展到下一层
-        if depth < self.config.max_recursion_depth - 1 {
-            let next_layer = self.build_next_layer(&layers[depth])?;
-            if !next_layer.is_empty() {
-                layers.push(next_layer);
-                return self.resolve_conflicts_recursive(layers, depth + 1);
+    #[test]
+    fn test_cell_weight_multiplier() {
+        let uniform_manager = |seed: u64| {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            for _ in 0..3 {
+                tile_set.add_tile(vec![0, 0, 0, 0], 1);
             }
-        }
+            let config = WfcConfig::builder().seed(seed).build();
+            let (grid, cells) = build_orthogonal_grid(3, 3);
+            let manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+            (manager, cells[1][1])
+        };
+        let run = |seed: u64, factor: f64| {
+            let (mut manager, center) = uniform_manager(seed);
+            manager
+                .set_cell_weight_multiplier(center, 2, factor)
+                .unwrap();
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            manager.run().unwrap();
+            manager.get_collapsed_cell_tile(center).unwrap()
+        };
 
-        Ok(false)
+        // 倍数很大时，中心单元格几乎总是坍塌为该瓷砖
+        let boosted = (0..50).filter(|&seed| run(seed, 1000.0) == 2).count();
+        let baseline = (0..50).filter(|&seed| run(seed, 1.0) == 2).count();
+        assert!(boosted >= 48, "boosted tile chosen {} times", boosted);
+        assert!(baseline < boosted);
+
+        // 倍数也作用于熵值，恢复为1.0后与未设置时相同
+        let (mut manager, center) = uniform_manager(0);
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        let entropy = manager.wfc_data[&center].entropy;
+        manager
+            .set_cell_weight_multiplier(center, 2, 1000.0)
+            .unwrap();
+        assert!(manager.wfc_data[&center].entropy < entropy);
+        manager.set_cell_weight_multiplier(center, 2, 1.0).unwrap();
+        assert_eq!(manager.wfc_data[&center].entropy, entropy);
+        assert!(manager.weight_multipliers.is_empty());
+
+        assert_eq!(
+            manager.set_cell_weight_multiplier(center, 99, 2.0),
+            Err(WfcError::TileNotFound(99))
+        );
+        assert_eq!(
+            manager.set_cell_weight_multiplier(center, 2, -1.0),
+            Err(WfcError::InvalidWeightMultiplier(-1.0))
+        );
+        assert!(matches!(
+            manager.set_cell_weight_multiplier(center, 2, f64::NAN),
+            Err(WfcError::InvalidWeightMultiplier(factor)) if factor.is_nan()
+        ));
     }
 
-    /// 恢复单元格可能性，用于分层修复
-    fn recover_cell_possibilities(
-        &mut self,
-        cell_id: CellId,
-        layers: &[Vec<CellId>],
-    ) -> Result<(), WfcError> {
-        // 将layers转换为Vec<Vec<CellId>>用于find_in_2d_vector
-        let layers_vec: Vec<Vec<CellId>> = layers.to_vec();
+    #[test]
+    fn test_entropy_noise() {
+        let pipes = || {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            tile_set.add_tile(vec![0, 0, 0, 0], 3);
+            tile_set
+                .tiles_mut()
+                .add_tile_with_rotations(vec![1, 0, 1, 0], 2);
+            tile_set
+                .tiles_mut()
+                .add_tile_with_rotations(vec![1, 1, 0, 0], 2);
+            Box::new(tile_set)
+        };
+        let run = |config: WfcConfig| {
+            let (grid, cells) = build_orthogonal_grid(6, 6);
+            let mut manager = WfcManager::with_config(grid, pipes(), config).unwrap();
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            manager.run().unwrap();
+            for &cell in cells.iter().flatten() {
+                let data = &manager.wfc_data[&cell];
+                assert_eq!(data.state, CellState::Collapsed);
+                assert!(manager.is_consistent_with_real_neighbors(cell, data));
+            }
+            let tiles: Vec<TileId> = cells
+                .iter()
+                .flatten()
+                .map(|&cell| manager.get_collapsed_cell_tile(cell).unwrap())
+                .collect();
+            (tiles, manager.propagation_steps())
+        };
+        let config = |noise: f64| {
+            WfcConfig::builder()
+                .seed(7)
+                .conflict_strategy(ConflictStrategy::Backtrack)
+                .entropy_noise(noise)
+                .build()
+        };
 
-        let (cx, _) = find_in_2d_vector(&layers_vec, &cell_id).unwrap_or((layers.len(), 0));
-        let neighbors = self.grid.get_neighbors(cell_id);
-        let mut neighbor_possibilities = Vec::new();
+        // 噪声为0时与未设置噪声的配置结果完全相同
+        let default_config = WfcConfig::builder()
+            .seed(7)
+            .conflict_strategy(ConflictStrategy::Backtrack)
+            .build();
+        assert_eq!(run(config(0.0)), run(default_config));
+
+        // 不同噪声幅度都得到完整有效的结果，且同一噪声可以复现
+        let small = run(config(0.01));
+        let large = run(config(0.5));
+        assert_eq!(run(config(0.01)), small);
+        assert_eq!(run(config(0.5)), large);
+
+        // 噪声改变了熵值本身
+        let (grid, cells) = build_orthogonal_grid(3, 3);
+        let mut manager = WfcManager::with_config(grid, pipes(), config(0.5)).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        let entropies: HashSet<u64> = cells
+            .iter()
+            .flatten()
+            .map(|cell| manager.wfc_data[cell].entropy.to_bits())
+            .collect();
+        assert_eq!(entropies.len(), 9);
+    }
 
-        for neighbor in neighbors {
-            let (nx, _) = find_in_2d_vector(&layers_vec, &neighbor).unwrap_or((layers.len(), 0));
-            if nx >= cx {
-                if let Some(neighbor_data) = self.wfc_data.get(&neighbor) {
-                    neighbor_possibilities.push(neighbor_data.possibilities.clone());
-                } else {
-                    neighbor_possibilities.push(vec![]);
-                }
-            } else {
-                neighbor_possibilities.push(vec![]);
-            }
+    #[test]
+    fn test_entropy_cache() {
+        let (mut manager, _) = match_manager(2, 2);
+        manager.entropy_cache.clear();
+        manager.entropy_cache_hits = 0;
+
+        // 相同集合（与插入顺序无关）得到逐位相同的熵值
+        let tiles: FixedBitSet = [0, 1, 3].into_iter().collect();
+        let first = manager.calculate_entropy(&tiles);
+        let second = manager.calculate_entropy(&[3, 0, 1].into_iter().collect());
+        assert_eq!(first.to_bits(), second.to_bits());
+        assert_eq!(first.to_bits(), manager.compute_entropy(&tiles).to_bits());
+        assert_eq!(manager.entropy_cache_hits, 1);
+        assert_eq!(manager.entropy_cache.len(), 1);
+
+        // 初始化时所有单元格共享全集，熵值只计算一次，其余都命中缓存
+        let config = WfcConfig::builder().seed(5).build();
+        let (mut manager, _) = match_manager_with_config(12, 12, config);
+        manager.entropy_cache_hits = 0;
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        assert_eq!(manager.entropy_cache.len(), 1);
+        assert_eq!(manager.entropy_cache_hits, manager.wfc_data.len() - 1);
+
+        // 传播时熵值按权重和增量更新，不再经过缓存
+        let _ = manager.run();
+        assert_eq!(manager.entropy_cache.len(), 1);
+    }
+
+    #[test]
+    fn test_incremental_entropy_matches_full_computation() {
+        let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+        for weight in [1, 2, 3, 5, 8, 13, 0, 4] {
+            tile_set.add_tile(vec![0, 0, 0, 0], weight);
         }
+        let (grid, cells) = build_orthogonal_grid(2, 1);
+        let mut manager = WfcManager::new(grid, Box::new(tile_set)).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        let (plain, weighted) = (cells[0][0], cells[0][1]);
+        manager
+            .set_cell_weight_multiplier(weighted, 2, 0.3)
+            .unwrap();
+
+        let mut rng = StdRng::seed_from_u64(21);
+        for cell in [plain, weighted] {
+            for _ in 0..20 {
+                let mut possibilities = manager.wfc_data[&cell].possibilities.clone();
+                let remaining: Vec<TileId> = possibilities.ones().collect();
+                if remaining.is_empty() {
+                    break;
+                }
+                let count = rng.random_range(1..=remaining.len().min(2));
+                for _ in 0..count {
+                    possibilities.remove(remaining[rng.random_range(0..remaining.len())]);
+                }
 
-        // 根据邻居约束恢复可能性
-        let mut new_possibilities = Vec::new();
-        for tile_id in self.tile_set.get_all_tile_ids() {
-            if self
-                .tile_set
-                .judge_possibility(&neighbor_possibilities, tile_id)
-            {
-                new_possibilities.push(tile_id);
+                let incremental = manager.narrow_possibilities(cell, possibilities.clone());
+                let expected = if cell == plain {
+                    manager.calculate_entropy(&possibilities)
+                } else {
+                    manager.cell_entropy(cell, &possibilities)
+                };
+                assert!(
+                    (incremental - expected).abs() < 1e-9,
+                    "{:?}: {} != {}",
+                    possibilities.ones().collect::<Vec<_>>(),
+                    incremental,
+                    expected
+                );
             }
+            assert!(manager.wfc_data[&cell].possibilities.is_clear());
         }
+    }
 
-        // 计算新的熵值
-        let new_entropy = self.calculate_entropy(&new_possibilities);
+    // 两种边完全相同的瓷砖，不会产生冲突
+    fn conflict_free_manager(width: usize, height: usize) -> (WfcManager<i32>, Vec<Vec<CellId>>) {
+        let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+        tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        tile_set.add_tile(vec![0, 0, 0, 0], 2);
+        let (grid, cells) = build_orthogonal_grid(width, height);
+        let mut manager = WfcManager::new(grid, Box::new(tile_set)).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        (manager, cells)
+    }
 
-        // 确定新状态
-        let new_state = if new_possibilities.is_empty() {
-            CellState::Conflict
-        } else {
-            CellState::Uncollapsed
+    #[test]
+    fn test_set_weight_changes_entropy() {
+        let entropy_with_weight = |weight: i32| {
+            let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+            let grass = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+            tile_set.add_tile(vec![0, 0, 0, 0], 1);
+            tile_set.tiles_mut().set_weight(grass, weight).unwrap();
+
+            let (grid, _) = build_orthogonal_grid(1, 1);
+            let mut manager = WfcManager::new(grid, Box::new(tile_set)).unwrap();
+            let all_tiles: FixedBitSet = manager.get_all_tile_ids().into_iter().collect();
+            manager.calculate_entropy(&all_tiles)
         };
 
-        // 最后更新单元格数据
-        let cell_data = self
-            .wfc_data
-            .get_mut(&cell_id)
-            .ok_or(WfcError::CellNotFound(cell_id))?;
+        // 等权重时两种瓷砖的熵为1比特，权重3:1时熵降低
+        assert!((entropy_with_weight(1) - 1.0).abs() < 1e-9);
+        let skewed = -(0.75f64 * 0.75f64.log2() + 0.25 * 0.25f64.log2());
+        assert!((entropy_with_weight(3) - skewed).abs() < 1e-9);
 
-        cell_data.possibilities = new_possibilities;
-        cell_data.entropy = new_entropy;
-        cell_data.state = new_state;
+        let mut tile_set = TileSet::new();
+        tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        assert_eq!(tile_set.set_weight(1, 2), Err(GridError::IndexOutOfBounds));
+        tile_set.remove_tile(0);
+        assert_eq!(tile_set.set_weight(0, 2), Err(GridError::IndexOutOfBounds));
+    }
 
-        Ok(())
+    #[test]
+    fn test_weight_change_invalidates_entropy_cache() {
+        let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+        let grass = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        let sand = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        let water = tile_set.add_tile(vec![0, 0, 0, 0], 2);
+        let (grid, cells) = build_orthogonal_grid(1, 1);
+        let cell = cells[0][0];
+        let mut manager = WfcManager::new(grid, Box::new(tile_set)).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        assert!((manager.get_entropy(cell).unwrap() - 1.5).abs() < 1e-9);
+
+        // 修改权重后单元格立即按新权重计算熵值，而不是读取旧缓存
+        manager.set_tile_weight(grass, 6).unwrap();
+        let entropy = |weights: &[f64]| {
+            let total: f64 = weights.iter().sum();
+            -weights
+                .iter()
+                .map(|weight| weight / total * (weight / total).log2())
+                .sum::<f64>()
+        };
+        assert!((manager.get_entropy(cell).unwrap() - entropy(&[6.0, 1.0, 2.0])).abs() < 1e-9);
+
+        // 单元格上的权重和同步更新，增量计算的熵值同样使用新权重
+        manager.ban_tile(cell, water).unwrap();
+        assert!((manager.get_entropy(cell).unwrap() - entropy(&[6.0, 1.0])).abs() < 1e-9);
+        assert_eq!(manager.get_possibilities(cell).unwrap(), vec![grass, sand]);
+
+        assert_eq!(
+            manager.set_tile_weight(99, 1),
+            Err(WfcError::TileNotFound(99))
+        );
+        let (mut fixed, _) = match_manager(1, 1);
+        assert_eq!(
+            fixed.set_tile_weight(0, 1),
+            Err(WfcError::TileSetNotMutable)
+        );
     }
 
-    /// 构建下一层
-    fn build_next_layer(&self, current_layer: &[CellId]) -> Result<Vec<CellId>, WfcError> {
-        let mut next_layer = Vec::new();
+    // 两种瓷砖的东边与任何瓷砖的西边都不匹配，宽度大于1时无解
+    fn unsatisfiable_manager(config: WfcConfig) -> WfcManager<i32> {
+        let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+        tile_set.add_tile(vec![0, 0, 1, 0], 1);
+        tile_set.add_tile(vec![0, 0, 1, 0], 2);
+        let (grid, _) = build_orthogonal_grid(4, 3);
+        let mut manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        manager
+    }
 
-        for &cell in current_layer {
-            let neighbors = self.grid.get_neighbors(cell);
-            for neighbor in neighbors {
-                if let Some(neighbor_data) = self.wfc_data.get(&neighbor) {
-                    if neighbor_data.state == CellState::Collapsed
-                        && !next_layer.contains(&neighbor)
-                    {
-                        next_layer.push(neighbor);
-                    }
-                }
-            }
+    #[test]
+    fn test_unsatisfiable_tile_set_errors() {
+        let config = WfcConfig::builder().seed(1).max_conflict_retries(2).build();
+        let mut manager = unsatisfiable_manager(config.clone());
+        match manager.run() {
+            Err(WfcError::UnresolvableConflicts(report)) => assert!(!report.is_empty()),
+            other => panic!("unexpected result: {:?}", other),
         }
 
-        Ok(next_layer)
+        // 逐步运行时，修复最多失败max_conflict_retries次后报错
+        let mut manager = unsatisfiable_manager(config);
+        let mut failures = 0;
+        let error = loop {
+            match manager.run_step() {
+                Ok(StepResult::ConflictResolutionFailed) => failures += 1,
+                Ok(StepResult::Complete) => panic!("unsatisfiable tile set completed"),
+                Ok(_) => {}
+                Err(error) => break error,
+            }
+        };
+        assert!(matches!(error, WfcError::UnresolvableConflicts(_)));
+        assert_eq!(failures, 2);
     }
 
-    /// 回溯求解算法，对应C++的retrospectiveGetSolution()
-    ///
-    /// 这是分层修复过程中使用的局部回溯算法，用于在冲突修复时寻找可行的瓷砖组合。
-    /// 注意：这不是传统WFC的全局回溯，而是针对特定冲突层的局部求解。
-    fn backtrack_solution(&mut self, cells: &[CellId], index: usize) -> Result<bool, WfcError> {
-        if index >= cells.len() {
-            return Ok(true);
+    #[test]
+    fn test_run_with_observer() {
+        let (mut manager, _) = conflict_free_manager(4, 3);
+
+        let mut events = Vec::new();
+        manager
+            .run_with_observer(|progress| events.push(progress.clone()))
+            .unwrap();
+
+        let total = manager.get_grid().get_cells_count();
+        assert_eq!(events.len(), total);
+        let collapsed: HashSet<CellId> = events
+            .iter()
+            .map(|progress| progress.collapsed.unwrap().0)
+            .collect();
+        assert_eq!(collapsed.len(), total);
+
+        // 进度单调递增，最后一次报告时全部完成
+        for (step, progress) in events.iter().enumerate() {
+            assert_eq!(progress.completed_count, step + 1);
+            assert_eq!(progress.total_cells, total);
         }
+    }
 
-        let cell_id = cells[index];
-        let cell_data = self
-            .wfc_data
-            .get(&cell_id)
-            .ok_or(WfcError::CellNotFound(cell_id))?;
+    #[test]
+    fn test_run_until_cancel() {
+        let (mut manager, cells) = conflict_free_manager(4, 4);
+
+        let mut collapsed = Vec::new();
+        let outcome = manager
+            .run_until(|progress| {
+                collapsed.push(progress.collapsed.unwrap().0);
+                collapsed.len() < 3
+            })
+            .unwrap();
+        assert_eq!(outcome, RunOutcome::Cancelled);
+        assert_eq!(manager.completed_count, 3);
+        assert!(!manager.is_complete());
+
+        // 中止后的状态仍然可以查询
+        for &cell in &collapsed {
+            assert_eq!(manager.get_cell_state(cell).unwrap(), CellState::Collapsed);
+            assert!(manager.get_collapsed_cell_tile(cell).is_ok());
+        }
+        let untouched = cells
+            .iter()
+            .flatten()
+            .find(|cell| !collapsed.contains(cell))
+            .unwrap();
+        assert_eq!(
+            manager.get_cell_state(*untouched).unwrap(),
+            CellState::Uncollapsed
+        );
 
-        if cell_data.possibilities.is_empty() {
-            return Ok(false);
+        // 继续运行直至完成
+        assert_eq!(manager.run_until(|_| true).unwrap(), RunOutcome::Completed);
+        assert!(manager.is_complete());
+    }
+
+    /// 按边标签判断的两瓷砖集："same"边两侧相同，"diff"边两侧不同
+    struct LabeledTileSet {
+        tiles: TileSet<i32>,
+    }
+
+    impl LabeledTileSet {
+        fn new() -> Self {
+            let mut tiles = TileSet::new();
+            tiles.add_tile(vec![], 1);
+            tiles.add_tile(vec![], 1);
+            Self { tiles }
         }
+    }
 
-        // 最后一个单元直接选择第一种可能性
-        if index == cells.len() - 1 {
-            let first_possibility = cell_data.possibilities[0];
-            self.set_tile_for_cell(cell_id, first_possibility)?;
-            return Ok(true);
+    impl TileSetVirtual<i32> for LabeledTileSet {
+        fn build_tile_set(&mut self) -> Result<(), GridError> {
+            Ok(())
         }
 
-        // 保存当前状态
-        let snapshot = self.create_snapshot();
+        fn judge_possibility(&self, _: &[Vec<TileId>], _: TileId) -> bool {
+            true
+        }
 
-        // 尝试每种可能性
-        for &possibility in &cell_data.possibilities.clone() {
-            if self.tile_is_compatible(possibility, cell_id)? {
-                // 设置瓷砖
-                self.set_tile_for_cell(cell_id, possibility)?;
+        fn uses_edge_labels(&self) -> bool {
+            true
+        }
 
-                // 递归处理下一个单元
-                if self.backtrack_solution(cells, index + 1)? {
-                    return Ok(true);
-                }
+        fn judge_possibility_labeled(
+            &self,
+            edge_labels: &[Option<&str>],
+            neighbor_possibilities: &[Vec<TileId>],
+            candidate: TileId,
+        ) -> bool {
+            edge_labels
+                .iter()
+                .zip(neighbor_possibilities)
+                .all(|(label, tiles)| match label {
+                    Some("same") => tiles.contains(&candidate),
+                    Some("diff") => tiles.iter().any(|&tile| tile != candidate),
+                    _ => true,
+                })
+        }
 
-                // 恢复状态
-                self.restore_snapshot(snapshot.clone())?;
-            }
+        fn get_tile(&self, tile_id: TileId) -> Option<&Tile<i32>> {
+            self.tiles.get_tile(tile_id)
         }
 
-        Ok(false)
-    }
+        fn get_tile_count(&self) -> usize {
+            self.tiles.get_tile_count()
+        }
 
-    /// 创建系统快照，对应C++的getSystem()
-    fn create_snapshot(&self) -> SystemSnapshot {
-        SystemSnapshot {
-            data: self.wfc_data.clone(),
-            completed_count: self.completed_count,
+        fn get_all_tile_ids(&self) -> Vec<TileId> {
+            self.tiles.get_all_tile_ids()
         }
     }
 
-    /// 恢复系统快照，对应C++的setSystem()
-    fn restore_snapshot(&mut self, snapshot: SystemSnapshot) -> Result<(), WfcError> {
-        self.wfc_data = snapshot.data;
-        self.completed_count = snapshot.completed_count;
-        Ok(())
+    #[test]
+    fn test_edge_labels_on_irregular_graph() {
+        // hub有5个邻居，spoke有3个邻居，每对邻居之间的两条边使用相同标签
+        let build = || {
+            let mut grid = GridSystem::new();
+            let cells: Vec<CellId> = (0..7).map(|i| grid.add_cell(Cell::with_id(i))).collect();
+            let mut connect = |a: usize, b: usize, label: &str| {
+                let edge = || GraphEdge::with_type(label.to_string());
+                grid.create_edge_with_data(cells[a], Some(cells[b]), edge())
+                    .unwrap();
+                grid.create_edge_with_data(cells[b], Some(cells[a]), edge())
+                    .unwrap();
+            };
+            connect(0, 1, "diff");
+            connect(0, 2, "same");
+            connect(0, 3, "same");
+            connect(0, 4, "diff");
+            connect(0, 5, "diff");
+            connect(1, 6, "same");
+            connect(1, 2, "diff");
+            (grid, cells)
+        };
+
+        for propagation in [PropagationMode::Naive, PropagationMode::ArcConsistency] {
+            let (grid, cells) = build();
+            let (hub, spoke) = (cells[0], cells[1]);
+            assert_eq!(grid.get_neighbors(hub).len(), 5);
+            assert_eq!(grid.get_neighbors(spoke).len(), 3);
+
+            let config = WfcConfig::builder().propagation(propagation).build();
+            let mut manager =
+                WfcManager::with_config(grid, Box::new(LabeledTileSet::new()), config).unwrap();
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            manager.pre_collapse(hub, 0).unwrap();
+
+            // 约束经由度数不同的单元格传播到整个图
+            let expected = [0, 1, 0, 0, 1, 1, 1];
+            for (&cell, &tile) in cells.iter().zip(&expected) {
+                assert_eq!(
+                    manager.get_possibilities(cell).unwrap(),
+                    vec![tile],
+                    "{:?}",
+                    propagation
+                );
+            }
+        }
     }
 
-    /// 检查是否有冲突
-    fn has_conflicts(&self) -> bool {
-        self.wfc_data
-            .values()
-            .any(|data| data.state == CellState::Conflict)
+    /// 统计`judge_possibility`调用次数的瓷砖集包装
+    struct CountingTileSet {
+        inner: MatchTileSet,
+        calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
     }
-}
 
-// =============================================================================
-// 测试模块
-// =============================================================================
+    impl TileSetVirtual<i32> for CountingTileSet {
+        fn build_tile_set(&mut self) -> Result<(), GridError> {
+            self.inner.build_tile_set()
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::tile_set::TileSet;
+        fn judge_possibility(
+            &self,
+            neighbor_possibilities: &[Vec<TileId>],
+            candidate: TileId,
+        ) -> bool {
+            self.calls
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            self.inner
+                .judge_possibility(neighbor_possibilities, candidate)
+        }
 
-    // 测试用的简单瓷砖集
-    struct TestTileSet {
-        tiles: TileSet<&'static str>,
+        fn get_tile(&self, tile_id: TileId) -> Option<&Tile<i32>> {
+            self.inner.get_tile(tile_id)
+        }
+
+        fn get_tile_count(&self) -> usize {
+            self.inner.get_tile_count()
+        }
+
+        fn get_all_tile_ids(&self) -> Vec<TileId> {
+            self.inner.get_all_tile_ids()
+        }
     }
 
-    impl TestTileSet {
-        pub fn new() -> Self {
-            let mut tiles = TileSet::new();
-            tiles.add_tile(vec!["A", "B", "C", "D"], 10);
-            tiles.add_tile(vec!["B", "A", "D", "C"], 15);
-            Self { tiles }
+    #[test]
+    fn test_arc_consistency_matches_naive() {
+        let run = |seed: u64, strategy: ConflictStrategy, mode: PropagationMode| {
+            let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            let tile_set = CountingTileSet {
+                inner: MatchTileSet::new(),
+                calls: calls.clone(),
+            };
+            let config = WfcConfig::builder()
+                .seed(seed)
+                .conflict_strategy(strategy)
+                .propagation(mode)
+                .build();
+            let (grid, _) = build_orthogonal_grid(12, 12);
+            let mut manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+            manager.initialize_with(&mut DefaultInitializer).unwrap();
+            let result = manager.run();
+            let calls = calls.load(std::sync::atomic::Ordering::Relaxed);
+            (result, manager.collapsed_tiles(), calls)
+        };
+
+        // 回溯会恢复快照，支持计数需要随之增量恢复
+        let (mut naive_calls, mut arc_calls) = (0, 0);
+        for strategy in [ConflictStrategy::LayeredRepair, ConflictStrategy::Backtrack] {
+            for seed in 0..6 {
+                let (naive_result, naive_tiles, calls) =
+                    run(seed, strategy, PropagationMode::Naive);
+                naive_calls += calls;
+                let (arc_result, arc_tiles, calls) =
+                    run(seed, strategy, PropagationMode::ArcConsistency);
+                arc_calls += calls;
+                assert_eq!(arc_result, naive_result, "seed {}", seed);
+                assert_eq!(arc_tiles, naive_tiles, "seed {}", seed);
+            }
         }
+        assert!(arc_calls < naive_calls, "{} >= {}", arc_calls, naive_calls);
     }
 
-    impl TileSetVirtual<&'static str> for TestTileSet {
+    /// 每次重新构建都多一个瓷砖的瓷砖集
+    struct GrowingTileSet {
+        inner: MatchTileSet,
+        builds: usize,
+    }
+
+    impl TileSetVirtual<i32> for GrowingTileSet {
         fn build_tile_set(&mut self) -> Result<(), GridError> {
-            // 已在new中构建
+            if self.builds > 0 {
+                self.inner.tiles.add_tile(vec![1, 1, 1, 1], 1);
+            }
+            self.builds += 1;
             Ok(())
         }
 
         fn judge_possibility(
             &self,
-            _neighbor_possibilities: &[Vec<TileId>],
-            _candidate: TileId,
+            neighbor_possibilities: &[Vec<TileId>],
+            candidate: TileId,
         ) -> bool {
-            // 简单返回true用于测试
-            true
+            self.inner
+                .judge_possibility(neighbor_possibilities, candidate)
         }
 
-        fn get_tile(&self, tile_id: TileId) -> Option<&Tile<&'static str>> {
-            self.tiles.get_tile(tile_id)
+        fn get_tile(&self, tile_id: TileId) -> Option<&Tile<i32>> {
+            self.inner.get_tile(tile_id)
         }
 
         fn get_tile_count(&self) -> usize {
-            self.tiles.get_tile_count()
+            self.inner.get_tile_count()
         }
 
         fn get_all_tile_ids(&self) -> Vec<TileId> {
-            self.tiles.get_all_tile_ids()
+            self.inner.get_all_tile_ids()
         }
     }
 
+    #[test]
+    fn test_reinitialize_rebuilds_support_counts() {
+        let tile_set = GrowingTileSet {
+            inner: MatchTileSet::new(),
+            builds: 0,
+        };
+        let config = WfcConfig::builder()
+            .propagation(PropagationMode::ArcConsistency)
+            .build();
+        let (grid, cells) = build_orthogonal_grid(3, 3);
+        let mut manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        manager.pre_collapse(cells[1][1], 3).unwrap();
+        assert_eq!(manager.get_possibilities(cells[1][2]).unwrap(), vec![2, 3]);
+
+        // 重新初始化后瓷砖数量变化，支持计数需要按新的瓷砖集重建
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        assert_eq!(manager.get_all_tile_ids().len(), 5);
+        manager.pre_collapse(cells[1][1], 4).unwrap();
+        assert_eq!(
+            manager.get_possibilities(cells[1][2]).unwrap(),
+            vec![2, 3, 4]
+        );
+    }
 
     #[test]
-    fn test_wfc_manager_creation() {
-        let grid = GridSystem::new();
-        let tile_set = Box::new(TestTileSet::new()) as Box<dyn TileSetVirtual<&'static str>>;
+    fn test_run_steps() {
+        let (mut manager, cells) = conflict_free_manager(4, 4);
+
+        assert_eq!(manager.run_steps(1).unwrap(), RunOutcome::BudgetExhausted);
+        assert_eq!(manager.completed_count, 1);
+        let collapsed = manager.collapsed_tiles();
+        assert_eq!(collapsed.len(), 1);
+        for &cell in cells.iter().flatten() {
+            if !collapsed.contains_key(&cell) {
+                assert_eq!(
+                    manager.get_cell_state(cell).unwrap(),
+                    CellState::Uncollapsed
+                );
+            }
+        }
 
-        let manager = WfcManager::new(grid, tile_set).unwrap();
-        assert_eq!(manager.completed_count, 0);
-        assert!(manager.is_complete()); // 空网格自动完成
+        assert_eq!(
+            manager.run_steps(usize::MAX).unwrap(),
+            RunOutcome::Completed
+        );
+        assert!(manager.is_complete());
+        assert_eq!(manager.run_steps(1).unwrap(), RunOutcome::Completed);
     }
 
     #[test]
-    fn test_wfc_states() {
-        assert_eq!(CellState::Uncollapsed, CellState::Uncollapsed);
-        assert_ne!(CellState::Uncollapsed, CellState::Collapsed);
+    fn test_into_parts() {
+        let (mut manager, _) = conflict_free_manager(4, 4);
+        manager.run().unwrap();
+        let cells_count = manager.get_grid().get_cells_count();
+
+        let (grid, tile_set) = manager.into_parts();
+        assert_eq!(grid.get_cells_count(), cells_count);
+        assert_eq!(tile_set.get_tile_count(), 2);
+
+        // 使用取回的网格和瓷砖集以不同配置重新运行
+        let config = WfcConfig::builder()
+            .observation_heuristic(ObservationHeuristic::Scanline)
+            .build();
+        let mut rerun = WfcManager::with_config(grid, tile_set, config).unwrap();
+        rerun.initialize_with(&mut DefaultInitializer).unwrap();
+        assert!(!rerun.is_complete());
+        rerun.run().unwrap();
+        assert!(rerun.is_complete());
+    }
 
-        let data = CellWfcData::new(12345, vec![0, 1]);
-        assert_eq!(data.state, CellState::Uncollapsed);
-        assert_eq!(data.rand_seed, 12345);
-        assert_eq!(data.possibilities.len(), 2);
+    #[test]
+    fn test_run_region() {
+        let config = WfcConfig::builder().seed(13).build();
+        let (mut manager, cells) = match_manager_with_config(4, 4, config);
+        manager.run().unwrap();
+        let before = manager.collapsed_tiles();
+
+        let region: HashSet<CellId> = cells[1..3]
+            .iter()
+            .flat_map(|row| row[1..3].iter().copied())
+            .collect();
+        manager.set_seed(14);
+        manager.run_region(&region).unwrap();
+        assert!(manager.is_complete());
+
+        // 区域外的单元格保持不变，区域内的新瓷砖与所有邻居兼容
+        let after = manager.collapsed_tiles();
+        for (cell, tile) in &before {
+            if !region.contains(cell) {
+                assert_eq!(after[cell], *tile);
+            }
+        }
+        for &cell in &region {
+            assert!(manager.tile_is_compatible(after[&cell], cell).unwrap());
+        }
+
+        // 区域外未坍塌的单元格同样不受传播影响
+        let (mut manager, cells) = match_manager(4, 4);
+        let all_tiles = manager.get_all_tile_ids();
+        manager.run_region(&region).unwrap();
+        for (y, row) in cells.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                let state = manager.get_cell_state(cell).unwrap();
+                if (1..3).contains(&x) && (1..3).contains(&y) {
+                    assert_eq!(state, CellState::Collapsed);
+                } else {
+                    assert_eq!(state, CellState::Uncollapsed);
+                    assert_eq!(manager.get_possibilities(cell).unwrap(), all_tiles);
+                }
+            }
+        }
+
+        // 之后仍可正常完成剩余部分
+        manager.run().unwrap();
+        assert!(manager.is_complete());
+        assert_eq!(
+            manager.run_region(&[CellId::new(1000)].into_iter().collect()),
+            Err(WfcError::CellNotFound(CellId::new(1000)))
+        );
+    }
+
+    #[test]
+    fn test_repin() {
+        // 草地、沙地、水面：只有相邻高度的边可以相接
+        let mut tile_set =
+            AdjacencyTileSet::<i32, Direction4Full>::with_compatibility(|a, b| (a - b).abs() <= 1);
+        let grass = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+        let sand = tile_set.add_tile(vec![1, 1, 1, 1], 1);
+        let water = tile_set.add_tile(vec![2, 2, 2, 2], 1);
+        let (grid, cells) = build_orthogonal_grid(7, 7);
+        let mut manager = WfcManager::new(grid, Box::new(tile_set)).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        let all_grass: Vec<(CellId, TileId)> =
+            cells.iter().flatten().map(|&cell| (cell, grass)).collect();
+        manager.pre_collapse_many(&all_grass).unwrap();
+        manager.run().unwrap();
+
+        // 水面不能直接挨着草地，四个邻居被重新生成为沙地，其余单元格不变
+        let center = cells[3][3];
+        manager.repin(center, water).unwrap();
+        assert!(manager.is_complete());
+        assert_eq!(manager.get_collapsed_cell_tile(center).unwrap(), water);
+        for (y, row) in cells.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                let tile = manager.get_collapsed_cell_tile(cell).unwrap();
+                match x.abs_diff(3) + y.abs_diff(3) {
+                    0 => {}
+                    1 => assert_eq!(tile, sand),
+                    _ => {
+                        assert_eq!(tile, grass);
+                        continue;
+                    }
+                }
+                assert!(manager.tile_is_compatible(tile, cell).unwrap());
+            }
+        }
+
+        // 与周围相容时只改动目标单元格
+        manager.repin(cells[0][0], sand).unwrap();
+        assert_eq!(manager.get_collapsed_cell_tile(cells[0][0]).unwrap(), sand);
+        assert_eq!(manager.get_collapsed_cell_tile(cells[0][1]).unwrap(), grass);
+        assert_eq!(
+            manager.repin(center, 10),
+            Err(WfcError::InvalidTileChoice {
+                cell: center,
+                tile: 10
+            })
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_restore() {
+        let config = WfcConfig::builder().seed(9).build();
+        let (mut manager, _) = match_manager_with_config(4, 4, config);
+        let collapse_steps = |manager: &mut WfcManager<i32>, count: usize| {
+            let mut steps = 0;
+            manager
+                .run_until(|_| {
+                    steps += 1;
+                    steps < count
+                })
+                .unwrap();
+        };
+
+        collapse_steps(&mut manager, 2);
+        let checkpoint = manager.checkpoint();
+        let expected: Vec<(CellId, CellState, Vec<TileId>, u64)> = {
+            let mut cells: Vec<_> = manager
+                .wfc_data
+                .iter()
+                .map(|(&cell, data)| {
+                    let entropy = data.entropy.to_bits();
+                    (cell, data.state, data.possibilities(), entropy)
+                })
+                .collect();
+            cells.sort_by_key(|entry| entry.0);
+            cells
+        };
+        let completed = manager.completed_count;
+
+        collapse_steps(&mut manager, 3);
+        assert_eq!(manager.completed_count, completed + 3);
+
+        manager.restore(checkpoint).unwrap();
+        assert_eq!(manager.completed_count, completed);
+        for (cell, state, possibilities, entropy) in expected {
+            let data = &manager.wfc_data[&cell];
+            assert_eq!(data.state, state);
+            assert_eq!(data.possibilities(), possibilities);
+            assert_eq!(data.entropy.to_bits(), entropy);
+        }
+
+        // 其他网格的快照无法恢复
+        let (other, _) = match_manager(3, 3);
+        assert_eq!(
+            manager.restore(other.checkpoint()).unwrap_err(),
+            WfcError::InconsistentState
+        );
+        assert_eq!(manager.completed_count, completed);
+    }
+
+    #[test]
+    fn test_warm_start() {
+        // 空间哈希打破并列，保证相同种子下的运行过程可复现
+        let config = WfcConfig {
+            random_seed: Some(7),
+            tie_break: TieBreak::SpatialHash,
+            ..WfcConfig::default()
+        };
+        let (mut cold, cells) = match_manager_with_config(4, 4, config.clone());
+        cold.run().unwrap();
+        let cold_steps = cold.propagation_steps();
+
+        // 只重置中心单元格，其余单元格沿用上一次的结果
+        let center = cells[1][1];
+        let mut domains: HashMap<CellId, Vec<TileId>> = cold
+            .wfc_data
+            .iter()
+            .map(|(&cell_id, data)| (cell_id, data.possibilities()))
+            .collect();
+        domains.insert(center, cold.get_all_tile_ids());
+
+        let (mut warm, _) = match_manager_with_config(4, 4, config);
+        warm.warm_start(domains.clone()).unwrap();
+        assert_eq!(warm.get_cell_state(center).unwrap(), CellState::Uncollapsed);
+        warm.run().unwrap();
+
+        assert!(warm.is_complete());
+        assert!(!warm.has_conflicts());
+        assert!(warm.propagation_steps() * 4 < cold_steps);
+
+        // 与邻居矛盾的热启动数据被拒绝，状态保持不变
+        let east = cells[1][2];
+        domains.insert(center, vec![3]);
+        domains.insert(east, vec![0]);
+        let (mut rejected, _) = match_manager(4, 4);
+        assert_eq!(
+            rejected.warm_start(domains),
+            Err(WfcError::InconsistentState)
+        );
+        assert_eq!(rejected.completed_count, 0);
+        assert_eq!(rejected.wfc_data[&center].possibility_count(), 4);
+
+        // 坍塌为单一瓷砖的单元格会约束不在domains中的邻居
+        let (mut partial, cells) = match_manager(3, 3);
+        partial
+            .warm_start(HashMap::from([(cells[1][1], vec![3])]))
+            .unwrap();
+        assert_eq!(
+            partial.get_cell_state(cells[1][1]).unwrap(),
+            CellState::Collapsed
+        );
+        assert_eq!(partial.wfc_data[&cells[1][2]].possibilities(), vec![2, 3]);
+        assert_eq!(partial.wfc_data[&cells[0][1]].possibilities(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_preflight() {
+        let (manager, _) = match_manager(3, 3);
+        assert_eq!(manager.preflight::<Direction4Full>(), Ok(()));
+
+        // 故意构造的错误配置
+        let (mut grid, cells) = build_orthogonal_grid(2, 2);
+        let isolated = grid.add_cell(Cell::with_id(100));
+        let dangling = grid.add_cell(Cell::with_id(101));
+        grid.create_edge(dangling, Some(cells[0][0])).unwrap();
+
+        let mut tile_set = MatchTileSet::new();
+        let short_tile = tile_set.tiles.add_tile(vec![0, 0, 0], 1);
+        let lonely_tile = tile_set.tiles.add_tile(vec![7, 0, 0, 0], 1);
+
+        let manager = WfcManager::new(grid, Box::new(tile_set)).unwrap();
+        let issues = manager.preflight::<Direction4Full>().unwrap_err();
+
+        assert!(issues.contains(&PreflightIssue::TileArityMismatch {
+            tile: short_tile,
+            edge_count: 3,
+            expected: 4,
+        }));
+        assert!(issues.contains(&PreflightIssue::MissingAdjacency {
+            tile: lonely_tile,
+            direction_index: 0,
+        }));
+        assert!(issues.contains(&PreflightIssue::IsolatedCell(isolated)));
+        assert!(issues.contains(&PreflightIssue::CellArityMismatch {
+            cell: dangling,
+            neighbor_count: 1,
+            expected: 4,
+        }));
     }
 }
@@ -51,6 +51,10 @@
 //!
 //! - [`DirectionTrait`]：通用的方向抽象
 //! - [`Direction4`]：四方向网格的具体实现
+//! - [`Direction4Full`]：每个单元格建全部四条边的四方向网格实现
+//! - [`Direction6`]：三维六方向网格的具体实现
+//! - [`Direction8`]：包含对角线的八方向网格实现
+//! - [`DirectionHex`]：六边形网格的具体实现
 //! - 支持编译时方向验证和运行时方向查询
 //!
 //! ## 使用示例
@@ -113,6 +117,10 @@ use petgraph::graph::{EdgeIndex, NodeIndex};
  */
 use petgraph::{Directed, Graph};
 
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
 // =============================================================================
 // 基础类型别名 - 对应原C++的using定义
 // =============================================================================
@@ -136,6 +144,9 @@ pub type TileId = usize;
 /// 单元格集合，对应原C++的`Cells = std::vector<CellID>`
 pub type Cells = Vec<CellId>;
 
+/// 单元格ID重映射表，元素为`(旧ID, 新ID)`，由删除节点等会移动ID的操作返回
+pub type CellRemap = Vec<(CellId, CellId)>;
+
 /// 瓷砖集合，对应原C++的`Tiles = std::vector<TileID<EdgeData>>`
 pub type Tiles = Vec<TileId>;
 
@@ -149,7 +160,9 @@ pub type Edges = Vec<EdgeId>;
 /// 1. **方向感知**：每条边都有明确的方向性
 /// 2. **稳定顺序**：petgraph保证邻居返回的稳定顺序
 /// 3. **高效查询**：O(1)的邻居查询操作
-pub type WFCGraph = Graph<Cell, GraphEdge, Directed>;
+///
+/// 类型参数`T`为单元格携带的应用数据，默认为`()`。
+pub type WFCGraph<T = ()> = Graph<Cell<T>, GraphEdge, Directed>;
 
 // =============================================================================
 // 基础数据结构
@@ -179,9 +192,19 @@ pub type WFCGraph = Graph<Cell, GraphEdge, Directed>;
 ///
 /// // 创建带名称的单元格
 /// let cell3 = Cell::with_name("center_cell".to_string());
+///
+/// // 创建携带应用数据的单元格
+/// let cell4 = Cell::with_data((3, 4));
+/// assert_eq!(cell4.data, (3, 4));
 /// ```
+///
+/// ## 应用数据
+///
+/// 类型参数`T`用于在单元格上直接存储应用数据（坐标、生物群系提示等），
+/// 不必另建以`CellId`为键的映射。默认为`()`，不需要数据时可以完全忽略它。
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
-pub struct Cell {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cell<T = ()> {
     /// 可选的单元格ID，用于调试和查找
     ///
     /// 这是一个辅助字段，不影响图的结构，主要用于：
@@ -197,6 +220,13 @@ pub struct Cell {
     /// - 单元测试中的验证
     /// - 可视化工具的显示
     pub name: Option<String>,
+
+    /// 应用数据
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, bound(deserialize = "T: serde::Deserialize<'de> + Default"))
+    )]
+    pub data: T,
 }
 
 impl Cell {
@@ -237,6 +267,7 @@ impl Cell {
         Self {
             id: Some(id),
             name: None,
+            data: (),
         }
     }
 
@@ -260,6 +291,28 @@ impl Cell {
         Self {
             id: None,
             name: Some(name),
+            data: (),
+        }
+    }
+}
+
+impl<T> Cell<T> {
+    /// 创建携带应用数据的单元格
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::Cell;
+    ///
+    /// let cell = Cell::with_data("forest");
+    /// assert_eq!(cell.data, "forest");
+    /// assert!(cell.id.is_none());
+    /// ```
+    pub fn with_data(data: T) -> Self {
+        Self {
+            id: None,
+            name: None,
+            data,
         }
     }
 }
@@ -290,6 +343,7 @@ impl Cell {
 /// let typed_edge = GraphEdge::with_type("path".to_string());
 /// ```
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GraphEdge {
     /// 可选的边权重（使用整数避免浮点数比较问题）
     ///
@@ -352,10 +406,16 @@ pub enum GridError {
     CapacityExhausted,
     /// 方向无效
     InvalidDirection,
+    /// 构建器参数无效，附带构建器给出的说明
+    BuilderError(String),
+    /// 名称已被其他单元格使用
+    NameAlreadyExists(String),
+    /// 坐标已被其他单元格使用
+    CoordsAlreadyExists(Vec<i32>),
 }
 
-impl std::fmt::Display for GridError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for GridError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             GridError::SelfLoop => write!(f, "Cannot create self-loop edge"),
             GridError::EdgeAlreadyExists => write!(f, "Edge already exists"),
@@ -364,10 +424,16 @@ impl std::fmt::Display for GridError {
             GridError::IndexOutOfBounds => write!(f, "Index out of bounds"),
             GridError::CapacityExhausted => write!(f, "Graph capacity exhausted"),
             GridError::InvalidDirection => write!(f, "Invalid direction"),
+            GridError::BuilderError(message) => write!(f, "Grid builder error: {}", message),
+            GridError::NameAlreadyExists(name) => write!(f, "Cell name already in use: {}", name),
+            GridError::CoordsAlreadyExists(coords) => {
+                write!(f, "Cell coordinates already in use: {:?}", coords)
+            }
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for GridError {}
 
 // =============================================================================
@@ -430,12 +496,12 @@ impl std::error::Error for GridError {}
 /// 这个trait设计支持多种网格类型：
 ///
 /// - **2D四方向**：东南西北（已实现为`Direction4`）
-/// - **2D八方向**：包含对角线方向
-/// - **六角形网格**：六个方向
-/// - **3D网格**：包含上下方向
+/// - **2D八方向**：包含对角线方向（已实现为`Direction8`）
+/// - **六角形网格**：六个方向（已实现为`DirectionHex`）
+/// - **3D网格**：包含上下方向（已实现为`Direction6`）
 /// - **自定义拓扑**：任意连接模式
 pub trait DirectionTrait:
-    Clone + Copy + PartialEq + Eq + std::hash::Hash + std::fmt::Debug
+    Clone + Copy + PartialEq + Eq + core::hash::Hash + core::fmt::Debug
 {
     /// 将方向转换为邻居数组的索引
     ///
@@ -477,6 +543,58 @@ pub trait DirectionTrait:
     /// ```
     fn to_neighbor_index(&self) -> Option<usize>;
 
+    /// 将邻居数组的索引转换回方向
+    ///
+    /// 这是[`to_neighbor_index`](Self::to_neighbor_index)的逆映射，
+    /// 适用于遍历`get_neighbors()`结果时确定每个邻居所在的方向。
+    ///
+    /// # 返回值
+    ///
+    /// - `Some(direction)` - 该索引对应的方向
+    /// - `None` - 索引超出范围，或该方向系统未提供逆映射（默认实现）
+    ///
+    /// 需要反向查找的方向（如`Direction4`的西和北）不出现在`neighbors()`结果中，
+    /// 因此没有任何索引会映射到这些方向。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{Direction4, DirectionTrait};
+    ///
+    /// assert_eq!(Direction4::from_neighbor_index(0), Some(Direction4::South));
+    /// assert_eq!(Direction4::from_neighbor_index(1), Some(Direction4::East));
+    /// assert_eq!(Direction4::from_neighbor_index(2), None);
+    /// ```
+    fn from_neighbor_index(index: usize) -> Option<Self> {
+        let _ = index;
+        None
+    }
+
+    /// 瓷砖边数据索引对应的相反方向索引
+    ///
+    /// 瓷砖的`edges`按照`neighbors()`返回顺序排列，判断兼容性时需要比较
+    /// 候选瓷砖在`index`方向的边与邻居瓷砖在相反方向的边。
+    ///
+    /// 默认实现通过[`from_neighbor_index`](Self::from_neighbor_index)、
+    /// [`opposite`](Self::opposite)和[`to_neighbor_index`](Self::to_neighbor_index)组合得到，
+    /// 适用于所有方向都能直接索引的方向系统。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{Direction4Full, Direction6, DirectionTrait};
+    ///
+    /// // [北, 西, 南, 东]：北 ↔ 南
+    /// assert_eq!(Direction4Full::opposite_edge_index(0), Some(2));
+    /// // [北, 西, 南, 东, 下, 上]：下 ↔ 上
+    /// assert_eq!(Direction6::opposite_edge_index(4), Some(5));
+    /// ```
+    fn opposite_edge_index(index: usize) -> Option<usize> {
+        Self::from_neighbor_index(index)?
+            .opposite()?
+            .to_neighbor_index()
+    }
+
     /// 获取相反方向
     ///
     /// 用于反向查找时确定对应关系，也用于双向连接的创建。
@@ -526,6 +644,38 @@ pub trait DirectionTrait:
     /// ```
     fn all_directions() -> Vec<Self>;
 
+    /// 顺时针旋转一个固定步长后的方向
+    ///
+    /// 步长由方向系统决定（`Direction4`为90°），用于生成旋转后的瓷砖变体。
+    /// 默认实现返回`None`，表示该方向系统不支持旋转。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{Direction4, DirectionTrait};
+    ///
+    /// assert_eq!(Direction4::East.rotate_cw(), Some(Direction4::South));
+    /// assert_eq!(Direction4::North.rotate_cw(), Some(Direction4::East));
+    /// ```
+    fn rotate_cw(&self) -> Option<Self> {
+        None
+    }
+
+    /// 逆时针旋转一个固定步长后的方向
+    ///
+    /// [`rotate_cw`](Self::rotate_cw)的逆操作，默认实现返回`None`。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use rlwfc::{Direction4, DirectionTrait};
+    ///
+    /// assert_eq!(Direction4::East.rotate_ccw(), Some(Direction4::North));
+    /// ```
+    fn rotate_ccw(&self) -> Option<Self> {
+        None
+    }
+
     /// 方向的显示名称（用于调试）
     ///
     /// 返回该方向的人类可读名称，主要用于调试输出和日志记录。
@@ -546,6 +696,13 @@ pub trait DirectionTrait:
 }
 
 /// 四方向网格的标准实现
+///
+/// 每个单元格只建东、南两条边，`get_neighbors()`返回`[南, 东]`，
+/// 西、北方向由`get_neighbor_by_direction`反向查找。
+///
+/// 这种网格没有四边瓷砖的边数据可比较，因此[`opposite_edge_index`](DirectionTrait::opposite_edge_index)
+/// 对所有下标返回`None`。每个单元格按东、南、西、北建全部四条边、
+/// 瓷砖边数据按`[北, 西, 南, 东]`排列的网格使用[`Direction4Full`]。
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Direction4 {
     East,  // 东
@@ -566,6 +723,14 @@ impl DirectionTrait for Direction4 {
         }
     }
 
+    fn from_neighbor_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Direction4::South),
+            1 => Some(Direction4::East),
+            _ => None,
+        }
+    }
+
     fn opposite(&self) -> Option<Self> {
         match self {
             Direction4::East => Some(Direction4::West),
@@ -584,6 +749,24 @@ impl DirectionTrait for Direction4 {
         ]
     }
 
+    fn rotate_cw(&self) -> Option<Self> {
+        match self {
+            Direction4::East => Some(Direction4::South),
+            Direction4::South => Some(Direction4::West),
+            Direction4::West => Some(Direction4::North),
+            Direction4::North => Some(Direction4::East),
+        }
+    }
+
+    fn rotate_ccw(&self) -> Option<Self> {
+        match self {
+            Direction4::East => Some(Direction4::North),
+            Direction4::South => Some(Direction4::East),
+            Direction4::West => Some(Direction4::South),
+            Direction4::North => Some(Direction4::West),
+        }
+    }
+
     fn name(&self) -> &'static str {
         match self {
             Direction4::East => "East",
@@ -594,6 +777,623 @@ impl DirectionTrait for Direction4 {
     }
 }
 
+/// 每个单元格建全部四条边的四方向网格
+///
+/// 与[`Direction4`]不同，`Direction4Full`要求每个单元格按东、南、西、北的顺序创建全部四条边，
+/// 边界处使用`create_edge(cell, None)`创建虚拟节点占位，因此所有方向都可以直接从
+/// `neighbors()`获取，四边瓷砖的边数据下标与邻居下标一致。
+/// [`GridBuilder2D`](crate::GridBuilder2D)的四邻接和模块文档中的正交网格都使用这种建边方式。
+///
+/// ## 边创建顺序
+///
+/// ```text
+/// 边创建顺序：东 → 南 → 西 → 北
+/// neighbors() 返回：[北, 西, 南, 东] (petgraph 逆序特性)
+/// 瓷砖边数据索引：[0,  1,  2,  3]
+/// ```
+///
+/// # 示例
+///
+/// ```rust
+/// use rlwfc::{Connectivity, Direction4Full, DirectionTrait, GridBuilder2D, GridSystem};
+///
+/// let grid = GridSystem::from_builder(GridBuilder2D::new(3, 3, Connectivity::Four)).unwrap();
+/// let center = grid.get_cell_at(&[1, 1]).unwrap();
+/// assert_eq!(
+///     grid.get_neighbor_by_direction(center, Direction4Full::North),
+///     grid.get_cell_at(&[1, 0])
+/// );
+/// assert_eq!(Direction4Full::opposite_edge_index(0), Some(2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction4Full {
+    East,  // 东
+    South, // 南
+    West,  // 西
+    North, // 北
+}
+
+impl DirectionTrait for Direction4Full {
+    fn to_neighbor_index(&self) -> Option<usize> {
+        // 创建顺序[东, 南, 西, 北]的逆序
+        match self {
+            Direction4Full::North => Some(0),
+            Direction4Full::West => Some(1),
+            Direction4Full::South => Some(2),
+            Direction4Full::East => Some(3),
+        }
+    }
+
+    fn from_neighbor_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Direction4Full::North),
+            1 => Some(Direction4Full::West),
+            2 => Some(Direction4Full::South),
+            3 => Some(Direction4Full::East),
+            _ => None,
+        }
+    }
+
+    fn opposite(&self) -> Option<Self> {
+        match self {
+            Direction4Full::East => Some(Direction4Full::West),
+            Direction4Full::West => Some(Direction4Full::East),
+            Direction4Full::North => Some(Direction4Full::South),
+            Direction4Full::South => Some(Direction4Full::North),
+        }
+    }
+
+    fn all_directions() -> Vec<Self> {
+        vec![
+            Direction4Full::East,
+            Direction4Full::South,
+            Direction4Full::West,
+            Direction4Full::North,
+        ]
+    }
+
+    fn rotate_cw(&self) -> Option<Self> {
+        match self {
+            Direction4Full::East => Some(Direction4Full::South),
+            Direction4Full::South => Some(Direction4Full::West),
+            Direction4Full::West => Some(Direction4Full::North),
+            Direction4Full::North => Some(Direction4Full::East),
+        }
+    }
+
+    fn rotate_ccw(&self) -> Option<Self> {
+        match self {
+            Direction4Full::East => Some(Direction4Full::North),
+            Direction4Full::South => Some(Direction4Full::East),
+            Direction4Full::West => Some(Direction4Full::South),
+            Direction4Full::North => Some(Direction4Full::West),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Direction4Full::East => "East",
+            Direction4Full::South => "South",
+            Direction4Full::West => "West",
+            Direction4Full::North => "North",
+        }
+    }
+}
+
+/// 三维六方向网格的标准实现
+///
+/// 用于体素（voxel）WFC网格。与[`Direction4`]不同，`Direction6`要求每个单元格
+/// 按固定顺序创建全部六条边，边界处使用`create_edge(cell, None)`创建虚拟节点占位，
+/// 因此所有方向都可以直接从`neighbors()`获取，无需反向查找。
+///
+/// ## 边创建顺序
+///
+/// ```text
+/// 边创建顺序：上 → 下 → 东 → 南 → 西 → 北
+/// neighbors() 返回：[北, 西, 南, 东, 下, 上] (petgraph 逆序特性)
+/// 瓷砖边数据索引：[0,  1,  2,  3,  4,  5]
+/// ```
+///
+/// 前四个索引与2D网格的 [北, 西, 南, 东] 约定一致，3D瓷砖只需在2D边数据后
+/// 追加下、上两条边。
+///
+/// # 示例
+///
+/// ```rust
+/// use rlwfc::{Direction6, DirectionTrait};
+///
+/// assert_eq!(Direction6::Up.opposite(), Some(Direction6::Down));
+/// assert_eq!(Direction6::North.to_neighbor_index(), Some(0));
+/// assert_eq!(Direction6::Up.to_neighbor_index(), Some(5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction6 {
+    East,  // 东
+    South, // 南
+    West,  // 西
+    North, // 北
+    Up,    // 上
+    Down,  // 下
+}
+
+impl DirectionTrait for Direction6 {
+    fn to_neighbor_index(&self) -> Option<usize> {
+        // 创建顺序[上, 下, 东, 南, 西, 北]的逆序
+        match self {
+            Direction6::North => Some(0),
+            Direction6::West => Some(1),
+            Direction6::South => Some(2),
+            Direction6::East => Some(3),
+            Direction6::Down => Some(4),
+            Direction6::Up => Some(5),
+        }
+    }
+
+    fn from_neighbor_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Direction6::North),
+            1 => Some(Direction6::West),
+            2 => Some(Direction6::South),
+            3 => Some(Direction6::East),
+            4 => Some(Direction6::Down),
+            5 => Some(Direction6::Up),
+            _ => None,
+        }
+    }
+
+    fn opposite(&self) -> Option<Self> {
+        match self {
+            Direction6::East => Some(Direction6::West),
+            Direction6::West => Some(Direction6::East),
+            Direction6::North => Some(Direction6::South),
+            Direction6::South => Some(Direction6::North),
+            Direction6::Up => Some(Direction6::Down),
+            Direction6::Down => Some(Direction6::Up),
+        }
+    }
+
+    fn all_directions() -> Vec<Self> {
+        vec![
+            Direction6::Up,
+            Direction6::Down,
+            Direction6::East,
+            Direction6::South,
+            Direction6::West,
+            Direction6::North,
+        ]
+    }
+
+    // 绕竖直轴旋转，上下方向保持不变
+    fn rotate_cw(&self) -> Option<Self> {
+        match self {
+            Direction6::East => Some(Direction6::South),
+            Direction6::South => Some(Direction6::West),
+            Direction6::West => Some(Direction6::North),
+            Direction6::North => Some(Direction6::East),
+            Direction6::Up | Direction6::Down => Some(*self),
+        }
+    }
+
+    fn rotate_ccw(&self) -> Option<Self> {
+        match self {
+            Direction6::East => Some(Direction6::North),
+            Direction6::South => Some(Direction6::East),
+            Direction6::West => Some(Direction6::South),
+            Direction6::North => Some(Direction6::West),
+            Direction6::Up | Direction6::Down => Some(*self),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Direction6::East => "East",
+            Direction6::South => "South",
+            Direction6::West => "West",
+            Direction6::North => "North",
+            Direction6::Up => "Up",
+            Direction6::Down => "Down",
+        }
+    }
+}
+
+/// 八方向（Moore邻域）网格的标准实现
+///
+/// 在四个正交方向之外加入四个对角方向，适用于需要对角约束的2D网格（如地牢生成）。
+/// 与[`Direction6`]相同，每个单元格按固定顺序创建全部八条边，边界使用虚拟节点占位。
+///
+/// ## 边创建顺序
+///
+/// ```text
+/// 边创建顺序：西北 → 西 → 西南 → 南 → 东南 → 东 → 东北 → 北
+/// neighbors() 返回：[北, 东北, 东, 东南, 南, 西南, 西, 西北] (petgraph 逆序特性)
+/// 瓷砖边数据索引：[0,  1,    2,  3,    4,  5,    6,  7]
+/// ```
+///
+/// 邻居索引从北开始顺时针递增，相反方向的索引为`(i + 4) % 8`，
+/// 对应瓷砖的`edges`长度为8。
+///
+/// # 示例
+///
+/// ```rust
+/// use rlwfc::{Cell, Direction8, DirectionTrait, GridSystem};
+///
+/// // 3x3网格，所有单元格按固定顺序创建八条边
+/// let mut grid = GridSystem::new();
+/// let cells: Vec<Vec<_>> = (0..3)
+///     .map(|y| (0..3).map(|x| grid.add_cell(Cell::with_id(y * 3 + x))).collect())
+///     .collect();
+///
+/// for y in 0..3i32 {
+///     for x in 0..3i32 {
+///         for direction in Direction8::all_directions() {
+///             let (dx, dy) = direction.offset();
+///             let (nx, ny) = (x + dx, y + dy);
+///             let target = ((0..3).contains(&nx) && (0..3).contains(&ny))
+///                 .then(|| cells[ny as usize][nx as usize]);
+///             grid.create_edge(cells[y as usize][x as usize], target).unwrap();
+///         }
+///     }
+/// }
+///
+/// // 中心单元格的八个方向都能解析
+/// let center = cells[1][1];
+/// assert_eq!(grid.get_neighbor_by_direction(center, Direction8::NorthEast), Some(cells[0][2]));
+/// assert_eq!(grid.get_neighbor_by_direction(center, Direction8::West), Some(cells[1][0]));
+/// for direction in Direction8::all_directions() {
+///     let (dx, dy) = direction.offset();
+///     let expected = cells[(1 + dy) as usize][(1 + dx) as usize];
+///     assert_eq!(grid.get_neighbor_by_direction(center, direction), Some(expected));
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    North,     // 北
+    NorthEast, // 东北
+    East,      // 东
+    SouthEast, // 东南
+    South,     // 南
+    SouthWest, // 西南
+    West,      // 西
+    NorthWest, // 西北
+}
+
+impl Direction8 {
+    /// 该方向在2D网格中的坐标偏移`(dx, dy)`，x向东、y向南
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction8::North => (0, -1),
+            Direction8::NorthEast => (1, -1),
+            Direction8::East => (1, 0),
+            Direction8::SouthEast => (1, 1),
+            Direction8::South => (0, 1),
+            Direction8::SouthWest => (-1, 1),
+            Direction8::West => (-1, 0),
+            Direction8::NorthWest => (-1, -1),
+        }
+    }
+}
+
+impl DirectionTrait for Direction8 {
+    fn to_neighbor_index(&self) -> Option<usize> {
+        // 创建顺序[西北, 西, 西南, 南, 东南, 东, 东北, 北]的逆序
+        match self {
+            Direction8::North => Some(0),
+            Direction8::NorthEast => Some(1),
+            Direction8::East => Some(2),
+            Direction8::SouthEast => Some(3),
+            Direction8::South => Some(4),
+            Direction8::SouthWest => Some(5),
+            Direction8::West => Some(6),
+            Direction8::NorthWest => Some(7),
+        }
+    }
+
+    fn from_neighbor_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Direction8::North),
+            1 => Some(Direction8::NorthEast),
+            2 => Some(Direction8::East),
+            3 => Some(Direction8::SouthEast),
+            4 => Some(Direction8::South),
+            5 => Some(Direction8::SouthWest),
+            6 => Some(Direction8::West),
+            7 => Some(Direction8::NorthWest),
+            _ => None,
+        }
+    }
+
+    fn opposite(&self) -> Option<Self> {
+        match self {
+            Direction8::North => Some(Direction8::South),
+            Direction8::NorthEast => Some(Direction8::SouthWest),
+            Direction8::East => Some(Direction8::West),
+            Direction8::SouthEast => Some(Direction8::NorthWest),
+            Direction8::South => Some(Direction8::North),
+            Direction8::SouthWest => Some(Direction8::NorthEast),
+            Direction8::West => Some(Direction8::East),
+            Direction8::NorthWest => Some(Direction8::SouthEast),
+        }
+    }
+
+    fn all_directions() -> Vec<Self> {
+        vec![
+            Direction8::NorthWest,
+            Direction8::West,
+            Direction8::SouthWest,
+            Direction8::South,
+            Direction8::SouthEast,
+            Direction8::East,
+            Direction8::NorthEast,
+            Direction8::North,
+        ]
+    }
+
+    fn rotate_cw(&self) -> Option<Self> {
+        match self {
+            Direction8::North => Some(Direction8::NorthEast),
+            Direction8::NorthEast => Some(Direction8::East),
+            Direction8::East => Some(Direction8::SouthEast),
+            Direction8::SouthEast => Some(Direction8::South),
+            Direction8::South => Some(Direction8::SouthWest),
+            Direction8::SouthWest => Some(Direction8::West),
+            Direction8::West => Some(Direction8::NorthWest),
+            Direction8::NorthWest => Some(Direction8::North),
+        }
+    }
+
+    fn rotate_ccw(&self) -> Option<Self> {
+        match self {
+            Direction8::North => Some(Direction8::NorthWest),
+            Direction8::NorthEast => Some(Direction8::North),
+            Direction8::East => Some(Direction8::NorthEast),
+            Direction8::SouthEast => Some(Direction8::East),
+            Direction8::South => Some(Direction8::SouthEast),
+            Direction8::SouthWest => Some(Direction8::South),
+            Direction8::West => Some(Direction8::SouthWest),
+            Direction8::NorthWest => Some(Direction8::West),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Direction8::North => "North",
+            Direction8::NorthEast => "NorthEast",
+            Direction8::East => "East",
+            Direction8::SouthEast => "SouthEast",
+            Direction8::South => "South",
+            Direction8::SouthWest => "SouthWest",
+            Direction8::West => "West",
+            Direction8::NorthWest => "NorthWest",
+        }
+    }
+}
+
+/// 六边形网格的标准实现（尖顶朝上，轴向坐标）
+///
+/// 使用轴向坐标`(q, r)`描述六边形网格，q向东、r向东南递增。
+/// 与[`Direction6`]相同，每个单元格按固定顺序创建全部六条边，
+/// 地图边界上缺失的方向使用虚拟节点占位，而不是跳过，
+/// 这样所有单元格的邻居索引映射保持一致。
+///
+/// ## 边创建顺序
+///
+/// ```text
+/// 边创建顺序：西北 → 西 → 西南 → 东南 → 东 → 东北
+/// neighbors() 返回：[东北, 东, 东南, 西南, 西, 西北] (petgraph 逆序特性)
+/// 瓷砖边数据索引：[0,    1,  2,    3,    4,  5]
+/// ```
+///
+/// 邻居索引从东北开始顺时针递增，相反方向的索引为`(i + 3) % 6`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DirectionHex {
+    NorthEast, // 东北
+    East,      // 东
+    SouthEast, // 东南
+    SouthWest, // 西南
+    West,      // 西
+    NorthWest, // 西北
+}
+
+impl DirectionHex {
+    /// 该方向的轴向坐标偏移`(dq, dr)`
+    pub fn axial_offset(&self) -> (i32, i32) {
+        match self {
+            DirectionHex::NorthEast => (1, -1),
+            DirectionHex::East => (1, 0),
+            DirectionHex::SouthEast => (0, 1),
+            DirectionHex::SouthWest => (-1, 1),
+            DirectionHex::West => (-1, 0),
+            DirectionHex::NorthWest => (0, -1),
+        }
+    }
+}
+
+impl DirectionTrait for DirectionHex {
+    fn to_neighbor_index(&self) -> Option<usize> {
+        // 创建顺序[西北, 西, 西南, 东南, 东, 东北]的逆序
+        match self {
+            DirectionHex::NorthEast => Some(0),
+            DirectionHex::East => Some(1),
+            DirectionHex::SouthEast => Some(2),
+            DirectionHex::SouthWest => Some(3),
+            DirectionHex::West => Some(4),
+            DirectionHex::NorthWest => Some(5),
+        }
+    }
+
+    fn from_neighbor_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(DirectionHex::NorthEast),
+            1 => Some(DirectionHex::East),
+            2 => Some(DirectionHex::SouthEast),
+            3 => Some(DirectionHex::SouthWest),
+            4 => Some(DirectionHex::West),
+            5 => Some(DirectionHex::NorthWest),
+            _ => None,
+        }
+    }
+
+    fn opposite(&self) -> Option<Self> {
+        match self {
+            DirectionHex::NorthEast => Some(DirectionHex::SouthWest),
+            DirectionHex::East => Some(DirectionHex::West),
+            DirectionHex::SouthEast => Some(DirectionHex::NorthWest),
+            DirectionHex::SouthWest => Some(DirectionHex::NorthEast),
+            DirectionHex::West => Some(DirectionHex::East),
+            DirectionHex::NorthWest => Some(DirectionHex::SouthEast),
+        }
+    }
+
+    fn all_directions() -> Vec<Self> {
+        vec![
+            DirectionHex::NorthWest,
+            DirectionHex::West,
+            DirectionHex::SouthWest,
+            DirectionHex::SouthEast,
+            DirectionHex::East,
+            DirectionHex::NorthEast,
+        ]
+    }
+
+    fn rotate_cw(&self) -> Option<Self> {
+        match self {
+            DirectionHex::NorthEast => Some(DirectionHex::East),
+            DirectionHex::East => Some(DirectionHex::SouthEast),
+            DirectionHex::SouthEast => Some(DirectionHex::SouthWest),
+            DirectionHex::SouthWest => Some(DirectionHex::West),
+            DirectionHex::West => Some(DirectionHex::NorthWest),
+            DirectionHex::NorthWest => Some(DirectionHex::NorthEast),
+        }
+    }
+
+    fn rotate_ccw(&self) -> Option<Self> {
+        match self {
+            DirectionHex::NorthEast => Some(DirectionHex::NorthWest),
+            DirectionHex::East => Some(DirectionHex::NorthEast),
+            DirectionHex::SouthEast => Some(DirectionHex::East),
+            DirectionHex::SouthWest => Some(DirectionHex::SouthEast),
+            DirectionHex::West => Some(DirectionHex::SouthWest),
+            DirectionHex::NorthWest => Some(DirectionHex::West),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            DirectionHex::NorthEast => "NorthEast",
+            DirectionHex::East => "East",
+            DirectionHex::SouthEast => "SouthEast",
+            DirectionHex::SouthWest => "SouthWest",
+            DirectionHex::West => "West",
+            DirectionHex::NorthWest => "NorthWest",
+        }
+    }
+}
+
+/// 三角形的朝向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriOrientation {
+    /// 尖顶朝上，底边在下方
+    Up,
+    /// 尖顶朝下，底边在上方
+    Down,
+}
+
+impl TriOrientation {
+    /// 按列`x`、行`y`计算朝向，`x + y`为偶数时尖顶朝上
+    ///
+    /// 同一行内朝向交替出现，相邻两行在同一列上的朝向相反，
+    /// 因此每个三角形都与它底边一侧的三角形共享一条水平边。
+    pub fn at(x: i32, y: i32) -> Self {
+        if (x + y).rem_euclid(2) == 0 {
+            TriOrientation::Up
+        } else {
+            TriOrientation::Down
+        }
+    }
+}
+
+/// 三角形网格的标准实现
+///
+/// 三角形网格按行排列，每行内尖顶朝上和朝下的三角形交替出现（见[`TriOrientation`]）。
+/// 每个三角形有三个邻居：左斜边、右斜边各一个，以及共享水平底边的一个。
+/// 朝上三角形的底边邻居在正下方，朝下三角形的在正上方。
+///
+/// ## 朝向的处理
+///
+/// 如果按罗盘方位命名，"南"对朝上三角形存在、对朝下三角形不存在，相反方向
+/// 和邻居索引都需要单元格的朝向作为上下文。这里改为按三角形自身的边命名方向：
+/// 左、右、底边。这样相反方向与朝向无关（左 ↔ 右，底边 ↔ 底边），
+/// `DirectionTrait`不需要任何额外上下文。朝向只在构建网格时用于计算坐标偏移，
+/// 见[`offset`](Self::offset)。
+///
+/// 120°旋转对朝上和朝下三角形的边轮换方向相反，因此不提供旋转。
+///
+/// ## 边创建顺序
+///
+/// ```text
+/// 边创建顺序：底边 → 右 → 左
+/// neighbors() 返回：[左, 右, 底边] (petgraph 逆序特性)
+/// 瓷砖边数据索引：[0,  1,  2]
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DirectionTri {
+    Left,  // 左斜边
+    Right, // 右斜边
+    Base,  // 水平底边
+}
+
+impl DirectionTri {
+    /// 给定朝向的三角形在该方向上的邻居坐标偏移`(dx, dy)`，y向下递增
+    pub fn offset(&self, orientation: TriOrientation) -> (i32, i32) {
+        match (self, orientation) {
+            (DirectionTri::Left, _) => (-1, 0),
+            (DirectionTri::Right, _) => (1, 0),
+            (DirectionTri::Base, TriOrientation::Up) => (0, 1),
+            (DirectionTri::Base, TriOrientation::Down) => (0, -1),
+        }
+    }
+}
+
+impl DirectionTrait for DirectionTri {
+    fn to_neighbor_index(&self) -> Option<usize> {
+        // 创建顺序[底边, 右, 左]的逆序
+        match self {
+            DirectionTri::Left => Some(0),
+            DirectionTri::Right => Some(1),
+            DirectionTri::Base => Some(2),
+        }
+    }
+
+    fn from_neighbor_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(DirectionTri::Left),
+            1 => Some(DirectionTri::Right),
+            2 => Some(DirectionTri::Base),
+            _ => None,
+        }
+    }
+
+    fn opposite(&self) -> Option<Self> {
+        match self {
+            DirectionTri::Left => Some(DirectionTri::Right),
+            DirectionTri::Right => Some(DirectionTri::Left),
+            DirectionTri::Base => Some(DirectionTri::Base),
+        }
+    }
+
+    fn all_directions() -> Vec<Self> {
+        vec![DirectionTri::Base, DirectionTri::Right, DirectionTri::Left]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            DirectionTri::Left => "Left",
+            DirectionTri::Right => "Right",
+            DirectionTri::Base => "Base",
+        }
+    }
+}
+
 // =============================================================================
 // 瓷砖系统
 // =============================================================================
@@ -669,9 +1469,10 @@ impl DirectionTrait for Direction4 {
 /// let is_compatible = candidate_edge == neighbor_edge;
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tile<EdgeData>
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug,
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
 {
     /// 瓷砖ID
     pub id: TileId,
@@ -679,15 +1480,33 @@ where
     pub weight: i32,
     /// 边信息，对应原C++的edge字段
     pub edges: Vec<EdgeData>,
+    /// 显示名称，供渲染或导出结果时使用，不参与兼容性判断
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub name: Option<String>,
+    /// 用户标签，例如精灵索引或旋转信息，不参与兼容性判断
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub tags: Vec<String>,
 }
 
 impl<EdgeData> Tile<EdgeData>
 where
-    EdgeData: Clone + PartialEq + std::fmt::Debug,
+    EdgeData: Clone + PartialEq + core::fmt::Debug,
 {
     /// 创建新瓷砖
     pub fn new(id: TileId, weight: i32, edges: Vec<EdgeData>) -> Self {
-        Self { id, weight, edges }
+        Self {
+            id,
+            weight,
+            edges,
+            name: None,
+            tags: Vec::new(),
+        }
     }
 
     /// 检查与另一个瓷砖的兼容性
@@ -723,6 +1542,8 @@ where
 // =============================================================================
 
 /// 在二维向量中查找元素，对应原C++的findIn2DVector函数
+///
+/// 返回第一个匹配项的`(行, 列)`，按行优先顺序查找。
 pub fn find_in_2d_vector<T>(vec_2d: &[Vec<T>], target: &T) -> Option<(usize, usize)>
 where
     T: PartialEq,
@@ -737,6 +1558,23 @@ where
     None
 }
 
+/// 在二维向量中查找所有匹配的元素，按行优先顺序返回每个匹配项的`(行, 列)`
+pub fn find_all_in_2d_vector<T>(vec_2d: &[Vec<T>], target: &T) -> Vec<(usize, usize)>
+where
+    T: PartialEq,
+{
+    vec_2d
+        .iter()
+        .enumerate()
+        .flat_map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .filter(move |(_, item)| *item == target)
+                .map(move |(j, _)| (i, j))
+        })
+        .collect()
+}
+
 // =============================================================================
 // 测试模块
 // =============================================================================
@@ -765,6 +1603,176 @@ mod tests {
         assert_eq!(Direction4::West.to_neighbor_index(), None);
     }
 
+    #[test]
+    fn test_from_neighbor_index() {
+        // 可直接获取的方向往返映射保持不变
+        for direction in Direction4::all_directions() {
+            if let Some(index) = direction.to_neighbor_index() {
+                assert_eq!(Direction4::from_neighbor_index(index), Some(direction));
+            }
+        }
+        // 西和北需要反向查找，不对应任何邻居索引
+        assert_eq!(Direction4::from_neighbor_index(2), None);
+        assert_eq!(Direction4::from_neighbor_index(3), None);
+
+        fn assert_round_trip<D: DirectionTrait>(count: usize) {
+            for direction in D::all_directions() {
+                let index = direction.to_neighbor_index().unwrap();
+                assert_eq!(D::from_neighbor_index(index), Some(direction));
+            }
+            assert_eq!(D::from_neighbor_index(count), None);
+        }
+        assert_round_trip::<Direction4Full>(4);
+        assert_round_trip::<Direction6>(6);
+        assert_round_trip::<Direction8>(8);
+        assert_round_trip::<DirectionHex>(6);
+    }
+
+    #[test]
+    fn test_opposite_edge_index() {
+        let expected: Vec<_> = (0..4).map(Direction4Full::opposite_edge_index).collect();
+        assert_eq!(expected, vec![Some(2), Some(3), Some(0), Some(1)]);
+        assert_eq!(Direction4Full::opposite_edge_index(4), None);
+        // 只建东、南两条边的网格没有相对的瓷砖边
+        assert!((0..4).all(|index| Direction4::opposite_edge_index(index).is_none()));
+
+        let expected: Vec<_> = (0..6).map(Direction6::opposite_edge_index).collect();
+        assert_eq!(
+            expected,
+            vec![Some(2), Some(3), Some(0), Some(1), Some(5), Some(4)]
+        );
+        assert_eq!(Direction8::opposite_edge_index(1), Some(5));
+        assert_eq!(DirectionHex::opposite_edge_index(0), Some(3));
+    }
+
+    #[test]
+    fn test_rotate() {
+        for direction in Direction4::all_directions() {
+            // 旋转四次回到原方向
+            let mut rotated = direction;
+            for _ in 0..4 {
+                rotated = rotated.rotate_cw().unwrap();
+            }
+            assert_eq!(rotated, direction);
+
+            // 旋转与取反可交换，顺逆时针互逆
+            assert_eq!(
+                direction.rotate_cw().unwrap().opposite(),
+                direction.opposite().unwrap().rotate_cw()
+            );
+            assert_eq!(
+                direction.rotate_cw().unwrap().rotate_cw(),
+                direction.opposite()
+            );
+            assert_eq!(direction.rotate_cw().unwrap().rotate_ccw(), Some(direction));
+        }
+        assert_eq!(Direction4::East.rotate_cw(), Some(Direction4::South));
+        assert_eq!(Direction4::South.rotate_cw(), Some(Direction4::West));
+        assert_eq!(Direction4::West.rotate_cw(), Some(Direction4::North));
+        assert_eq!(Direction4::North.rotate_cw(), Some(Direction4::East));
+
+        assert_eq!(
+            Direction4Full::North.rotate_cw(),
+            Some(Direction4Full::East)
+        );
+        assert_eq!(Direction6::Up.rotate_cw(), Some(Direction6::Up));
+        assert_eq!(Direction8::North.rotate_cw(), Some(Direction8::NorthEast));
+        assert_eq!(
+            DirectionHex::NorthWest.rotate_cw(),
+            Some(DirectionHex::NorthEast)
+        );
+    }
+
+    #[test]
+    fn test_direction6() {
+        assert_eq!(Direction6::Up.opposite(), Some(Direction6::Down));
+        assert_eq!(Direction6::Down.opposite(), Some(Direction6::Up));
+
+        // 每个方向都有唯一的邻居索引，且相反方向互逆
+        let mut indices: Vec<usize> = Direction6::all_directions()
+            .iter()
+            .map(|direction| direction.to_neighbor_index().unwrap())
+            .collect();
+        indices.sort_unstable();
+        assert_eq!(indices, vec![0, 1, 2, 3, 4, 5]);
+        for direction in Direction6::all_directions() {
+            assert_eq!(direction.opposite().unwrap().opposite(), Some(direction));
+        }
+    }
+
+    #[test]
+    fn test_direction8() {
+        use Direction8::*;
+        let pairs = [
+            (North, South),
+            (NorthEast, SouthWest),
+            (East, West),
+            (SouthEast, NorthWest),
+        ];
+        for (a, b) in pairs {
+            assert_eq!(a.opposite(), Some(b));
+            assert_eq!(b.opposite(), Some(a));
+        }
+
+        // 相反方向的邻居索引相差4
+        for direction in Direction8::all_directions() {
+            let index = direction.to_neighbor_index().unwrap();
+            let opposite_index = direction.opposite().unwrap().to_neighbor_index().unwrap();
+            assert_eq!(opposite_index, (index + 4) % 8);
+        }
+
+        // 八方向瓷砖的边数据
+        let tile = Tile::new(0, 1, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(tile.edge_count(), 8);
+        assert_eq!(
+            tile.get_edge(NorthWest.to_neighbor_index().unwrap()),
+            Some(&7)
+        );
+        assert_eq!(tile.get_edge(8), None);
+    }
+
+    #[test]
+    fn test_direction_hex() {
+        use DirectionHex::*;
+        for (a, b) in [(NorthEast, SouthWest), (East, West), (SouthEast, NorthWest)] {
+            assert_eq!(a.opposite(), Some(b));
+            assert_eq!(b.opposite(), Some(a));
+
+            // 相反方向的坐标偏移互为相反数
+            let (aq, ar) = a.axial_offset();
+            let (bq, br) = b.axial_offset();
+            assert_eq!((aq + bq, ar + br), (0, 0));
+        }
+
+        for direction in DirectionHex::all_directions() {
+            let index = direction.to_neighbor_index().unwrap();
+            let opposite_index = direction.opposite().unwrap().to_neighbor_index().unwrap();
+            assert_eq!(opposite_index, (index + 3) % 6);
+        }
+    }
+
+    #[test]
+    fn test_direction_tri() {
+        assert_eq!(DirectionTri::all_directions().len(), 3);
+        for (index, expected) in [(0, 1), (1, 0), (2, 2)] {
+            let direction = DirectionTri::from_neighbor_index(index).unwrap();
+            assert_eq!(direction.to_neighbor_index(), Some(index));
+            assert_eq!(DirectionTri::opposite_edge_index(index), Some(expected));
+        }
+
+        // 相邻三角形朝向相反，互为邻居
+        for (x, y) in [(0, 0), (1, 0), (2, 3)] {
+            let orientation = TriOrientation::at(x, y);
+            for direction in DirectionTri::all_directions() {
+                let (dx, dy) = direction.offset(orientation);
+                let neighbor = TriOrientation::at(x + dx, y + dy);
+                assert_ne!(neighbor, orientation);
+                let (bx, by) = direction.opposite().unwrap().offset(neighbor);
+                assert_eq!((dx + bx, dy + by), (0, 0));
+            }
+        }
+    }
+
     #[test]
     fn test_tile() {
         let tile = Tile::new(0, 10, vec!["A", "B", "C", "D"]);
@@ -773,4 +1781,21 @@ mod tests {
         assert_eq!(tile.edge_count(), 4);
         assert_eq!(tile.get_edge(0), Some(&"A"));
     }
+
+    #[test]
+    fn test_find_in_2d_vector() {
+        let layers = [vec![1, 2, 3], vec![], vec![4, 2], vec![2]];
+
+        // 接受切片，返回行优先的第一个匹配项
+        assert_eq!(find_in_2d_vector(&layers[..], &2), Some((0, 1)));
+        assert_eq!(find_in_2d_vector(&layers[2..], &2), Some((0, 1)));
+        assert_eq!(find_in_2d_vector(&layers, &5), None);
+
+        assert_eq!(
+            find_all_in_2d_vector(&layers, &2),
+            vec![(0, 1), (2, 1), (3, 0)]
+        );
+        assert_eq!(find_all_in_2d_vector(&layers, &4), vec![(2, 0)]);
+        assert!(find_all_in_2d_vector(&layers, &5).is_empty());
+    }
 }
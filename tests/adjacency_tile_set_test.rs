@@ -0,0 +1,158 @@
+/**
+ * @file adjacency_tile_set_test.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief 使用AdjacencyTileSet复现正交2D示例中SquareTileSet的生成结果
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+mod common;
+
+use common::{Orthogonal2DGridBuilder, SquareTileSet};
+use rlwfc::{
+    learn_adjacency_from_sample, AdjacencyTileSet, CellId, DefaultInitializer, Direction4Full,
+    GridSystem, TieBreak, TileId, TileSetVirtual, WfcConfig, WfcError, WfcManager,
+};
+use std::collections::HashMap;
+
+/// 与SquareTileSet相同的瓷砖，只使用AdjacencyTileSet内置的边匹配规则
+fn adjacency_square_tiles() -> AdjacencyTileSet<i32, Direction4Full> {
+    let mut tile_set = AdjacencyTileSet::new();
+    tile_set.add_tile(vec![0, 0, 0, 0], 1);
+    tile_set.add_tile(vec![1, 0, 1, 0], 1);
+    tile_set.add_tile(vec![0, 1, 0, 1], 1);
+    tile_set.add_tile(vec![1, 1, 1, 0], 1);
+    tile_set.add_tile(vec![1, 0, 1, 1], 1);
+    tile_set.add_tile(vec![0, 1, 1, 1], 1);
+    tile_set.add_tile(vec![1, 1, 0, 1], 1);
+    tile_set.add_tile(vec![1, 1, 1, 1], 1);
+    tile_set
+}
+
+/// 在固定种子下运行，返回每个真实单元格的瓷砖
+fn generate(
+    tile_set: Box<dyn TileSetVirtual<i32>>,
+    seed: u64,
+) -> Result<Vec<(CellId, TileId)>, WfcError> {
+    let grid = GridSystem::from_builder(Orthogonal2DGridBuilder::new(6, 6)).unwrap();
+    let config = WfcConfig {
+        random_seed: Some(seed),
+        tie_break: TieBreak::SpatialHash,
+        ..WfcConfig::default()
+    };
+    let mut manager = WfcManager::with_config(grid, tile_set, config)?;
+    manager.initialize_with(&mut DefaultInitializer)?;
+    manager.run()?;
+
+    let grid = manager.get_grid();
+    let mut cells: Vec<CellId> = grid
+        .get_all_cells()
+        .filter(|&cell| !grid.is_virtual_node(cell))
+        .collect();
+    cells.sort();
+    cells
+        .into_iter()
+        .map(|cell| Ok((cell, manager.get_collapsed_cell_tile(cell)?)))
+        .collect()
+}
+
+#[test]
+fn test_adjacency_tile_set_matches_square_tile_set() {
+    for seed in 0..8 {
+        let expected = generate(Box::new(SquareTileSet::new()), seed);
+        let actual = generate(Box::new(adjacency_square_tiles()), seed);
+        assert_eq!(actual, expected, "seed {}", seed);
+    }
+}
+
+#[test]
+fn test_asymmetric_compatibility_propagation() {
+    // 边0只能连接边1（反之亦然），唯一的合法结果是棋盘格
+    let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::with_compatibility(|a, b| a != b);
+    tile_set.add_tile(vec![0, 0, 0, 0], 1);
+    tile_set.add_tile(vec![1, 1, 1, 1], 1);
+
+    let (width, height) = (5, 4);
+    let grid = GridSystem::from_builder(Orthogonal2DGridBuilder::new(width, height)).unwrap();
+    let config = WfcConfig {
+        random_seed: Some(3),
+        ..WfcConfig::default()
+    };
+    let mut manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+    manager.initialize_with(&mut DefaultInitializer).unwrap();
+
+    // 确定第一个单元格后，传播应当决定其余所有单元格
+    let origin = manager.get_grid().get_cell_at(&[0, 0]).unwrap();
+    manager.pre_collapse(origin, 0).unwrap();
+    manager.run().unwrap();
+
+    let grid = manager.get_grid();
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            let cell = grid.get_cell_at(&[x, y]).unwrap();
+            let tile = manager.get_collapsed_cell_tile(cell).unwrap();
+            assert_eq!(tile, ((x + y) % 2) as TileId, "cell ({}, {})", x, y);
+        }
+    }
+}
+
+#[test]
+fn test_learn_adjacency_from_striped_sample() {
+    // 竖条纹样例：列交替为瓷砖0和瓷砖1
+    let (width, height) = (4, 3);
+    let sample_grid =
+        GridSystem::from_builder(Orthogonal2DGridBuilder::new(width, height)).unwrap();
+    let mut sample = HashMap::new();
+    for y in 0..height {
+        for x in 0..width {
+            let cell = sample_grid
+                .get_cell_by_name(&format!("cell_{}_{}", x, y))
+                .unwrap();
+            sample.insert(cell, x % 2);
+        }
+    }
+
+    let tile_set = learn_adjacency_from_sample::<Direction4Full>(&sample_grid, &sample);
+    assert_eq!(tile_set.get_all_tile_ids(), vec![0, 1]);
+    assert_eq!(tile_set.tiles().get_tile(0).unwrap().weight, 6);
+
+    // 邻居顺序为[北, 西, 南, 东]，样例中瓷砖0的东侧从未出现瓷砖0
+    let mut neighbors = vec![vec![]; 4];
+    neighbors[3] = vec![0];
+    assert!(!tile_set.judge_possibility(&neighbors, 0));
+    assert!(tile_set.judge_possibility(&neighbors, 1));
+
+    // 竖直方向上只出现过相同的瓷砖
+    let mut neighbors = vec![vec![]; 4];
+    neighbors[2] = vec![1];
+    assert!(tile_set.judge_possibility(&neighbors, 1));
+    assert!(!tile_set.judge_possibility(&neighbors, 0));
+
+    // 在更大的网格上生成，结果仍然是竖条纹
+    let (width, height) = (7, 5);
+    let grid = GridSystem::from_builder(Orthogonal2DGridBuilder::new(width, height)).unwrap();
+    let config = WfcConfig {
+        random_seed: Some(11),
+        ..WfcConfig::default()
+    };
+    let mut manager = WfcManager::with_config(grid, Box::new(tile_set), config).unwrap();
+    manager.initialize_with(&mut DefaultInitializer).unwrap();
+    manager.run().unwrap();
+
+    let grid = manager.get_grid();
+    let tile_at = |x: usize, y: usize| {
+        let cell = grid.get_cell_by_name(&format!("cell_{}_{}", x, y)).unwrap();
+        manager.get_collapsed_cell_tile(cell).unwrap()
+    };
+    for y in 0..height {
+        for x in 0..width {
+            if x + 1 < width {
+                assert_ne!(tile_at(x, y), tile_at(x + 1, y), "cell ({}, {})", x, y);
+            }
+            if y + 1 < height {
+                assert_eq!(tile_at(x, y), tile_at(x, y + 1), "cell ({}, {})", x, y);
+            }
+        }
+    }
+}
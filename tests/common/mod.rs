@@ -0,0 +1,15 @@
+#![allow(dead_code, unused_imports)]
+
+/**
+ * @file common/mod.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief 集成测试共享组件，直接复用orthogonal_2d_wfc示例中的网格构建器和瓷砖集
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+#[path = "../../examples/common/orthogonal_2d.rs"]
+mod orthogonal_2d;
+
+pub use orthogonal_2d::{Orthogonal2DGridBuilder, SquareTileSet};
@@ -89,7 +89,7 @@ fn test_direction_system_enhancement() {
     let mut grid = GridSystem::new();
 
     // 创建2x2网格，模拟C++中的网格构建
-    let cells = vec![
+    let cells = [
         vec![
             grid.add_cell(Cell::with_id(0)),
             grid.add_cell(Cell::with_id(1)),
@@ -162,7 +162,7 @@ fn test_wfc_manager_data_structures() {
     let cell_data = &wfc_cell_data[&test_cell];
     
     // 模拟权重计算和随机数使用
-    let tile_weights = vec![10, 15, 5]; // 假设的瓷砖权重
+    let tile_weights = [10, 15, 5]; // 假设的瓷砖权重
     let total_weight: i32 = tile_weights.iter().sum();
     let rand_num_mod = cell_data.rand_num % total_weight; // C++: randNum %= weightSum
     
@@ -288,7 +288,7 @@ fn test_overall_compatibility() {
     let neighbors = grid.get_neighbors(center);
 
     // 中心单元格应该有东向和南向邻居
-    assert!(neighbors.len() >= 1);
+    assert!(!neighbors.is_empty());
 
     // 5. 验证所有API都能正常工作
     assert!(grid.validate_structure().is_ok());
@@ -0,0 +1,26 @@
+/**
+ * @file fuzz_test.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief 多种子鲁棒性测试，使用testing模块运行正交2D示例瓷砖集
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+mod common;
+
+use common::{Orthogonal2DGridBuilder, SquareTileSet};
+use rlwfc::testing::fuzz_tileset;
+
+#[test]
+fn test_fuzz_orthogonal_tileset() {
+    let summary = fuzz_tileset(
+        SquareTileSet::new,
+        || Orthogonal2DGridBuilder::new(5, 5),
+        0..32,
+    );
+
+    assert_eq!(summary.runs, 32);
+    assert_eq!(summary.successes + summary.failures, summary.runs);
+    assert!(summary.success_rate() >= 0.8);
+}
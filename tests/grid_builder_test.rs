@@ -0,0 +1,217 @@
+/**
+ * @file grid_builder_test.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief GridBuilder集成测试，使用正交2D网格构建器验证构建期元数据
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+mod common;
+
+use common::Orthogonal2DGridBuilder;
+use rlwfc::{
+    AdjacencyTileSet, ChainBuilder, Connectivity, DefaultInitializer, Direction4Full, Direction8,
+    DirectionTrait, GridBuilder, GridBuilder2D, GridError, GridSystem, TileSetVirtual, WfcConfig,
+    WfcError, WfcManager,
+};
+
+/// 按坐标为已有单元格设置显示名称的构建器
+struct LabelBuilder;
+
+impl GridBuilder for LabelBuilder {
+    fn build_grid_system(&mut self, grid: &mut GridSystem) -> Result<(), GridError> {
+        let cells: Vec<_> = grid.get_all_cells().collect();
+        for cell_id in cells {
+            let Some(label) = grid
+                .get_cell_coords(cell_id)
+                .map(|coords| format!("label_{}_{}", coords[0], coords[1]))
+            else {
+                continue;
+            };
+            grid.get_cell_mut(cell_id).unwrap().name = Some(label);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_builder_populates_coordinates() {
+    let grid = GridSystem::from_builder(Orthogonal2DGridBuilder::new(3, 2)).unwrap();
+
+    let cell = grid.get_cell_at(&[2, 1]).unwrap();
+    assert_eq!(grid.get_cell_by_name("cell_2_1"), Some(cell));
+    assert_eq!(grid.get_cell_coords(cell), Some(&[2, 1][..]));
+
+    // 虚拟节点没有坐标
+    assert!(grid.get_cell_at(&[3, 1]).is_none());
+}
+
+#[test]
+fn test_chain_builder_applies_builders_in_order() {
+    let builder = ChainBuilder::new()
+        .then(Orthogonal2DGridBuilder::new(3, 2))
+        .then(LabelBuilder);
+    assert_eq!(builder.get_dimensions(), vec![3, 2]);
+    let grid = GridSystem::from_builder(builder).unwrap();
+
+    // 第一个构建器的单元格、边和坐标都保留
+    let cell = grid.get_cell_at(&[2, 1]).unwrap();
+    assert_eq!(grid.get_cell_by_name("cell_2_1"), Some(cell));
+    assert_eq!(grid.get_neighbors(cell).len(), 4);
+    assert!(grid.validate_structure().is_ok());
+
+    // 第二个构建器看到了坐标并设置了名称
+    assert_eq!(
+        grid.get_cell(cell).unwrap().name.as_deref(),
+        Some("label_2_1")
+    );
+    let labeled = grid
+        .get_all_cells()
+        .filter_map(|cell_id| grid.get_cell(cell_id).unwrap().name.as_deref())
+        .filter(|name| name.starts_with("label_"))
+        .count();
+    assert_eq!(labeled, 6);
+}
+
+#[test]
+fn test_grid_builder_2d_connectivity() {
+    let real_neighbors = |grid: &GridSystem, x: i32, y: i32| {
+        let cell = grid.get_cell_at(&[x, y]).unwrap();
+        grid.get_neighbors(cell)
+            .into_iter()
+            .filter(|&neighbor| !grid.is_virtual_node(neighbor))
+            .count()
+    };
+
+    // 八邻接：内部8个、边上5个、角上3个真实邻居，虚拟节点补齐到8条边
+    let builder = GridBuilder2D::new(4, 3, Connectivity::Eight);
+    assert_eq!(builder.get_dimensions(), vec![4, 3]);
+    let (nodes, edges) = builder.estimated_capacity();
+    let grid = GridSystem::from_builder(builder).unwrap();
+    assert_eq!(grid.get_cells_count(), nodes);
+    assert_eq!(grid.get_edges_count(), edges);
+    assert!(grid.validate_structure().is_ok());
+    assert_eq!(real_neighbors(&grid, 1, 1), 8);
+    assert_eq!(real_neighbors(&grid, 2, 0), 5);
+    assert_eq!(real_neighbors(&grid, 0, 1), 5);
+    assert_eq!(real_neighbors(&grid, 3, 2), 3);
+
+    let center = grid.get_cell_at(&[1, 1]).unwrap();
+    for direction in Direction8::all_directions() {
+        let (dx, dy) = direction.offset();
+        assert_eq!(
+            grid.get_neighbor_by_direction(center, direction),
+            grid.get_cell_at(&[1 + dx, 1 + dy])
+        );
+    }
+
+    // 角上的对角方向指向虚拟节点，正交方向仍然解析为真实单元格
+    let corner = grid.get_cell_at(&[0, 0]).unwrap();
+    for direction in [
+        Direction8::NorthWest,
+        Direction8::NorthEast,
+        Direction8::SouthWest,
+    ] {
+        let neighbor = grid.get_neighbor_by_direction(corner, direction).unwrap();
+        assert!(grid.is_virtual_node(neighbor));
+    }
+    assert_eq!(
+        grid.get_neighbor_by_direction(corner, Direction8::SouthEast),
+        Some(center)
+    );
+    assert_eq!(
+        grid.get_neighbor_by_direction(corner, Direction8::East),
+        grid.get_cell_at(&[1, 0])
+    );
+
+    // 四邻接：邻居顺序为[北, 西, 南, 东]，与四边瓷砖的边数据一致
+    let grid = GridSystem::from_builder(GridBuilder2D::new(4, 3, Connectivity::Four)).unwrap();
+    assert_eq!(real_neighbors(&grid, 1, 1), 4);
+    assert_eq!(real_neighbors(&grid, 2, 0), 3);
+    assert_eq!(real_neighbors(&grid, 3, 2), 2);
+    let center = grid.get_cell_at(&[1, 1]).unwrap();
+    let expected: Vec<_> = [[1, 0], [0, 1], [1, 2], [2, 1]]
+        .iter()
+        .map(|coords| grid.get_cell_at(coords).unwrap())
+        .collect();
+    assert_eq!(grid.get_neighbors(center), expected);
+    for direction in Direction4Full::all_directions() {
+        let index = direction.to_neighbor_index().unwrap();
+        assert_eq!(
+            grid.get_neighbor_by_direction(center, direction),
+            Some(expected[index])
+        );
+    }
+}
+
+#[test]
+fn test_grid_builder_2d_four_wfc_respects_adjacency() {
+    // 陆地与水面不能直接相邻，必须经过海岸瓷砖过渡
+    let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+    let land = tile_set.add_tile(vec![0, 0, 0, 0], 1);
+    let water = tile_set.add_tile(vec![1, 1, 1, 1], 1);
+    tile_set.add_tile(vec![0, 1, 0, 1], 1);
+    tile_set.add_tile(vec![1, 0, 1, 0], 1);
+    assert!(!tile_set.judge_possibility(&[vec![water], vec![], vec![], vec![]], land));
+
+    for seed in 0..10 {
+        let grid = GridSystem::from_builder(GridBuilder2D::new(6, 6, Connectivity::Four)).unwrap();
+        let config = WfcConfig {
+            random_seed: Some(seed),
+            ..WfcConfig::default()
+        };
+        let mut manager =
+            WfcManager::with_config(grid, Box::new(tile_set.clone()), config).unwrap();
+        manager.initialize_with(&mut DefaultInitializer).unwrap();
+        manager.run().unwrap();
+
+        // 每对相邻单元格的边数据都必须匹配：第i个邻居对应边i，邻居的相反边为(i + 2) % 4
+        let grid = manager.get_grid();
+        let mut checked_pairs = 0;
+        for cell in grid
+            .get_all_cells()
+            .filter(|&cell| !grid.is_virtual_node(cell))
+        {
+            let tile = manager.get_collapsed_cell_tile(cell).unwrap();
+            let edges = &tile_set.get_tile(tile).unwrap().edges;
+            for (index, neighbor) in grid.get_neighbors(cell).into_iter().enumerate() {
+                if grid.is_virtual_node(neighbor) {
+                    continue;
+                }
+                let neighbor_tile = manager.get_collapsed_cell_tile(neighbor).unwrap();
+                let neighbor_edges = &tile_set.get_tile(neighbor_tile).unwrap().edges;
+                assert_eq!(
+                    edges[index],
+                    neighbor_edges[(index + 2) % 4],
+                    "seed {}: {:?} -> {:?}",
+                    seed,
+                    cell,
+                    neighbor
+                );
+                checked_pairs += 1;
+            }
+        }
+        // 6x6网格的每条相邻关系都从两侧各检查一次
+        assert_eq!(checked_pairs, 2 * 2 * 6 * 5);
+    }
+}
+
+#[test]
+fn test_adjacency_tile_set_rejects_mismatched_grid() {
+    // 四边瓷砖不能用于八邻接网格
+    let mut tile_set = AdjacencyTileSet::<i32, Direction4Full>::new();
+    tile_set.add_tile(vec![0, 0, 0, 0], 1);
+    let grid = GridSystem::from_builder(GridBuilder2D::new(3, 3, Connectivity::Eight)).unwrap();
+    let first = grid.get_cell_at(&[0, 0]).unwrap();
+
+    let mut manager = WfcManager::new(grid, Box::new(tile_set)).unwrap();
+    assert_eq!(
+        manager.initialize_with(&mut DefaultInitializer),
+        Err(WfcError::NeighborCountMismatch {
+            cell: first,
+            neighbor_count: 8,
+            expected: 4,
+        })
+    );
+}
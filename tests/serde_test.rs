@@ -0,0 +1,90 @@
+#![cfg(feature = "serde")]
+
+/**
+ * @file serde_test.rs
+ * @author amazcuter (amazcuter@outlook.com)
+ * @brief serde特性下的JSON往返测试
+ * @version 1.0
+ * @date 2025-01-25
+ *
+ * @copyright Copyright (c) 2025
+ */
+mod common;
+
+use common::Orthogonal2DGridBuilder;
+use rlwfc::{Direction4Full, DirectionTrait, GridSystem, TileId, TileSet};
+
+#[test]
+fn test_grid_json_round_trip() {
+    let grid = GridSystem::from_builder(Orthogonal2DGridBuilder::new(3, 3)).unwrap();
+    let json = serde_json::to_string(&grid).unwrap();
+    let restored: GridSystem = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.get_cells_count(), grid.get_cells_count());
+    assert_eq!(restored.get_edges_count(), grid.get_edges_count());
+    for cell in grid.get_all_cells() {
+        assert_eq!(restored.get_neighbors(cell), grid.get_neighbors(cell));
+        assert_eq!(restored.is_virtual_node(cell), grid.is_virtual_node(cell));
+        assert_eq!(restored.get_cell_coords(cell), grid.get_cell_coords(cell));
+    }
+
+    // 名称、坐标和方向查询都保持不变
+    let center = restored.get_cell_by_name("cell_1_1").unwrap();
+    assert_eq!(restored.get_cell_at(&[1, 1]), Some(center));
+    for direction in Direction4Full::all_directions() {
+        assert_eq!(
+            restored.get_neighbor_by_direction(center, direction),
+            grid.get_neighbor_by_direction(center, direction)
+        );
+    }
+
+    // 再次序列化得到相同的JSON
+    assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+}
+
+#[test]
+fn test_grid_rejects_invalid_edges() {
+    let grid = GridSystem::from_builder(Orthogonal2DGridBuilder::new(2, 2)).unwrap();
+    let mut value = serde_json::to_value(&grid).unwrap();
+    value["edges"][0][1] = serde_json::json!(1000);
+
+    let result: Result<GridSystem, _> = serde_json::from_value(value);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_tile_set_from_json() {
+    let json = r#"{
+        "tiles": [
+            { "id": 0, "weight": 5, "edges": ["grass", "grass", "grass", "grass"] },
+            { "id": 1, "weight": 2, "edges": ["road", "grass", "road", "grass"] },
+            { "id": 2, "weight": 1, "edges": ["grass", "water", "grass", "water"] }
+        ]
+    }"#;
+    let tile_set: TileSet<String> = serde_json::from_str(json).unwrap();
+
+    assert_eq!(tile_set.get_all_tile_ids(), (0..3).collect::<Vec<TileId>>());
+    let road = tile_set.get_tile(1).unwrap();
+    assert_eq!(road.weight, 2);
+    assert_eq!(road.edges, ["road", "grass", "road", "grass"]);
+    assert_eq!(tile_set.get_tile(2).unwrap().edges[1], "water");
+
+    // 序列化后再加载得到相同的瓷砖
+    let restored: TileSet<String> =
+        serde_json::from_str(&serde_json::to_string(&tile_set).unwrap()).unwrap();
+    for tile_id in tile_set.get_all_tile_ids() {
+        assert_eq!(restored.get_tile(tile_id), tile_set.get_tile(tile_id));
+    }
+}
+
+#[test]
+fn test_tile_set_rejects_non_contiguous_ids() {
+    let json = r#"{
+        "tiles": [
+            { "id": 0, "weight": 1, "edges": [0, 0, 0, 0] },
+            { "id": 2, "weight": 1, "edges": [1, 1, 1, 1] }
+        ]
+    }"#;
+    let result: Result<TileSet<i32>, _> = serde_json::from_str(json);
+    assert!(result.unwrap_err().to_string().contains("non-contiguous"));
+}